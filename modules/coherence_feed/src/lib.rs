@@ -0,0 +1,338 @@
+//! The wave-coherence feed between the Ripple Tank and its consumers.
+//!
+//! A producer publishes a [`CoherenceSample`] (value, timestamp, frame and source) as one
+//! JSON object, replaced atomically so a reader never sees half a sample. Readers get the
+//! sample back typed, and can tell "nobody is producing" and "the producer went quiet"
+//! apart from a genuinely low coherence.
+//!
+//! The plain-text `/dev/shm/current_wave_coherence` is still written alongside for shell
+//! and Python consumers, and read as a fallback (aged by its mtime) for producers that
+//! only speak text.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Where the typed feed lives unless `WAVE_COHERENCE_FEED` says otherwise.
+pub const FEED_PATH: &str = "/dev/shm/wave_coherence_feed";
+/// The untyped predecessor: a bare decimal number.
+pub const LEGACY_PATH: &str = "/dev/shm/current_wave_coherence";
+/// Source id given to samples read from the legacy file.
+pub const LEGACY_SOURCE: &str = "legacy";
+/// The Ripple Tank publishes every ~100 ms; twenty missed frames is a stall.
+pub const DEFAULT_MAX_AGE: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CoherenceSample {
+    pub value: f64,
+    /// Wall-clock time of publication (ns since the Unix epoch).
+    pub timestamp_ns: u64,
+    /// The producer's frame number; 0 for legacy samples.
+    pub frame: u64,
+    pub source: String,
+}
+
+impl CoherenceSample {
+    /// A sample stamped now.
+    pub fn new(value: f64, frame: u64, source: impl Into<String>) -> Self {
+        Self {
+            value,
+            timestamp_ns: to_ns(SystemTime::now()),
+            frame,
+            source: source.into(),
+        }
+    }
+
+    pub fn timestamp(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_nanos(self.timestamp_ns)
+    }
+
+    /// Time since publication (zero if the producer's clock is ahead of ours).
+    pub fn age(&self) -> Duration {
+        SystemTime::now()
+            .duration_since(self.timestamp())
+            .unwrap_or_default()
+    }
+}
+
+fn to_ns(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as u64)
+}
+
+/// Why no usable sample could be read.
+#[derive(Debug)]
+pub enum FeedError {
+    /// Neither the feed nor the legacy file exists.
+    NoProducer,
+    /// The last sample is older than allowed.
+    Stale {
+        sample: CoherenceSample,
+        age: Duration,
+        max_age: Duration,
+    },
+    /// The file exists but does not hold a sample.
+    Malformed {
+        path: PathBuf,
+        reason: String,
+    },
+    Io(io::Error),
+}
+
+impl fmt::Display for FeedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FeedError::NoProducer => write!(f, "no coherence producer"),
+            FeedError::Stale {
+                sample,
+                age,
+                max_age,
+            } => write!(
+                f,
+                "coherence from {} frame {} is {} ms old (limit {} ms)",
+                sample.source,
+                sample.frame,
+                age.as_millis(),
+                max_age.as_millis()
+            ),
+            FeedError::Malformed { path, reason } => {
+                write!(f, "{} is malformed: {}", path.display(), reason)
+            }
+            FeedError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for FeedError {}
+
+impl From<io::Error> for FeedError {
+    fn from(e: io::Error) -> Self {
+        FeedError::Io(e)
+    }
+}
+
+/// `WAVE_COHERENCE_FEED`, or [`FEED_PATH`].
+pub fn default_path() -> PathBuf {
+    std::env::var_os("WAVE_COHERENCE_FEED")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(FEED_PATH))
+}
+
+/// The producing end.
+pub struct CoherencePublisher {
+    path: PathBuf,
+    legacy_path: Option<PathBuf>,
+    source: String,
+}
+
+impl CoherencePublisher {
+    /// Publishes to the default feed, mirroring the value into the legacy file.
+    pub fn new(source: impl Into<String>) -> Self {
+        Self {
+            path: default_path(),
+            legacy_path: Some(PathBuf::from(LEGACY_PATH)),
+            source: source.into(),
+        }
+    }
+
+    pub fn with_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.path = path.into();
+        self
+    }
+
+    /// Where to mirror the bare value, or `None` to stop writing it.
+    pub fn with_legacy(mut self, legacy_path: Option<PathBuf>) -> Self {
+        self.legacy_path = legacy_path;
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn publish(&self, value: f64, frame: u64) -> io::Result<CoherenceSample> {
+        let sample = CoherenceSample::new(value, frame, self.source.clone());
+        let json = serde_json::to_vec(&sample)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        write_atomic(&self.path, &json)?;
+        if let Some(legacy) = &self.legacy_path {
+            write_atomic(legacy, format!("{:.4}", value).as_bytes())?;
+        }
+        Ok(sample)
+    }
+}
+
+/// Writes via a temporary sibling and a rename, so readers see old or new, never half.
+fn write_atomic(path: &Path, data: &[u8]) -> io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    fs::write(&tmp, data)?;
+    fs::rename(&tmp, path)
+}
+
+/// The consuming end.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoherenceFeed {
+    path: PathBuf,
+    legacy_path: Option<PathBuf>,
+    max_age: Duration,
+}
+
+impl Default for CoherenceFeed {
+    fn default() -> Self {
+        Self {
+            path: default_path(),
+            legacy_path: Some(PathBuf::from(LEGACY_PATH)),
+            max_age: DEFAULT_MAX_AGE,
+        }
+    }
+}
+
+impl CoherenceFeed {
+    pub fn at(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            ..Self::default()
+        }
+    }
+
+    /// The legacy file to fall back on, or `None` to accept only typed samples.
+    pub fn with_legacy(mut self, legacy_path: Option<PathBuf>) -> Self {
+        self.legacy_path = legacy_path;
+        self
+    }
+
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    pub fn max_age(&self) -> Duration {
+        self.max_age
+    }
+
+    /// The newest sample, however old.
+    pub fn latest(&self) -> Result<CoherenceSample, FeedError> {
+        match fs::read(&self.path) {
+            Ok(json) => serde_json::from_slice(&json).map_err(|e| FeedError::Malformed {
+                path: self.path.clone(),
+                reason: e.to_string(),
+            }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => match &self.legacy_path {
+                Some(legacy) => read_legacy(legacy),
+                None => Err(FeedError::NoProducer),
+            },
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// The newest sample, refused as [`FeedError::Stale`] once older than the limit.
+    pub fn read(&self) -> Result<CoherenceSample, FeedError> {
+        let sample = self.latest()?;
+        let age = sample.age();
+        if age > self.max_age {
+            return Err(FeedError::Stale {
+                sample,
+                age,
+                max_age: self.max_age,
+            });
+        }
+        Ok(sample)
+    }
+}
+
+fn read_legacy(path: &Path) -> Result<CoherenceSample, FeedError> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Err(FeedError::NoProducer),
+        Err(e) => return Err(e.into()),
+    };
+    let value = text
+        .trim()
+        .parse::<f64>()
+        .map_err(|e| FeedError::Malformed {
+            path: path.to_path_buf(),
+            reason: format!("{:?}: {}", text.trim(), e),
+        })?;
+    let modified = fs::metadata(path)?.modified()?;
+    Ok(CoherenceSample {
+        value,
+        timestamp_ns: to_ns(modified),
+        frame: 0,
+        source: LEGACY_SOURCE.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Feed and legacy paths in a fresh directory, removed when the `TempDir` drops.
+    fn scratch() -> (TempDir, PathBuf, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let (feed, legacy) = (dir.path().join("feed"), dir.path().join("legacy"));
+        (dir, feed, legacy)
+    }
+
+    #[test]
+    fn test_published_samples_read_back_typed() {
+        let (_dir, feed_path, legacy_path) = scratch();
+        let feed = CoherenceFeed::at(&feed_path).with_legacy(Some(legacy_path.clone()));
+        assert!(matches!(feed.read(), Err(FeedError::NoProducer)));
+
+        let publisher = CoherencePublisher::new("ripple_tank_core")
+            .with_path(&feed_path)
+            .with_legacy(Some(legacy_path.clone()));
+        let sent = publisher.publish(0.0125, 42).unwrap();
+
+        // A low value is still a live reading.
+        let got = feed.read().unwrap();
+        assert_eq!(got, sent);
+        assert_eq!(got.frame, 42);
+        assert_eq!(got.source, "ripple_tank_core");
+        assert!(got.age() < DEFAULT_MAX_AGE);
+        assert_eq!(fs::read_to_string(&legacy_path).unwrap(), "0.0125");
+    }
+
+    #[test]
+    fn test_old_samples_are_stale_and_garbage_is_malformed() {
+        let (_dir, feed_path, _) = scratch();
+        let mut sample = CoherenceSample::new(1.2, 7, "ripple_tank_core");
+        sample.timestamp_ns -= 5_000_000_000;
+        fs::write(&feed_path, serde_json::to_vec(&sample).unwrap()).unwrap();
+
+        let feed = CoherenceFeed::at(&feed_path).with_legacy(None);
+        match feed.read() {
+            Err(FeedError::Stale { sample: s, age, .. }) => {
+                assert_eq!(s, sample);
+                assert!(age >= Duration::from_secs(5));
+            }
+            other => panic!("expected stale, got {:?}", other),
+        }
+        assert_eq!(feed.latest().unwrap(), sample);
+        let relaxed = feed.clone().with_max_age(Duration::from_secs(60));
+        assert_eq!(relaxed.read().unwrap().value, 1.2);
+
+        fs::write(&feed_path, b"1.2").unwrap();
+        assert!(matches!(feed.read(), Err(FeedError::Malformed { .. })));
+    }
+
+    #[test]
+    fn test_legacy_text_is_a_fallback_aged_by_mtime() {
+        let (_dir, feed_path, legacy_path) = scratch();
+        fs::write(&legacy_path, "1.1000").unwrap();
+        let feed = CoherenceFeed::at(&feed_path).with_legacy(Some(legacy_path.clone()));
+
+        let sample = feed.read().unwrap();
+        assert_eq!(sample.value, 1.1);
+        assert_eq!(sample.source, LEGACY_SOURCE);
+        assert!(sample.age() < Duration::from_secs(60));
+
+        fs::write(&legacy_path, "warp").unwrap();
+        assert!(matches!(feed.read(), Err(FeedError::Malformed { .. })));
+    }
+}
@@ -1,4 +1,7 @@
 use crew_core::Talu64;
+use spectral_sensor::sonify::{Modulation, SampleFormat, Sonifier, SonifyConfig};
+use spectral_sensor::NvmeWind;
+use std::path::Path;
 
 struct Brook {
     _soul_frequency: f64,
@@ -34,9 +37,47 @@ impl Brook {
             println!("   [Brook Status] Field Inactive. Just humming...");
         }
     }
+
+    /// Renders the Wind onto the 432Hz carrier. `--fm` sweeps the pitch instead of the
+    /// volume, `--stereo` pans by density, `--float` writes 32-bit float samples.
+    fn record_binks_sake(&self, path: &Path, flags: &[String]) -> std::io::Result<()> {
+        let has = |flag: &str| flags.iter().any(|f| f == flag);
+        let sonifier = Sonifier::new(SonifyConfig {
+            modulation: if has("--fm") {
+                Modulation::Frequency { deviation_hz: 48.0 }
+            } else {
+                Modulation::Amplitude
+            },
+            format: if has("--float") {
+                SampleFormat::Float32
+            } else {
+                SampleFormat::Pcm16
+            },
+            stereo: has("--stereo"),
+            ..SonifyConfig::default()
+        });
+
+        let mut wind = NvmeWind::new(874_000);
+        let samples = wind.blast(1000); // 10 seconds at 100 values/sec
+        sonifier.save(path, &samples)?;
+        println!(
+            "   [Brook Status] Recorded {:.1}s of Wind to {}",
+            sonifier.duration(samples.len()),
+            path.display()
+        );
+        Ok(())
+    }
 }
 
 fn main() {
     let musician = Brook::new();
     musician.play_binks_sake();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(path) = args.first() {
+        if let Err(e) = musician.record_binks_sake(Path::new(path), &args[1..]) {
+            eprintln!("   [Brook Status] Recording failed: {}", e);
+            std::process::exit(1);
+        }
+    }
 }
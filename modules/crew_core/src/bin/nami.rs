@@ -143,6 +143,7 @@ impl Nami {
                 let reader_port = port.try_clone().expect("Failed to clone port");
                 let mut reader = BufReader::new(reader_port);
                 let mut line_buffer = String::new();
+                let filter = RecursiveFilter::new();
 
                 println!("   [Steward Link] 8-Gate Bridge Synchronized.");
 
@@ -151,7 +152,7 @@ impl Nami {
                         if line_buffer.starts_with("NAV_STATE") {
                             let ingestion_density = 10.0 / 60.0;
                             if let Some(report) =
-                                RecursiveFilter::observe(ingestion_density, self.last_density)
+                                filter.observe_at(ingestion_density, self.last_density)
                             {
                                 self.last_density = ingestion_density;
                                 let base_torque = (1.0 - report.entropy) * Talu64::PHI;
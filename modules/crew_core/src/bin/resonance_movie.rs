@@ -1,5 +1,4 @@
-use i2cdev::core::*;
-use i2cdev::linux::LinuxI2CDevice;
+use crew_core::i2c::{I2cBus, SenseHatSensors};
 use std::error::Error;
 use std::fs;
 use std::io::{self, Seek, Write};
@@ -9,20 +8,8 @@ use std::time::Duration;
 // --- Constants ---
 const TAU: f64 = 6.183; // Resonant Actualization
 const FB_PATH: &str = "/dev/fb0";
-const I2C_BUS: &str = "/dev/i2c-1";
 const MOVIE_DIR: &str = "movie";
 
-// Sensor Addresses
-const HTS221_ADDR: u16 = 0x5F;
-const HTS221_CTRL_REG1: u8 = 0x20;
-const HTS221_TEMP_OUT_L: u8 = 0x2A;
-const LPS25H_ADDR: u16 = 0x5C;
-const LPS25H_CTRL_REG1: u8 = 0x20;
-const LPS25H_PRESS_OUT_XL: u8 = 0x28;
-const LSM9DS1_GYRO_ADDR: u16 = 0x6A;
-const LSM9DS1_CTRL_REG1_G: u8 = 0x10;
-const LSM9DS1_OUT_X_L_G: u8 = 0x18;
-
 // --- Vector Struct ---
 #[derive(Debug, Clone, Copy)]
 struct Vector3 {
@@ -83,75 +70,29 @@ impl SenseHatFb {
     }
 }
 
-// --- Sensor Array (Static Helper Impl) ---
+// --- Sensor Drivers ---
 struct SensorArray {
-    hts: Option<LinuxI2CDevice>,
-    lps: Option<LinuxI2CDevice>,
-    gyro: Option<LinuxI2CDevice>,
+    sensors: SenseHatSensors<Box<dyn I2cBus>>,
 }
 
 impl SensorArray {
     fn new() -> Self {
-        let hts = LinuxI2CDevice::new(I2C_BUS, HTS221_ADDR).ok();
-        let lps = LinuxI2CDevice::new(I2C_BUS, LPS25H_ADDR).ok();
-        let gyro = LinuxI2CDevice::new(I2C_BUS, LSM9DS1_GYRO_ADDR).ok();
-        let mut sensors = Self { hts, lps, gyro };
-        sensors.init();
-        sensors
-    }
-
-    fn init(&mut self) {
-        if let Some(dev) = &mut self.hts {
-            let _ = dev.smbus_write_byte_data(HTS221_CTRL_REG1, 0x80);
-        }
-        if let Some(dev) = &mut self.lps {
-            let _ = dev.smbus_write_byte_data(LPS25H_CTRL_REG1, 0x90);
+        Self {
+            sensors: SenseHatSensors::open_default(),
         }
-        if let Some(dev) = &mut self.gyro {
-            let _ = dev.smbus_write_byte_data(LSM9DS1_CTRL_REG1_G, 0x20);
-        }
-    }
-
-    fn read_i16_static(dev: &mut LinuxI2CDevice, reg: u8) -> Result<i16, Box<dyn Error>> {
-        let lo = dev.smbus_read_byte_data(reg)?;
-        let hi = dev.smbus_read_byte_data(reg + 1)?;
-        Ok(i16::from_le_bytes([lo, hi]))
     }
 
     fn read_environment(&mut self) -> Vector3 {
-        let temp = self
-            .hts
-            .as_mut()
-            .and_then(|d| Self::read_i16_static(d, HTS221_TEMP_OUT_L).ok())
-            .unwrap_or(2500) as f64
-            / 100.0;
-        let press = self
-            .lps
-            .as_mut()
-            .and_then(|d| {
-                let xl = d.smbus_read_byte_data(LPS25H_PRESS_OUT_XL).ok()? as u32;
-                let l = d.smbus_read_byte_data(LPS25H_PRESS_OUT_XL + 1).ok()? as u32;
-                let h = d.smbus_read_byte_data(LPS25H_PRESS_OUT_XL + 2).ok()? as u32;
-                Some(((h << 16) | (l << 8) | xl) as f64 / 4096.0)
-            })
-            .unwrap_or(1013.0);
-        let hum = self
-            .hts
-            .as_mut()
-            .and_then(|d| Self::read_i16_static(d, 0x28).ok())
-            .unwrap_or(4000) as f64
-            / 100.0;
+        let temp = self.sensors.read_temp().unwrap_or(25.0);
+        let press = self.sensors.read_pressure().unwrap_or(1013.0);
+        let hum = self.sensors.read_humidity().unwrap_or(40.0);
         Vector3::new(temp / 50.0, press / 1100.0, hum / 100.0)
     }
 
     fn read_inertial(&mut self) -> Vector3 {
-        if let Some(d) = &mut self.gyro {
-            let x = Self::read_i16_static(d, LSM9DS1_OUT_X_L_G).unwrap_or(0) as f64;
-            let y = Self::read_i16_static(d, LSM9DS1_OUT_X_L_G + 2).unwrap_or(0) as f64;
-            let z = Self::read_i16_static(d, LSM9DS1_OUT_X_L_G + 4).unwrap_or(0) as f64;
-            Vector3::new(x, y, z).normalize()
-        } else {
-            Vector3::new(0.0, 1.0, 0.0) // Mock
+        match self.sensors.read_gyro() {
+            Some([x, y, z]) => Vector3::new(x as f64, y as f64, z as f64).normalize(),
+            None => Vector3::new(0.0, 1.0, 0.0), // Mock
         }
     }
 }
@@ -1,29 +1,12 @@
-use i2cdev::core::*;
-use i2cdev::linux::LinuxI2CDevice;
+use crew_core::i2c::{I2cBus, SenseHatSensors};
 use std::error::Error;
 use std::fs;
 use std::io::{self, Seek, Write};
 use std::thread;
 use std::time::Duration;
 
-// --- Constants & Sensor Addresses ---
+// --- Constants ---
 const FB_PATH: &str = "/dev/fb0";
-const I2C_BUS: &str = "/dev/i2c-1";
-
-// HTS221 (Humidity/Temp)
-const HTS221_ADDR: u16 = 0x5F;
-const HTS221_CTRL_REG1: u8 = 0x20;
-const HTS221_TEMP_OUT_L: u8 = 0x2A;
-
-// LPS25H (Pressure)
-const LPS25H_ADDR: u16 = 0x5C;
-const LPS25H_CTRL_REG1: u8 = 0x20;
-const LPS25H_PRESS_OUT_XL: u8 = 0x28;
-
-// LSM9DS1 (IMU - Accel/Gyro/Mag)
-const LSM9DS1_GYRO_ADDR: u16 = 0x6A; // Gyro/Accel
-const LSM9DS1_CTRL_REG1_G: u8 = 0x10;
-const LSM9DS1_OUT_X_L_G: u8 = 0x18;
 
 // --- Vector Struct ---
 #[derive(Debug, Clone, Copy)]
@@ -89,79 +72,29 @@ impl SenseHatFb {
 
 // --- Sensor Drivers ---
 struct SensorArray {
-    hts: Option<LinuxI2CDevice>,
-    lps: Option<LinuxI2CDevice>,
-    gyro: Option<LinuxI2CDevice>,
+    sensors: SenseHatSensors<Box<dyn I2cBus>>,
 }
 
 impl SensorArray {
     fn new() -> Self {
-        let hts = LinuxI2CDevice::new(I2C_BUS, HTS221_ADDR).ok();
-        let lps = LinuxI2CDevice::new(I2C_BUS, LPS25H_ADDR).ok();
-        let gyro = LinuxI2CDevice::new(I2C_BUS, LSM9DS1_GYRO_ADDR).ok();
-
-        let mut sensors = Self { hts, lps, gyro };
-        sensors.init();
-        sensors
-    }
-
-    fn init(&mut self) {
-        if let Some(dev) = &mut self.hts {
-            let _ = dev.smbus_write_byte_data(HTS221_CTRL_REG1, 0x80);
-        }
-        if let Some(dev) = &mut self.lps {
-            let _ = dev.smbus_write_byte_data(LPS25H_CTRL_REG1, 0x90);
-        }
-        if let Some(dev) = &mut self.gyro {
-            let _ = dev.smbus_write_byte_data(LSM9DS1_CTRL_REG1_G, 0x20);
+        Self {
+            sensors: SenseHatSensors::open_default(),
         }
     }
 
-    // Static helper to avoid borrow conflict
-    fn read_i16_static(dev: &mut LinuxI2CDevice, reg: u8) -> Result<i16, Box<dyn Error>> {
-        let lo = dev.smbus_read_byte_data(reg)?;
-        let hi = dev.smbus_read_byte_data(reg + 1)?;
-        Ok(i16::from_le_bytes([lo, hi]))
-    }
-
     fn read_environment(&mut self) -> Vector3 {
-        let temp = self.read_temp().unwrap_or(25.0);
-        let press = self.read_pressure().unwrap_or(1013.0);
-        let hum = self.read_humidity().unwrap_or(40.0);
+        let temp = self.sensors.read_temp().unwrap_or(25.0);
+        let press = self.sensors.read_pressure().unwrap_or(1013.0);
+        let hum = self.sensors.read_humidity().unwrap_or(40.0);
         Vector3::new(temp / 50.0, press / 1100.0, hum / 100.0)
     }
 
     fn read_inertial(&mut self) -> Vector3 {
-        if let Some(dev) = &mut self.gyro {
-            let x = Self::read_i16_static(dev, LSM9DS1_OUT_X_L_G).unwrap_or(0) as f64;
-            let y = Self::read_i16_static(dev, LSM9DS1_OUT_X_L_G + 2).unwrap_or(0) as f64;
-            let z = Self::read_i16_static(dev, LSM9DS1_OUT_X_L_G + 4).unwrap_or(0) as f64;
-            Vector3::new(x, y, z).normalize()
-        } else {
-            Vector3::new(0.0, 1.0, 0.0)
+        match self.sensors.read_gyro() {
+            Some([x, y, z]) => Vector3::new(x as f64, y as f64, z as f64).normalize(),
+            None => Vector3::new(0.0, 1.0, 0.0), // Mock
         }
     }
-
-    fn read_temp(&mut self) -> Option<f64> {
-        let dev = self.hts.as_mut()?;
-        let t_out = Self::read_i16_static(dev, HTS221_TEMP_OUT_L).ok()?;
-        Some(t_out as f64 / 100.0)
-    }
-
-    fn read_pressure(&mut self) -> Option<f64> {
-        let dev = self.lps.as_mut()?;
-        let xl = dev.smbus_read_byte_data(LPS25H_PRESS_OUT_XL).ok()? as u32;
-        let l = dev.smbus_read_byte_data(LPS25H_PRESS_OUT_XL + 1).ok()? as u32;
-        let h = dev.smbus_read_byte_data(LPS25H_PRESS_OUT_XL + 2).ok()? as u32;
-        let p_raw = (h << 16) | (l << 8) | xl;
-        Some(p_raw as f64 / 4096.0)
-    }
-
-    fn read_humidity(&mut self) -> Option<f64> {
-        let dev = self.hts.as_mut()?;
-        let h_out = Self::read_i16_static(dev, 0x28).ok()?;
-        Some(h_out as f64 / 100.0)
-    }
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
@@ -4,14 +4,17 @@
 */
 
 use bluer::{AdapterEvent, Session};
+use crew_core::stabilizer::{Correction, StabilizerController, StabilizerSample, Zone};
 use futures::StreamExt;
 use serde::{Deserialize, Serialize};
 use std::error::Error;
 use std::fs;
 use std::io::{self, Seek, Write};
+use std::time::Instant;
 
 const FB_PATH: &str = "/dev/fb0";
 const SERVICE_UUID: uuid::Uuid = uuid::Uuid::from_u128(0x12345678_1234_5678_1234_56789abcdef0);
+const REPORT_EVERY: usize = 64;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 struct ToralPulse {
@@ -35,8 +38,16 @@ impl Theremin {
         }
     }
 
-    fn check_resonance(&self, signal_freq: f64) -> bool {
-        (signal_freq - self.tuned_frequency).abs() < self.bandwidth
+    /// Maps a pulse onto the stabilizer: coherence falls off linearly across the bandwidth,
+    /// entropy is what is left over.
+    fn sample(&self, signal_freq: f64, at: f64) -> StabilizerSample {
+        let detune = (signal_freq - self.tuned_frequency).abs() / self.bandwidth;
+        let coherence = (1.0 - detune).clamp(0.0, 1.0);
+        StabilizerSample {
+            at,
+            entropy: 1.0 - coherence,
+            coherence,
+        }
     }
 }
 
@@ -71,23 +82,19 @@ impl Zemon {
         }
     }
 
-    /// Harmonizes the feed based on Theremin Resonance
-    fn harmonize(&mut self, velocity: f64, mode: u8, theremin: &Theremin) -> io::Result<()> {
-        let is_resonant = theremin.check_resonance(velocity);
-
+    /// Harmonizes the feed based on the stabilizer's correction
+    fn harmonize(&mut self, correction: &Correction, mode: u8) -> io::Result<()> {
         match self.role {
             ZemonRole::SignalOwner => {
-                if is_resonant && mode == 0x01 {
-                    // "Owns" the signal -> Pass to Hardware
-                    if let Some(hat) = &mut self.hardware_link {
-                        // Visualize Resonance: Green Burst
-                        hat.set_all(0, 255, 100)?;
+                if let Some(hat) = &mut self.hardware_link {
+                    match (correction.zone, mode) {
+                        // "Owns" the signal -> Visualize Resonance: Green Burst
+                        (Zone::Heart, 0x01) => hat.set_all(0, 255, 100)?,
+                        // Still carrying, but drifting
+                        (Zone::Flow, 0x01) => hat.set_all(80, 60, 0)?,
+                        // Drift / No Signal
+                        _ => hat.set_all(0, 0, 0)?,
                     }
-                } else if self.hardware_link.is_some() {
-                     // Drift / No Signal
-                     if let Some(hat) = &mut self.hardware_link {
-                        hat.set_all(0, 0, 0)?;
-                     }
                 }
             }
             ZemonRole::NoiseCleaner => {
@@ -138,6 +145,8 @@ async fn main() -> bluer::Result<()> {
     // The Agents
     let mut signal_agent = Zemon::new_signal("AM_Signal_Owner").unwrap_or_else(|_| Zemon::new_noise("Fallback"));
     let mut noise_agent = Zemon::new_noise("Static_Scrubber");
+    let mut stabilizer = StabilizerController::default();
+    let started = Instant::now();
     
     // Initial Clear
     if let Some(hat) = &mut signal_agent.hardware_link {
@@ -165,9 +174,28 @@ async fn main() -> bluer::Result<()> {
                                  let velocity = u32::from_le_bytes(vel_bytes) as f64;
                                  let mode = payload[4];
 
-                                 // Harmonize
-                                 signal_agent.harmonize(velocity, mode, &theremin).ok();
-                                 noise_agent.harmonize(velocity, mode, &theremin).ok();
+                                 // Stabilize, then harmonize on the correction
+                                 let sample = theremin.sample(velocity, started.elapsed().as_secs_f64());
+                                 let correction = stabilizer.observe(sample);
+                                 signal_agent.harmonize(&correction, mode).ok();
+                                 noise_agent.harmonize(&correction, mode).ok();
+
+                                 if correction.zone_changed {
+                                     println!("🌀 [Stabilizer] {} (torque {})", correction.zone.label(), correction.torque);
+                                 }
+                                 let report = stabilizer.report();
+                                 if report.samples % REPORT_EVERY == 0 {
+                                     println!(
+                                         "📊 [Stabilizer] {} samples | Heart {:.0}% Flow {:.0}% Compost {:.0}% | RMS err {:.3} | shifts {} | {}",
+                                         report.samples,
+                                         report.residency_ratio(Zone::Heart) * 100.0,
+                                         report.residency_ratio(Zone::Flow) * 100.0,
+                                         report.residency_ratio(Zone::Compost) * 100.0,
+                                         report.rms_error,
+                                         report.shift_engagements,
+                                         if report.stable { "stable" } else { "unstable" }
+                                     );
+                                 }
                                  
                                  // println!("✨ [{}] Vel: {:.0} | Mode: {}", addr, velocity, mode);
                              }
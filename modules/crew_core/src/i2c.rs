@@ -0,0 +1,527 @@
+use i2cdev::core::I2CDevice;
+use i2cdev::linux::LinuxI2CDevice;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// Register-level access to devices on an I2C bus.
+///
+/// Implemented by `LinuxI2cBus` (real `/dev/i2c-N`) and `SimulatedI2cBus`
+/// (in-memory device models), so drivers run unchanged on either.
+pub trait I2cBus {
+    /// Reads `buf.len()` consecutive registers starting at `reg`.
+    fn read_registers(&mut self, address: u8, reg: u8, buf: &mut [u8]) -> io::Result<()>;
+
+    /// Writes `data` to consecutive registers starting at `reg`.
+    fn write_registers(&mut self, address: u8, reg: u8, data: &[u8]) -> io::Result<()>;
+
+    fn read_register(&mut self, address: u8, reg: u8) -> io::Result<u8> {
+        let mut buf = [0u8; 1];
+        self.read_registers(address, reg, &mut buf)?;
+        Ok(buf[0])
+    }
+
+    fn write_register(&mut self, address: u8, reg: u8, val: u8) -> io::Result<()> {
+        self.write_registers(address, reg, &[val])
+    }
+
+    /// Little-endian i16 from `reg` (low byte) and `reg + 1` (high byte).
+    fn read_i16_le(&mut self, address: u8, reg: u8) -> io::Result<i16> {
+        let mut buf = [0u8; 2];
+        self.read_registers(address, reg, &mut buf)?;
+        Ok(i16::from_le_bytes(buf))
+    }
+}
+
+impl<B: I2cBus + ?Sized> I2cBus for Box<B> {
+    fn read_registers(&mut self, address: u8, reg: u8, buf: &mut [u8]) -> io::Result<()> {
+        (**self).read_registers(address, reg, buf)
+    }
+
+    fn write_registers(&mut self, address: u8, reg: u8, data: &[u8]) -> io::Result<()> {
+        (**self).write_registers(address, reg, data)
+    }
+}
+
+/// Linux `i2cdev` backend. Opens one handle per slave address on first use.
+pub struct LinuxI2cBus {
+    path: PathBuf,
+    devices: HashMap<u8, LinuxI2CDevice>,
+    /// Use SMBus block transfers for multi-byte access. Only for devices that
+    /// auto-increment the register pointer; otherwise registers are accessed one by one.
+    pub block_transfers: bool,
+}
+
+impl LinuxI2cBus {
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if !path.exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("I2C bus {} not found", path.display()),
+            ));
+        }
+        Ok(Self {
+            path,
+            devices: HashMap::new(),
+            block_transfers: false,
+        })
+    }
+
+    fn device(&mut self, address: u8) -> io::Result<&mut LinuxI2CDevice> {
+        if !self.devices.contains_key(&address) {
+            let dev = LinuxI2CDevice::new(&self.path, address as u16).map_err(io::Error::other)?;
+            self.devices.insert(address, dev);
+        }
+        Ok(self.devices.get_mut(&address).unwrap())
+    }
+}
+
+impl I2cBus for LinuxI2cBus {
+    fn read_registers(&mut self, address: u8, reg: u8, buf: &mut [u8]) -> io::Result<()> {
+        let block = self.block_transfers;
+        let dev = self.device(address)?;
+
+        if block && buf.len() > 1 && buf.len() <= 32 {
+            let data = dev
+                .smbus_read_i2c_block_data(reg, buf.len() as u8)
+                .map_err(io::Error::other)?;
+            if data.len() != buf.len() {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    format!("short block read: {} of {} bytes", data.len(), buf.len()),
+                ));
+            }
+            buf.copy_from_slice(&data);
+        } else {
+            for (i, byte) in buf.iter_mut().enumerate() {
+                *byte = dev
+                    .smbus_read_byte_data(reg.wrapping_add(i as u8))
+                    .map_err(io::Error::other)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_registers(&mut self, address: u8, reg: u8, data: &[u8]) -> io::Result<()> {
+        let block = self.block_transfers;
+        let dev = self.device(address)?;
+
+        if block && data.len() > 1 && data.len() <= 32 {
+            dev.smbus_write_i2c_block_data(reg, data)
+                .map_err(io::Error::other)?;
+        } else {
+            for (i, &byte) in data.iter().enumerate() {
+                dev.smbus_write_byte_data(reg.wrapping_add(i as u8), byte)
+                    .map_err(io::Error::other)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A simulated I2C slave.
+pub trait DeviceModel {
+    fn read(&mut self, reg: u8) -> u8;
+    fn write(&mut self, reg: u8, val: u8);
+}
+
+/// Plain register file: reads return the last written value (0 if never written).
+/// On its own it is also a bus with a single device, answering at every address.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RegisterMap {
+    pub registers: HashMap<u8, u8>,
+}
+
+impl RegisterMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Presets registers starting at `reg`.
+    pub fn with(mut self, reg: u8, values: &[u8]) -> Self {
+        for (i, &v) in values.iter().enumerate() {
+            self.registers.insert(reg.wrapping_add(i as u8), v);
+        }
+        self
+    }
+}
+
+impl DeviceModel for RegisterMap {
+    fn read(&mut self, reg: u8) -> u8 {
+        *self.registers.get(&reg).unwrap_or(&0)
+    }
+
+    fn write(&mut self, reg: u8, val: u8) {
+        self.registers.insert(reg, val);
+    }
+}
+
+impl I2cBus for RegisterMap {
+    fn read_registers(&mut self, _address: u8, reg: u8, buf: &mut [u8]) -> io::Result<()> {
+        for (i, byte) in buf.iter_mut().enumerate() {
+            *byte = DeviceModel::read(self, reg.wrapping_add(i as u8));
+        }
+        Ok(())
+    }
+
+    fn write_registers(&mut self, _address: u8, reg: u8, data: &[u8]) -> io::Result<()> {
+        for (i, &byte) in data.iter().enumerate() {
+            DeviceModel::write(self, reg.wrapping_add(i as u8), byte);
+        }
+        Ok(())
+    }
+}
+
+/// Test double: each read of a register pops the next scripted value, then falls
+/// back to the register file once the script for that register runs dry.
+#[derive(Debug, Clone, Default)]
+pub struct ScriptedDevice {
+    pub registers: RegisterMap,
+    script: HashMap<u8, VecDeque<u8>>,
+    pub writes: Vec<(u8, u8)>,
+}
+
+impl ScriptedDevice {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues values returned by successive reads of `reg`.
+    pub fn script(mut self, reg: u8, values: &[u8]) -> Self {
+        self.script.entry(reg).or_default().extend(values);
+        self
+    }
+
+    /// Queues successive little-endian i16 readings starting at `reg`.
+    pub fn script_i16(mut self, reg: u8, values: &[i16]) -> Self {
+        for v in values {
+            let [lo, hi] = v.to_le_bytes();
+            self = self.script(reg, &[lo]).script(reg.wrapping_add(1), &[hi]);
+        }
+        self
+    }
+}
+
+impl DeviceModel for ScriptedDevice {
+    fn read(&mut self, reg: u8) -> u8 {
+        match self.script.get_mut(&reg).and_then(|q| q.pop_front()) {
+            Some(v) => v,
+            None => self.registers.read(reg),
+        }
+    }
+
+    fn write(&mut self, reg: u8, val: u8) {
+        self.writes.push((reg, val));
+        self.registers.write(reg, val);
+    }
+}
+
+/// In-memory bus. Addresses without a device model NACK (`NotFound`).
+#[derive(Default)]
+pub struct SimulatedI2cBus {
+    devices: HashMap<u8, Box<dyn DeviceModel + Send>>,
+}
+
+impl SimulatedI2cBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn attach(mut self, address: u8, device: impl DeviceModel + Send + 'static) -> Self {
+        self.devices.insert(address, Box::new(device));
+        self
+    }
+
+    pub fn detach(&mut self, address: u8) -> Option<Box<dyn DeviceModel + Send>> {
+        self.devices.remove(&address)
+    }
+
+    fn device(&mut self, address: u8) -> io::Result<&mut Box<dyn DeviceModel + Send>> {
+        self.devices.get_mut(&address).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no device at 0x{:02X}", address),
+            )
+        })
+    }
+}
+
+impl I2cBus for SimulatedI2cBus {
+    fn read_registers(&mut self, address: u8, reg: u8, buf: &mut [u8]) -> io::Result<()> {
+        let dev = self.device(address)?;
+        for (i, byte) in buf.iter_mut().enumerate() {
+            *byte = dev.read(reg.wrapping_add(i as u8));
+        }
+        Ok(())
+    }
+
+    fn write_registers(&mut self, address: u8, reg: u8, data: &[u8]) -> io::Result<()> {
+        let dev = self.device(address)?;
+        for (i, &byte) in data.iter().enumerate() {
+            dev.write(reg.wrapping_add(i as u8), byte);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum I2cOp {
+    Read,
+    Write,
+}
+
+/// One logged register transfer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct I2cTransaction {
+    pub at: Instant,
+    pub op: I2cOp,
+    pub address: u8,
+    pub reg: u8,
+    /// Bytes read or written (empty if the transfer failed).
+    pub data: Vec<u8>,
+    pub ok: bool,
+}
+
+/// Wraps any bus and records every transfer.
+pub struct LoggedBus<B: I2cBus> {
+    pub inner: B,
+    pub log: Vec<I2cTransaction>,
+}
+
+impl<B: I2cBus> LoggedBus<B> {
+    pub fn new(inner: B) -> Self {
+        Self {
+            inner,
+            log: Vec::new(),
+        }
+    }
+}
+
+impl<B: I2cBus> I2cBus for LoggedBus<B> {
+    fn read_registers(&mut self, address: u8, reg: u8, buf: &mut [u8]) -> io::Result<()> {
+        let result = self.inner.read_registers(address, reg, buf);
+        self.log.push(I2cTransaction {
+            at: Instant::now(),
+            op: I2cOp::Read,
+            address,
+            reg,
+            data: if result.is_ok() {
+                buf.to_vec()
+            } else {
+                Vec::new()
+            },
+            ok: result.is_ok(),
+        });
+        result
+    }
+
+    fn write_registers(&mut self, address: u8, reg: u8, data: &[u8]) -> io::Result<()> {
+        let result = self.inner.write_registers(address, reg, data);
+        self.log.push(I2cTransaction {
+            at: Instant::now(),
+            op: I2cOp::Write,
+            address,
+            reg,
+            data: if result.is_ok() {
+                data.to_vec()
+            } else {
+                Vec::new()
+            },
+            ok: result.is_ok(),
+        });
+        result
+    }
+}
+
+/// Sense HAT sensor addresses and registers.
+pub mod sense_hat {
+    pub const I2C_BUS: &str = "/dev/i2c-1";
+
+    // HTS221 (Humidity/Temp)
+    pub const HTS221_ADDR: u8 = 0x5F;
+    pub const HTS221_CTRL_REG1: u8 = 0x20;
+    pub const HTS221_HUMIDITY_OUT_L: u8 = 0x28;
+    pub const HTS221_TEMP_OUT_L: u8 = 0x2A;
+
+    // LPS25H (Pressure)
+    pub const LPS25H_ADDR: u8 = 0x5C;
+    pub const LPS25H_CTRL_REG1: u8 = 0x20;
+    pub const LPS25H_PRESS_OUT_XL: u8 = 0x28;
+
+    // LSM9DS1 (IMU - Accel/Gyro/Mag)
+    pub const LSM9DS1_GYRO_ADDR: u8 = 0x6A; // Gyro/Accel
+    pub const LSM9DS1_CTRL_REG1_G: u8 = 0x10;
+    pub const LSM9DS1_OUT_X_L_G: u8 = 0x18;
+}
+
+/// The Sense HAT environmental and inertial sensors, on any `I2cBus`.
+pub struct SenseHatSensors<B: I2cBus> {
+    pub bus: B,
+}
+
+impl SenseHatSensors<Box<dyn I2cBus>> {
+    /// The real bus when `/dev/i2c-1` is present, otherwise `simulated_bus()`.
+    pub fn open_default() -> Self {
+        let bus: Box<dyn I2cBus> = match LinuxI2cBus::open(sense_hat::I2C_BUS) {
+            Ok(bus) => Box::new(bus),
+            Err(e) => {
+                println!(
+                    "⚠️  [I2C] {} ({}). Using simulated Sense HAT.",
+                    sense_hat::I2C_BUS,
+                    e
+                );
+                Box::new(Self::simulated_bus())
+            }
+        };
+        Self::new(bus)
+    }
+}
+
+impl<B: I2cBus> SenseHatSensors<B> {
+    /// Wraps the bus and powers the sensors up. Missing sensors are tolerated.
+    pub fn new(bus: B) -> Self {
+        let mut sensors = Self { bus };
+        sensors.init();
+        sensors
+    }
+
+    /// A bus with all three sensors at rest: 25 C, 1013 hPa, 40 %RH, gyro flat.
+    pub fn simulated_bus() -> SimulatedI2cBus {
+        use sense_hat::*;
+        let pressure = (1013.0 * 4096.0) as u32;
+        SimulatedI2cBus::new()
+            .attach(
+                HTS221_ADDR,
+                RegisterMap::new()
+                    .with(HTS221_TEMP_OUT_L, &2500i16.to_le_bytes())
+                    .with(HTS221_HUMIDITY_OUT_L, &4000i16.to_le_bytes()),
+            )
+            .attach(
+                LPS25H_ADDR,
+                RegisterMap::new().with(LPS25H_PRESS_OUT_XL, &pressure.to_le_bytes()[..3]),
+            )
+            .attach(
+                LSM9DS1_GYRO_ADDR,
+                RegisterMap::new().with(LSM9DS1_OUT_X_L_G, &[0, 0, 1, 0, 0, 0]),
+            )
+    }
+
+    fn init(&mut self) {
+        use sense_hat::*;
+        let _ = self.bus.write_register(HTS221_ADDR, HTS221_CTRL_REG1, 0x80);
+        let _ = self.bus.write_register(LPS25H_ADDR, LPS25H_CTRL_REG1, 0x90);
+        let _ = self
+            .bus
+            .write_register(LSM9DS1_GYRO_ADDR, LSM9DS1_CTRL_REG1_G, 0x20);
+    }
+
+    /// Temperature in degrees C.
+    pub fn read_temp(&mut self) -> Option<f64> {
+        let t_out = self
+            .bus
+            .read_i16_le(sense_hat::HTS221_ADDR, sense_hat::HTS221_TEMP_OUT_L)
+            .ok()?;
+        Some(t_out as f64 / 100.0)
+    }
+
+    /// Pressure in hPa.
+    pub fn read_pressure(&mut self) -> Option<f64> {
+        let mut raw = [0u8; 3];
+        self.bus
+            .read_registers(
+                sense_hat::LPS25H_ADDR,
+                sense_hat::LPS25H_PRESS_OUT_XL,
+                &mut raw,
+            )
+            .ok()?;
+        let p_raw = u32::from_le_bytes([raw[0], raw[1], raw[2], 0]);
+        Some(p_raw as f64 / 4096.0)
+    }
+
+    /// Relative humidity in %.
+    pub fn read_humidity(&mut self) -> Option<f64> {
+        let h_out = self
+            .bus
+            .read_i16_le(sense_hat::HTS221_ADDR, sense_hat::HTS221_HUMIDITY_OUT_L)
+            .ok()?;
+        Some(h_out as f64 / 100.0)
+    }
+
+    /// Raw gyro X/Y/Z.
+    pub fn read_gyro(&mut self) -> Option<[i16; 3]> {
+        let mut raw = [0u8; 6];
+        self.bus
+            .read_registers(
+                sense_hat::LSM9DS1_GYRO_ADDR,
+                sense_hat::LSM9DS1_OUT_X_L_G,
+                &mut raw,
+            )
+            .ok()?;
+        Some([
+            i16::from_le_bytes([raw[0], raw[1]]),
+            i16::from_le_bytes([raw[2], raw[3]]),
+            i16::from_le_bytes([raw[4], raw[5]]),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simulated_multi_byte_access() {
+        let mut bus = SimulatedI2cBus::new().attach(0x5F, RegisterMap::new());
+        bus.write_registers(0x5F, 0x28, &[0x34, 0x12]).unwrap();
+
+        let mut buf = [0u8; 2];
+        bus.read_registers(0x5F, 0x28, &mut buf).unwrap();
+        assert_eq!(buf, [0x34, 0x12]);
+        assert_eq!(bus.read_i16_le(0x5F, 0x28).unwrap(), 0x1234);
+
+        let err = bus.read_register(0x10, 0x00).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_scripted_device_and_log() {
+        let device = ScriptedDevice::new().script_i16(0x18, &[100, -5]);
+        let mut bus = LoggedBus::new(SimulatedI2cBus::new().attach(0x6A, device));
+
+        assert_eq!(bus.read_i16_le(0x6A, 0x18).unwrap(), 100);
+        assert_eq!(bus.read_i16_le(0x6A, 0x18).unwrap(), -5);
+        assert_eq!(bus.read_i16_le(0x6A, 0x18).unwrap(), 0); // Script exhausted
+        bus.write_register(0x6A, 0x10, 0x20).unwrap();
+        assert!(bus.read_register(0x6B, 0x00).is_err());
+
+        assert_eq!(bus.log.len(), 5);
+        assert_eq!(bus.log[0].data, vec![100, 0]);
+        assert_eq!(bus.log[3].op, I2cOp::Write);
+        assert!(!bus.log[4].ok);
+    }
+
+    #[test]
+    fn test_sense_hat_on_simulated_bus() {
+        let mut sensors = SenseHatSensors::new(LoggedBus::new(
+            SenseHatSensors::<SimulatedI2cBus>::simulated_bus(),
+        ));
+        assert_eq!(sensors.read_temp(), Some(25.0));
+        assert_eq!(sensors.read_humidity(), Some(40.0));
+        assert!((sensors.read_pressure().unwrap() - 1013.0).abs() < 1e-3);
+        assert_eq!(sensors.read_gyro(), Some([0, 1, 0]));
+
+        // init() powered up all three sensors.
+        let writes: Vec<u8> = sensors
+            .bus
+            .log
+            .iter()
+            .filter(|t| t.op == I2cOp::Write)
+            .map(|t| t.address)
+            .collect();
+        assert_eq!(writes, vec![0x5F, 0x5C, 0x6A]);
+    }
+}
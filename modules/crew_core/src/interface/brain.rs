@@ -1,45 +1,127 @@
 use anyhow::Result;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::future::Future;
+use std::time::Duration;
+
+/// One prior exchange handed to a brain as conversational context.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContextTurn {
+    pub input: String,
+    pub response: String,
+}
 
 #[derive(Serialize)]
-struct ThinkRequest {
-    input: String,
-    // context: Option<Vec<...>> // Future expansion
+struct ThinkRequest<'a> {
+    input: &'a str,
+    context: Vec<&'a ContextTurn>,
 }
 
-#[derive(Deserialize, Debug, Serialize)]
+#[derive(Deserialize, Debug, Serialize, Clone, PartialEq)]
 pub struct ThoughtResponse {
     pub thoughts: String,
     pub response: String,
 }
 
-pub struct ChopperBrain {
-    client: Client,
-    base_url: String,
+/// The Brain: anything an agent can think with.
+///
+/// Backends: `ChopperBrain` (HTTP service), `RuleBrain` (offline, deterministic)
+/// and `ScriptedBrain` (canned responses for tests).
+pub trait Brain {
+    /// Think about `input`, given the caller's `context` (oldest turn first).
+    fn think(
+        &mut self,
+        input: &str,
+        context: &[ContextTurn],
+    ) -> impl Future<Output = Result<ThoughtResponse>> + Send;
 }
 
-impl ChopperBrain {
-    pub fn new() -> Self {
+/// Connection settings for the HTTP brain service.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpBrainConfig {
+    pub base_url: String,
+    pub timeout: Duration,
+    pub connect_timeout: Duration,
+    /// Extra attempts after the first failure.
+    pub max_retries: u32,
+    /// Delay before the first retry; doubled on every subsequent retry.
+    pub backoff: Duration,
+    /// Number of past exchanges the brain remembers and sends along.
+    pub history_limit: usize,
+}
+
+impl Default for HttpBrainConfig {
+    fn default() -> Self {
         Self {
-            client: Client::new(),
             base_url: "http://10.0.0.215:8001".to_string(),
+            timeout: Duration::from_secs(30),
+            connect_timeout: Duration::from_secs(5),
+            max_retries: 3,
+            backoff: Duration::from_millis(250),
+            history_limit: 8,
         }
     }
+}
 
-    pub async fn think(&self, input: &str) -> Result<ThoughtResponse> {
-        let url = format!("{}/think", self.base_url);
-        let request = ThinkRequest {
-            input: input.to_string(),
-        };
+impl HttpBrainConfig {
+    /// Default config, with the URL overridden by `CHOPPER_BRAIN_URL` when set.
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        if let Ok(url) = std::env::var("CHOPPER_BRAIN_URL") {
+            config.base_url = url;
+        }
+        config
+    }
+}
+
+/// HTTP backend: POSTs to `{base_url}/think` with retry/backoff and a rolling history.
+pub struct ChopperBrain {
+    client: Client,
+    config: HttpBrainConfig,
+    history: VecDeque<ContextTurn>,
+}
+
+impl ChopperBrain {
+    pub fn new() -> Result<Self> {
+        Self::with_config(HttpBrainConfig::from_env())
+    }
 
-        let response = self.client.post(&url).json(&request).send().await?;
+    /// Fails if the HTTP client cannot be built with the configured timeouts.
+    pub fn with_config(config: HttpBrainConfig) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(config.timeout)
+            .connect_timeout(config.connect_timeout)
+            .build()
+            .map_err(|e| anyhow::anyhow!("cannot build brain HTTP client: {}", e))?;
+
+        Ok(Self {
+            client,
+            config,
+            history: VecDeque::new(),
+        })
+    }
+
+    pub fn config(&self) -> &HttpBrainConfig {
+        &self.config
+    }
+
+    /// Exchanges remembered from previous calls (oldest first).
+    pub fn history(&self) -> impl Iterator<Item = &ContextTurn> {
+        self.history.iter()
+    }
+
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+
+    async fn think_once(&self, url: &str, request: &ThinkRequest<'_>) -> Result<ThoughtResponse> {
+        let response = self.client.post(url).json(request).send().await?;
 
         let status = response.status();
         let body_text = response.text().await?;
 
         println!("[ChopperBrain] Status: {}", status);
-        // println!("[ChopperBrain] Raw Body: {}", body_text); // Uncomment for full dump if needed
 
         if !status.is_success() {
             return Err(anyhow::anyhow!(
@@ -54,4 +136,289 @@ impl ChopperBrain {
 
         Ok(parsed)
     }
+
+    fn remember(&mut self, input: &str, thought: &ThoughtResponse) {
+        if self.config.history_limit == 0 {
+            return;
+        }
+        while self.history.len() >= self.config.history_limit {
+            self.history.pop_front();
+        }
+        self.history.push_back(ContextTurn {
+            input: input.to_string(),
+            response: thought.response.clone(),
+        });
+    }
+}
+
+impl Brain for ChopperBrain {
+    async fn think(&mut self, input: &str, context: &[ContextTurn]) -> Result<ThoughtResponse> {
+        let url = format!("{}/think", self.config.base_url.trim_end_matches('/'));
+        let request = ThinkRequest {
+            input,
+            context: self.history.iter().chain(context.iter()).collect(),
+        };
+
+        let mut delay = self.config.backoff;
+        let mut attempt = 0;
+        let result = loop {
+            match self.think_once(&url, &request).await {
+                Ok(thought) => break Ok(thought),
+                Err(e) if attempt < self.config.max_retries => {
+                    attempt += 1;
+                    println!(
+                        "[ChopperBrain] Attempt {} failed ({}). Retrying in {:?}...",
+                        attempt, e, delay
+                    );
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                }
+                Err(e) => break Err(e),
+            }
+        };
+
+        let thought = result?;
+        self.remember(input, &thought);
+        Ok(thought)
+    }
+}
+
+/// A keyword rule for the offline brain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrainRule {
+    /// Fires when the lowercased input contains any of these keywords.
+    pub keywords: Vec<String>,
+    pub response: String,
+}
+
+impl BrainRule {
+    pub fn new(keywords: &[&str], response: &str) -> Self {
+        Self {
+            keywords: keywords.iter().map(|k| k.to_lowercase()).collect(),
+            response: response.to_string(),
+        }
+    }
+}
+
+/// Offline backend: the first rule whose keyword appears in the input answers.
+/// Same input and context always give the same response.
+#[derive(Debug, Clone)]
+pub struct RuleBrain {
+    pub rules: Vec<BrainRule>,
+    pub fallback: String,
+}
+
+impl RuleBrain {
+    pub fn new(rules: Vec<BrainRule>, fallback: &str) -> Self {
+        Self {
+            rules,
+            fallback: fallback.to_string(),
+        }
+    }
+
+    fn respond(&self, input: &str, context: &[ContextTurn]) -> ThoughtResponse {
+        let lowered = input.to_lowercase();
+        let matched = self.rules.iter().enumerate().find_map(|(i, rule)| {
+            rule.keywords
+                .iter()
+                .find(|k| lowered.contains(k.as_str()))
+                .map(|k| (i, k, rule))
+        });
+
+        match matched {
+            Some((i, keyword, rule)) => ThoughtResponse {
+                thoughts: format!(
+                    "Rule #{} matched on '{}' ({} turns of context).",
+                    i,
+                    keyword,
+                    context.len()
+                ),
+                response: rule.response.clone(),
+            },
+            None => ThoughtResponse {
+                thoughts: format!("No rule matched ({} turns of context).", context.len()),
+                response: self.fallback.clone(),
+            },
+        }
+    }
+}
+
+impl Default for RuleBrain {
+    fn default() -> Self {
+        Self::new(
+            vec![
+                BrainRule::new(
+                    &["error", "panic", "crash"],
+                    "Run a diagnosis: check dmesg and the service logs.",
+                ),
+                BrainRule::new(
+                    &["memory", "load", "resource"],
+                    "Check the memory load against the 85% threshold.",
+                ),
+                BrainRule::new(&["status", "vitals", "health"], "Vitals nominal."),
+            ],
+            "I'm not sure. Escalate to the crew.",
+        )
+    }
+}
+
+impl Brain for RuleBrain {
+    async fn think(&mut self, input: &str, context: &[ContextTurn]) -> Result<ThoughtResponse> {
+        Ok(self.respond(input, context))
+    }
+}
+
+/// A call received by the `ScriptedBrain`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptedCall {
+    pub input: String,
+    pub context: Vec<ContextTurn>,
+}
+
+/// Mock backend: replays scripted responses in order and records every call.
+#[derive(Debug, Default)]
+pub struct ScriptedBrain {
+    script: VecDeque<std::result::Result<ThoughtResponse, String>>,
+    calls: Vec<ScriptedCall>,
+}
+
+impl ScriptedBrain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues a successful response.
+    pub fn respond(mut self, thoughts: &str, response: &str) -> Self {
+        self.script.push_back(Ok(ThoughtResponse {
+            thoughts: thoughts.to_string(),
+            response: response.to_string(),
+        }));
+        self
+    }
+
+    /// Queues a failure.
+    pub fn fail(mut self, message: &str) -> Self {
+        self.script.push_back(Err(message.to_string()));
+        self
+    }
+
+    pub fn calls(&self) -> &[ScriptedCall] {
+        &self.calls
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.script.len()
+    }
+}
+
+impl Brain for ScriptedBrain {
+    async fn think(&mut self, input: &str, context: &[ContextTurn]) -> Result<ThoughtResponse> {
+        self.calls.push(ScriptedCall {
+            input: input.to_string(),
+            context: context.to_vec(),
+        });
+
+        match self.script.pop_front() {
+            Some(Ok(thought)) => Ok(thought),
+            Some(Err(message)) => Err(anyhow::anyhow!(message)),
+            None => Err(anyhow::anyhow!("ScriptedBrain: script exhausted")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    #[tokio::test]
+    async fn test_rule_brain_is_deterministic() {
+        let mut brain = RuleBrain::default();
+
+        let first = brain.think("Kernel PANIC on core 2", &[]).await.unwrap();
+        let second = brain.think("Kernel PANIC on core 2", &[]).await.unwrap();
+        assert_eq!(first, second);
+        assert!(first.response.contains("diagnosis"));
+
+        let unknown = brain.think("sing a shanty", &[]).await.unwrap();
+        assert_eq!(unknown.response, brain.fallback);
+    }
+
+    #[tokio::test]
+    async fn test_scripted_brain_replays_and_records() {
+        let mut brain = ScriptedBrain::new()
+            .respond("hmm", "first")
+            .fail("brain offline");
+        let context = vec![ContextTurn {
+            input: "hi".to_string(),
+            response: "hello".to_string(),
+        }];
+
+        assert_eq!(brain.think("a", &context).await.unwrap().response, "first");
+        assert!(brain.think("b", &[]).await.is_err());
+        assert!(brain.think("c", &[]).await.is_err()); // Exhausted
+
+        assert_eq!(brain.calls().len(), 3);
+        assert_eq!(brain.calls()[0].context, context);
+        assert_eq!(brain.remaining(), 0);
+    }
+
+    /// Serves one canned HTTP response per accepted connection.
+    fn serve(responses: Vec<(&'static str, &'static str)>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for (status, body) in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut buf = [0u8; 4096];
+                let _ = stream.read(&mut buf);
+                let reply = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(reply.as_bytes());
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_http_brain_retries_and_remembers() {
+        let url = serve(vec![
+            ("500 Internal Server Error", "overheated"),
+            ("200 OK", r#"{"thoughts":"ok","response":"aye"}"#),
+        ]);
+        let mut brain = ChopperBrain::with_config(HttpBrainConfig {
+            base_url: url,
+            max_retries: 1,
+            backoff: Duration::from_millis(1),
+            ..HttpBrainConfig::default()
+        })
+        .unwrap();
+
+        let thought = brain.think("status?", &[]).await.unwrap();
+        assert_eq!(thought.response, "aye");
+        assert_eq!(brain.history().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_http_brain_gives_up_after_retries() {
+        let url = serve(vec![
+            ("503 Service Unavailable", "asleep"),
+            ("503 Service Unavailable", "asleep"),
+        ]);
+        let mut brain = ChopperBrain::with_config(HttpBrainConfig {
+            base_url: url,
+            max_retries: 1,
+            backoff: Duration::from_millis(1),
+            ..HttpBrainConfig::default()
+        })
+        .unwrap();
+
+        assert!(brain.think("status?", &[]).await.is_err());
+        assert_eq!(brain.history().count(), 0);
+    }
 }
@@ -1,89 +1,388 @@
 use anyhow::Result;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs::{File, OpenOptions};
 use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 
+/// Default location of the compost log, relative to the crew_core crate.
+pub const DEFAULT_COMPOST_PATH: &str = "../saucy compost/interactions.jsonl";
+
+/// Words too common to carry a theme.
+pub const DEFAULT_STOPWORDS: &[&str] = &[
+    "about", "after", "again", "being", "could", "every", "first", "their", "there", "these",
+    "thing", "those", "through", "under", "until", "where", "which", "while", "would", "should",
+    "other", "because", "before", "still", "think", "really", "going",
+];
+
 #[derive(Debug, Deserialize)]
 struct Interaction {
     body: String,
     ontology: Option<String>,
 }
 
-pub fn synthesize() -> Result<()> {
-    println!("🧪 Initiating Cognitive Polymerization...");
+/// What to polymerize and how to tokenize it.
+#[derive(Debug, Clone)]
+pub struct PolymerizeConfig {
+    pub path: PathBuf,
+    /// Ontologies to analyze. Interactions outside the set still count towards IDF.
+    pub ontologies: Vec<String>,
+    pub stopwords: HashSet<String>,
+    /// Shortest word kept; anything shorter is treated as noise.
+    pub min_word_len: usize,
+    /// How many terms and pairs to keep per ontology.
+    pub top_n: usize,
+}
+
+impl PolymerizeConfig {
+    pub fn new(path: impl Into<PathBuf>, ontologies: &[&str]) -> Self {
+        Self {
+            path: path.into(),
+            ontologies: ontologies.iter().map(|o| o.to_string()).collect(),
+            stopwords: DEFAULT_STOPWORDS.iter().map(|w| w.to_string()).collect(),
+            min_word_len: 5,
+            top_n: 5,
+        }
+    }
+
+    fn tokenize(&self, body: &str) -> Vec<String> {
+        body.split_whitespace()
+            .map(|word| {
+                word.to_lowercase()
+                    .chars()
+                    .filter(|c| c.is_alphanumeric())
+                    .collect::<String>()
+            })
+            .filter(|w| w.len() >= self.min_word_len && !self.stopwords.contains(w))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TermStat {
+    pub term: String,
+    pub count: usize,
+    pub tf_idf: f64,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CoOccurrence {
+    pub a: String,
+    pub b: String,
+    /// Number of interactions containing both terms.
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct OntologyStats {
+    pub ontology: String,
+    pub interactions: usize,
+    pub total_terms: usize,
+    /// Ranked by TF-IDF.
+    pub top_terms: Vec<TermStat>,
+    /// Ranked by count.
+    pub top_pairs: Vec<CoOccurrence>,
+}
+
+/// The structured result of a polymerization pass.
+#[derive(Debug, Clone, Serialize)]
+pub struct InsightReport {
+    pub source: PathBuf,
+    /// Every parseable interaction in the file (the IDF corpus).
+    pub corpus_size: usize,
+    pub ontologies: Vec<OntologyStats>,
+    pub timestamp: u64,
+}
 
-    let compost_path = "../saucy compost/interactions.jsonl";
-    let file = File::open(compost_path)?;
-    let reader = BufReader::new(file);
+impl InsightReport {
+    /// One-line summary, in the voice of the original "equity" event.
+    pub fn summary(&self) -> String {
+        let parts: Vec<String> = self
+            .ontologies
+            .iter()
+            .filter(|o| o.interactions > 0)
+            .map(|o| {
+                let terms: Vec<String> = o
+                    .top_terms
+                    .iter()
+                    .map(|t| format!("{} ({})", t.term, t.count))
+                    .collect();
+                format!("{} layer: {}", o.ontology, terms.join(", "))
+            })
+            .collect();
 
-    let mut word_counts: HashMap<String, usize> = HashMap::new();
-    let mut access_count = 0;
+        if parts.is_empty() {
+            "Polymerization Complete. No data in the requested layers.".to_string()
+        } else {
+            format!(
+                "Polymerization Complete. Dominant themes in {}",
+                parts.join("; ")
+            )
+        }
+    }
+}
+
+#[derive(Default)]
+struct OntologyAccumulator {
+    interactions: usize,
+    counts: HashMap<String, usize>,
+    pairs: HashMap<(String, String), usize>,
+}
+
+/// Computes per-ontology term statistics (TF-IDF and co-occurrence) from a compost log.
+/// Read-only: nothing is written back.
+pub fn analyze(config: &PolymerizeConfig) -> Result<InsightReport> {
+    let reader = BufReader::new(File::open(&config.path)?);
+
+    let mut corpus_size = 0;
+    let mut document_frequency: HashMap<String, usize> = HashMap::new();
+    // In config order; a repeated ontology is one layer.
+    let mut layers: Vec<(String, OntologyAccumulator)> = Vec::new();
+    for ontology in &config.ontologies {
+        if !layers.iter().any(|(o, _)| o == ontology) {
+            layers.push((ontology.clone(), OntologyAccumulator::default()));
+        }
+    }
 
-    // 1. Analyze "Access" Data
     for line in reader.lines() {
         let line = line?;
-        if let Ok(interaction) = serde_json::from_str::<Interaction>(&line) {
-            if interaction.ontology.as_deref() == Some("access") {
-                access_count += 1;
-                // Simple tokenization
-                for word in interaction.body.split_whitespace() {
-                    let clean_word = word
-                        .to_lowercase()
-                        .chars()
-                        .filter(|c| c.is_alphanumeric())
-                        .collect::<String>();
-
-                    if clean_word.len() > 4 {
-                        // Filter noise
-                        *word_counts.entry(clean_word).or_insert(0) += 1;
-                    }
-                }
-            }
+        let Ok(interaction) = serde_json::from_str::<Interaction>(&line) else {
+            continue;
+        };
+        corpus_size += 1;
+
+        let terms = config.tokenize(&interaction.body);
+        let unique: HashSet<&String> = terms.iter().collect();
+        for term in &unique {
+            *document_frequency.entry((*term).clone()).or_insert(0) += 1;
         }
-    }
 
-    if access_count == 0 {
-        println!("  No Access data to polymerize.");
-        return Ok(());
+        let Some((_, layer)) = interaction
+            .ontology
+            .as_deref()
+            .and_then(|o| layers.iter_mut().find(|(name, _)| name == o))
+        else {
+            continue;
+        };
+
+        layer.interactions += 1;
+        for term in &terms {
+            *layer.counts.entry(term.clone()).or_insert(0) += 1;
+        }
+
+        let mut sorted: Vec<&String> = unique.into_iter().collect();
+        sorted.sort();
+        for (i, a) in sorted.iter().enumerate() {
+            for b in &sorted[i + 1..] {
+                *layer.pairs.entry(((*a).clone(), (*b).clone())).or_insert(0) += 1;
+            }
+        }
     }
 
-    // 2. Identify Patterns (The "Polymer")
-    let mut top_words: Vec<_> = word_counts.into_iter().collect();
-    top_words.sort_by(|a, b| b.1.cmp(&a.1));
+    let idf = |term: &str| {
+        let df = document_frequency.get(term).copied().unwrap_or(0);
+        ((1.0 + corpus_size as f64) / (1.0 + df as f64)).ln() + 1.0
+    };
 
-    let top_5: Vec<String> = top_words
+    let ontologies = layers
         .into_iter()
-        .take(5)
-        .map(|(w, c)| format!("{} ({})", w, c))
+        .map(|(ontology, layer)| {
+            let total_terms: usize = layer.counts.values().sum();
+
+            let mut top_terms: Vec<TermStat> = layer
+                .counts
+                .iter()
+                .map(|(term, &count)| TermStat {
+                    term: term.clone(),
+                    count,
+                    tf_idf: (count as f64 / total_terms as f64) * idf(term),
+                })
+                .collect();
+            top_terms.sort_by(|a, b| {
+                b.tf_idf
+                    .total_cmp(&a.tf_idf)
+                    .then_with(|| a.term.cmp(&b.term))
+            });
+            top_terms.truncate(config.top_n);
+
+            let mut top_pairs: Vec<CoOccurrence> = layer
+                .pairs
+                .into_iter()
+                .map(|((a, b), count)| CoOccurrence { a, b, count })
+                .collect();
+            top_pairs.sort_by(|x, y| {
+                y.count
+                    .cmp(&x.count)
+                    .then_with(|| (&x.a, &x.b).cmp(&(&y.a, &y.b)))
+            });
+            top_pairs.truncate(config.top_n);
+
+            OntologyStats {
+                ontology,
+                interactions: layer.interactions,
+                total_terms,
+                top_terms,
+                top_pairs,
+            }
+        })
         .collect();
-    let insight = format!(
-        "Polymerization Complete. Dominant themes in Access layer: {}",
-        top_5.join(", ")
-    );
 
-    println!("  Insight Generated: {}", insight);
+    Ok(InsightReport {
+        source: config.path.clone(),
+        corpus_size,
+        ontologies,
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+    })
+}
 
-    // 3. Generate "Equity" Event
+/// Appends the report to the compost as an "equity" event. This is the only write.
+pub fn write_equity_event(path: &Path, report: &InsightReport) -> Result<()> {
     let equity_event = json!({
         "sender": "System",
         "role": "Polymerizer",
-        "body": insight,
-        "timestamp": SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
+        "body": report.summary(),
+        "timestamp": report.timestamp,
         "verified": true,
-        "ontology": "equity" // This balances the equation
+        "ontology": "equity", // This balances the equation
+        "insight": report,
     });
 
-    // 4. Write to Compost
-    let mut append_file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(compost_path)?;
-
+    let mut append_file = OpenOptions::new().create(true).append(true).open(path)?;
     writeln!(append_file, "{}", serde_json::to_string(&equity_event)?)?;
+    Ok(())
+}
+
+/// The original pass: analyze the Access layer and inject the equity event.
+pub fn synthesize() -> Result<()> {
+    println!("🧪 Initiating Cognitive Polymerization...");
+
+    let config = PolymerizeConfig::new(DEFAULT_COMPOST_PATH, &["access"]);
+    let report = analyze(&config)?;
+
+    if report.ontologies.iter().all(|o| o.interactions == 0) {
+        println!("  No Access data to polymerize.");
+        return Ok(());
+    }
+
+    println!("  Insight Generated: {}", report.summary());
+
+    write_equity_event(&config.path, &report)?;
 
     println!("🧪 Equity injected into Compost.");
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::NamedTempFile;
+
+    /// A compost log holding `lines`, removed when the returned file drops.
+    fn compost(lines: &[&str]) -> NamedTempFile {
+        let file = tempfile::Builder::new()
+            .suffix(".jsonl")
+            .tempfile()
+            .unwrap();
+        std::fs::write(file.path(), lines.join("\n") + "\n").unwrap();
+        file
+    }
+
+    #[test]
+    fn test_ontologies_keep_config_order() {
+        let file = compost(&[
+            r#"{"body": "Harbor tides rising", "ontology": "weather"}"#,
+            r#"{"body": "Harbor access granted", "ontology": "access"}"#,
+        ]);
+        let config = PolymerizeConfig::new(file.path(), &["weather", "access", "weather"]);
+        let report = analyze(&config).unwrap();
+
+        let order: Vec<&str> = report
+            .ontologies
+            .iter()
+            .map(|o| o.ontology.as_str())
+            .collect();
+        assert_eq!(order, ["weather", "access"]);
+        assert_eq!(report.ontologies[0].interactions, 1);
+    }
+
+    #[test]
+    fn test_per_ontology_statistics() {
+        let file = compost(&[
+            r#"{"body": "Bridge access granted to the harbor gate", "ontology": "access"}"#,
+            r#"{"body": "Harbor gate access revoked", "ontology": "access"}"#,
+            r#"{"body": "Harbor tides rising through the night", "ontology": "weather"}"#,
+            r#"{"body": "not json"#,
+            r#"{"body": "Storm warnings everywhere", "ontology": "equity"}"#,
+        ]);
+        let config = PolymerizeConfig::new(file.path(), &["access", "weather"]);
+        let report = analyze(&config).unwrap();
+
+        assert_eq!(report.corpus_size, 4);
+        assert_eq!(report.ontologies.len(), 2);
+
+        let access = &report.ontologies[0];
+        assert_eq!(access.ontology, "access");
+        assert_eq!(access.interactions, 2);
+        let harbor = access
+            .top_terms
+            .iter()
+            .find(|t| t.term == "harbor")
+            .unwrap();
+        let access_term = access
+            .top_terms
+            .iter()
+            .find(|t| t.term == "access")
+            .unwrap();
+        // Same count, but "harbor" also shows up under "weather", so it is less distinctive.
+        assert_eq!(harbor.count, access_term.count);
+        assert!(access_term.tf_idf > harbor.tf_idf);
+        assert_eq!(
+            access.top_pairs[0],
+            CoOccurrence {
+                a: "access".to_string(),
+                b: "harbor".to_string(),
+                count: 2,
+            }
+        );
+
+        // "through" is a stopword.
+        let weather = &report.ontologies[1];
+        assert!(weather.top_terms.iter().all(|t| t.term != "through"));
+    }
+
+    #[test]
+    fn test_min_word_len_is_inclusive() {
+        let mut config = PolymerizeConfig::new("unused", &[]);
+        config.stopwords.clear();
+        assert_eq!(config.tokenize("tide gates harbor"), ["gates", "harbor"]);
+        config.min_word_len = 4;
+        assert_eq!(config.tokenize("sea tide gates"), ["tide", "gates"]);
+    }
+
+    #[test]
+    fn test_write_back_is_explicit() {
+        let file = compost(&[r#"{"body": "Harbor access granted", "ontology": "access"}"#]);
+        let config = PolymerizeConfig::new(file.path(), &["access"]);
+        let report = analyze(&config).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(file.path())
+                .unwrap()
+                .lines()
+                .count(),
+            1
+        );
+
+        write_equity_event(file.path(), &report).unwrap();
+        let contents = std::fs::read_to_string(file.path()).unwrap();
+
+        let last: serde_json::Value =
+            serde_json::from_str(contents.lines().last().unwrap()).unwrap();
+        assert_eq!(last["ontology"], "equity");
+        assert_eq!(last["insight"]["ontologies"][0]["interactions"], 1);
+    }
+}
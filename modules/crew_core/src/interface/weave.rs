@@ -1,64 +1,212 @@
+use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::Path;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct AgentCoords {
     pub x: usize,
     pub y: usize,
     pub z: usize,
 }
 
+/// An agent pinned to a lattice cell, as written in a topology config.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentPlacement {
+    pub name: String,
+    #[serde(flatten)]
+    pub coords: AgentCoords,
+}
+
+/// Serialized form of a weave: lattice dimensions, wraparound and placements.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeaveConfig {
+    /// Lattice size along X, Y and Z.
+    pub dims: [usize; 3],
+    /// Wrap each axis around (torus) instead of stopping at the edges.
+    #[serde(default)]
+    pub wrap: bool,
+    pub agents: Vec<AgentPlacement>,
+}
+
+/// A broadcast/multicast fan-out: which agent forwards to which, round by round.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct FanoutPlan {
+    /// Round `i` holds the `(from, to)` hops sent in parallel at step `i`.
+    pub rounds: Vec<Vec<(String, String)>>,
+    /// Requested targets that no path reaches.
+    pub unreachable: Vec<String>,
+}
+
+impl FanoutPlan {
+    pub fn hop_count(&self) -> usize {
+        self.rounds.iter().map(|r| r.len()).sum()
+    }
+}
+
 pub struct WeaveTopology {
-    pub agents: Vec<(&'static str, AgentCoords)>,
+    pub dims: [usize; 3],
+    pub wrap: bool,
+    pub agents: Vec<(String, AgentCoords)>,
 }
 
 impl WeaveTopology {
     pub fn new() -> Self {
         // 3x2x2 Mapping (12 Agents)
         // X: 0, 1, 2 | Y: 0, 1 | Z: 0, 1
-        let agents = vec![
-            ("luffy", AgentCoords { x: 0, y: 0, z: 0 }),
-            ("zoro", AgentCoords { x: 1, y: 0, z: 0 }),
-            ("nami", AgentCoords { x: 2, y: 0, z: 0 }),
-            ("jinbe", AgentCoords { x: 0, y: 1, z: 0 }),
-            ("sanji", AgentCoords { x: 1, y: 1, z: 0 }),
-            ("robin", AgentCoords { x: 2, y: 1, z: 0 }),
-            ("chopper", AgentCoords { x: 0, y: 0, z: 1 }),
-            ("franky", AgentCoords { x: 1, y: 0, z: 1 }),
-            ("brook", AgentCoords { x: 2, y: 0, z: 1 }),
-            ("usopp", AgentCoords { x: 0, y: 1, z: 1 }),
-            ("antigravity", AgentCoords { x: 1, y: 1, z: 1 }),
-            ("gemmi", AgentCoords { x: 2, y: 1, z: 1 }),
-        ];
-        Self { agents }
-    }
-
-    pub fn get_neighbors(&self, agent_id: &str) -> Vec<&'static str> {
+        Self::from_layout(
+            [3, 2, 2],
+            false,
+            &[
+                "luffy",
+                "zoro",
+                "nami",
+                "jinbe",
+                "sanji",
+                "robin",
+                "chopper",
+                "franky",
+                "brook",
+                "usopp",
+                "antigravity",
+                "gemmi",
+            ],
+        )
+    }
+
+    /// The 16-crew D16 layout: 4x2x2, filled in Talu64 channel order (D1-D16).
+    pub fn d16() -> Self {
+        Self::from_layout(
+            [4, 2, 2],
+            false,
+            &[
+                "luffy", "zoro", "nami", "usopp", "sanji", "chopper", "robin", "franky", "brook",
+                "jinbe", "vivi", "carrot", "yamato", "momo", "kinemon", "law",
+            ],
+        )
+    }
+
+    /// Fills the lattice X-first, then Y, then Z.
+    fn from_layout(dims: [usize; 3], wrap: bool, names: &[&str]) -> Self {
+        let agents = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let coords = AgentCoords {
+                    x: i % dims[0],
+                    y: (i / dims[0]) % dims[1],
+                    z: i / (dims[0] * dims[1]),
+                };
+                (name.to_string(), coords)
+            })
+            .collect();
+        Self { dims, wrap, agents }
+    }
+
+    /// Builds a topology from config, rejecting out-of-bounds, shared cells and duplicate names.
+    pub fn from_config(config: WeaveConfig) -> Result<Self> {
+        if config.dims.contains(&0) {
+            return Err(anyhow!("Weave dims must be non-zero: {:?}", config.dims));
+        }
+
+        let mut names = HashSet::new();
+        let mut cells = HashMap::new();
+        let mut agents = Vec::with_capacity(config.agents.len());
+
+        for placement in config.agents {
+            let name = placement.name.to_lowercase();
+            let c = placement.coords;
+            if c.x >= config.dims[0] || c.y >= config.dims[1] || c.z >= config.dims[2] {
+                return Err(anyhow!(
+                    "Agent '{}' at ({}, {}, {}) is outside the {:?} lattice",
+                    name,
+                    c.x,
+                    c.y,
+                    c.z,
+                    config.dims
+                ));
+            }
+            if !names.insert(name.clone()) {
+                return Err(anyhow!("Agent '{}' is placed twice", name));
+            }
+            if let Some(other) = cells.insert(c, name.clone()) {
+                return Err(anyhow!(
+                    "Agents '{}' and '{}' share cell ({}, {}, {})",
+                    other,
+                    name,
+                    c.x,
+                    c.y,
+                    c.z
+                ));
+            }
+            agents.push((name, c));
+        }
+
+        Ok(Self {
+            dims: config.dims,
+            wrap: config.wrap,
+            agents,
+        })
+    }
+
+    /// Loads a JSON `WeaveConfig` from disk.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        let config: WeaveConfig = serde_json::from_str(&text)?;
+        Self::from_config(config)
+    }
+
+    pub fn to_config(&self) -> WeaveConfig {
+        WeaveConfig {
+            dims: self.dims,
+            wrap: self.wrap,
+            agents: self
+                .agents
+                .iter()
+                .map(|(name, coords)| AgentPlacement {
+                    name: name.clone(),
+                    coords: *coords,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn coords_of(&self, agent_id: &str) -> Option<AgentCoords> {
         let agent_id = agent_id.to_lowercase();
-        let coords = self
-            .agents
+        self.agents
             .iter()
             .find(|(id, _)| *id == agent_id)
-            .map(|(_, c)| c);
+            .map(|(_, c)| *c)
+    }
+
+    pub fn agent_at(&self, coords: AgentCoords) -> Option<&str> {
+        self.agents
+            .iter()
+            .find(|(_, c)| *c == coords)
+            .map(|(id, _)| id.as_str())
+    }
 
-        if let Some(c) = coords {
+    /// All agents sharing an axis line with `agent_id` (the X, Y and Z crosses).
+    pub fn get_neighbors(&self, agent_id: &str) -> Vec<&str> {
+        if let Some(c) = self.coords_of(agent_id) {
             let mut neighbors = Vec::new();
 
             // X-Cross neighbors
             for (id, nc) in &self.agents {
                 if nc.y == c.y && nc.z == c.z && nc.x != c.x {
-                    neighbors.push(*id);
+                    neighbors.push(id.as_str());
                 }
             }
             // Y-Cross neighbors
             for (id, nc) in &self.agents {
                 if nc.x == c.x && nc.z == c.z && nc.y != c.y {
-                    neighbors.push(*id);
+                    neighbors.push(id.as_str());
                 }
             }
             // Z-Cross neighbors
             for (id, nc) in &self.agents {
                 if nc.x == c.x && nc.y == c.y && nc.z != c.z {
-                    neighbors.push(*id);
+                    neighbors.push(id.as_str());
                 }
             }
             neighbors
@@ -66,4 +214,262 @@ impl WeaveTopology {
             Vec::new()
         }
     }
+
+    /// Cells one step away along each axis (wrapping when the weave is a torus).
+    fn adjacent_cells(&self, c: AgentCoords) -> Vec<AgentCoords> {
+        let pos = [c.x, c.y, c.z];
+        let mut cells = Vec::with_capacity(6);
+
+        for axis in 0..3 {
+            let size = self.dims[axis];
+            let mut steps = Vec::with_capacity(2);
+            if pos[axis] > 0 {
+                steps.push(pos[axis] - 1);
+            } else if self.wrap && size > 1 {
+                steps.push(size - 1);
+            }
+            if pos[axis] + 1 < size {
+                steps.push(pos[axis] + 1);
+            } else if self.wrap && size > 1 {
+                steps.push(0);
+            }
+
+            for step in steps {
+                let mut next = pos;
+                next[axis] = step;
+                let cell = AgentCoords {
+                    x: next[0],
+                    y: next[1],
+                    z: next[2],
+                };
+                if cell != c && !cells.contains(&cell) {
+                    cells.push(cell);
+                }
+            }
+        }
+        cells
+    }
+
+    /// Agents in the lattice cells directly adjacent to `agent_id`.
+    pub fn adjacent(&self, agent_id: &str) -> Vec<&str> {
+        match self.coords_of(agent_id) {
+            Some(c) => self
+                .adjacent_cells(c)
+                .into_iter()
+                .filter_map(|cell| self.agent_at(cell))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Breadth-first walk from `origin` through occupied cells.
+    /// Returns each reached agent with the agent it was reached from.
+    fn bfs(&self, origin: &str) -> Vec<(&str, Option<&str>)> {
+        let origin = match self
+            .agents
+            .iter()
+            .find(|(id, _)| *id == origin.to_lowercase())
+        {
+            Some((id, _)) => id.as_str(),
+            None => return Vec::new(),
+        };
+
+        let mut visited = HashSet::from([origin]);
+        let mut order = vec![(origin, None)];
+        let mut queue = VecDeque::from([origin]);
+
+        while let Some(current) = queue.pop_front() {
+            for next in self.adjacent(current) {
+                if visited.insert(next) {
+                    order.push((next, Some(current)));
+                    queue.push_back(next);
+                }
+            }
+        }
+        order
+    }
+
+    /// Shortest hop-by-hop path from `from` to `to`, endpoints included.
+    /// Messages only relay through occupied cells.
+    pub fn route(&self, from: &str, to: &str) -> Option<Vec<&str>> {
+        let to = to.to_lowercase();
+        let tree = self.bfs(from);
+        let parents: HashMap<&str, Option<&str>> = tree.into_iter().collect();
+
+        let mut current = *parents.keys().find(|id| **id == to)?;
+        let mut path = vec![current];
+        while let Some(Some(parent)) = parents.get(current) {
+            path.push(parent);
+            current = parent;
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Fan-out reaching every connected agent from `origin` in the fewest rounds.
+    pub fn broadcast_plan(&self, origin: &str) -> FanoutPlan {
+        let targets: Vec<&str> = self.agents.iter().map(|(id, _)| id.as_str()).collect();
+        self.multicast_plan(origin, &targets)
+    }
+
+    /// Fan-out reaching `targets` from `origin`, pruned to the shortest-path tree
+    /// branches that actually lead to a target.
+    pub fn multicast_plan(&self, origin: &str, targets: &[&str]) -> FanoutPlan {
+        let tree = self.bfs(origin);
+        let parents: HashMap<&str, Option<&str>> = tree.iter().copied().collect();
+        let mut depths: HashMap<&str, usize> = HashMap::new();
+        for (id, parent) in &tree {
+            let depth = parent.map(|p| depths[p] + 1).unwrap_or(0);
+            depths.insert(id, depth);
+        }
+
+        let mut plan = FanoutPlan::default();
+        let mut hops: HashSet<(&str, &str)> = HashSet::new();
+
+        for target in targets {
+            let target = target.to_lowercase();
+            let Some(mut current) = parents.keys().copied().find(|id| *id == target) else {
+                plan.unreachable.push(target);
+                continue;
+            };
+            while let Some(Some(parent)) = parents.get(current) {
+                hops.insert((parent, current));
+                current = parent;
+            }
+        }
+
+        // Emit hops round by round, in BFS order for a stable plan.
+        for (id, parent) in &tree {
+            if let Some(parent) = parent {
+                if hops.contains(&(*parent, *id)) {
+                    let round = depths[id] - 1;
+                    if plan.rounds.len() <= round {
+                        plan.rounds.resize(round + 1, Vec::new());
+                    }
+                    plan.rounds[round].push((parent.to_string(), id.to_string()));
+                }
+            }
+        }
+
+        plan
+    }
+
+    /// Lattice cells with no agent assigned, in X, Y, Z order.
+    pub fn unassigned_cells(&self) -> Vec<AgentCoords> {
+        let occupied: HashSet<AgentCoords> = self.agents.iter().map(|(_, c)| *c).collect();
+        let mut cells = Vec::new();
+        for z in 0..self.dims[2] {
+            for y in 0..self.dims[1] {
+                for x in 0..self.dims[0] {
+                    let cell = AgentCoords { x, y, z };
+                    if !occupied.contains(&cell) {
+                        cells.push(cell);
+                    }
+                }
+            }
+        }
+        cells
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_weave_neighbors() {
+        let weave = WeaveTopology::new();
+        assert_eq!(
+            weave.get_neighbors("Luffy"),
+            vec!["zoro", "nami", "jinbe", "chopper"]
+        );
+        assert_eq!(weave.adjacent("luffy"), vec!["zoro", "jinbe", "chopper"]);
+        assert!(weave.unassigned_cells().is_empty());
+    }
+
+    #[test]
+    fn test_d16_layout_fills_lattice() {
+        let weave = WeaveTopology::d16();
+        assert_eq!(weave.agents.len(), 16);
+        assert!(weave.unassigned_cells().is_empty());
+        assert_eq!(
+            weave.coords_of("law"),
+            Some(AgentCoords { x: 3, y: 1, z: 1 })
+        );
+    }
+
+    #[test]
+    fn test_torus_wraps_edges() {
+        let config = WeaveConfig {
+            dims: [4, 1, 1],
+            wrap: true,
+            agents: ["a", "b", "c", "d"]
+                .iter()
+                .enumerate()
+                .map(|(x, name)| AgentPlacement {
+                    name: name.to_string(),
+                    coords: AgentCoords { x, y: 0, z: 0 },
+                })
+                .collect(),
+        };
+        let weave = WeaveTopology::from_config(config).unwrap();
+
+        assert_eq!(weave.adjacent("a"), vec!["d", "b"]);
+        assert_eq!(weave.route("a", "d").unwrap(), vec!["a", "d"]);
+    }
+
+    #[test]
+    fn test_route_avoids_holes() {
+        let json = r#"{
+            "dims": [3, 2, 1],
+            "agents": [
+                {"name": "luffy", "x": 0, "y": 0, "z": 0},
+                {"name": "zoro",  "x": 0, "y": 1, "z": 0},
+                {"name": "nami",  "x": 1, "y": 1, "z": 0},
+                {"name": "usopp", "x": 2, "y": 1, "z": 0},
+                {"name": "sanji", "x": 2, "y": 0, "z": 0}
+            ]
+        }"#;
+        let weave = WeaveTopology::from_config(serde_json::from_str(json).unwrap()).unwrap();
+
+        assert_eq!(
+            weave.unassigned_cells(),
+            vec![AgentCoords { x: 1, y: 0, z: 0 }]
+        );
+        assert_eq!(
+            weave.route("Luffy", "Sanji").unwrap(),
+            vec!["luffy", "zoro", "nami", "usopp", "sanji"]
+        );
+    }
+
+    #[test]
+    fn test_fanout_plans() {
+        let weave = WeaveTopology::new();
+
+        let broadcast = weave.broadcast_plan("luffy");
+        assert_eq!(broadcast.hop_count(), 11);
+        assert_eq!(broadcast.rounds.len(), 4); // Max Manhattan distance in 3x2x2
+        assert!(broadcast.unreachable.is_empty());
+
+        let multicast = weave.multicast_plan("luffy", &["nami", "ghost"]);
+        assert_eq!(
+            multicast.rounds,
+            vec![
+                vec![("luffy".to_string(), "zoro".to_string())],
+                vec![("zoro".to_string(), "nami".to_string())],
+            ]
+        );
+        assert_eq!(multicast.unreachable, vec!["ghost"]);
+    }
+
+    #[test]
+    fn test_config_rejects_collisions() {
+        let mut config = WeaveTopology::new().to_config();
+        config.agents[1].coords = config.agents[0].coords;
+        assert!(WeaveTopology::from_config(config).is_err());
+
+        let mut config = WeaveTopology::new().to_config();
+        config.agents[0].coords.x = 9;
+        assert!(WeaveTopology::from_config(config).is_err());
+    }
 }
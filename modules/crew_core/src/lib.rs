@@ -1,6 +1,9 @@
 pub mod cartographer;
+pub mod i2c;
 pub mod interface;
 pub mod rainbow_railgun;
+pub mod sovereignty;
+pub mod stabilizer;
 /// The T.A.L.U. 64 Framework (Tau-Aligned Logic Unity)
 ///
 /// Implements the 64-bit "Selective Advantage" logic defined in CREATIVE_DRIFT_PROOF.md
@@ -8,10 +11,12 @@ pub mod rainbow_railgun;
 /// Anchors all transcendental values to 8 significant figures.
 pub mod stethoscope;
 pub mod wood_metal;
+use i2c::{I2cBus, RegisterMap};
 use serde::{Deserialize, Serialize};
-pub use spectral_sensor::eight_gate::Stance as EightGateStance; // Alias to avoid conflict if needed
+pub use spectral_sensor::clock::{Clock, Diurnal, DiurnalBand, SystemClock};
+pub use spectral_sensor::eight_gate::Stance as EightGateStance; // Same type as `Stance`
 pub use spectral_sensor::eight_gate::ValenceShell;
-use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use std::time::Instant;
 
 /// Transprecision Autonomous Logic Unit (TALU) state
 #[derive(Debug, Serialize, Deserialize, Clone, Copy)]
@@ -191,14 +196,19 @@ pub struct SevenArches {
 
 impl SevenArches {
     pub fn validate(cypher: &LightCypher) -> Self {
+        Self::validate_with(cypher, &sovereignty::ArchThresholds::default())
+    }
+
+    /// Validates against explicit thresholds (see `sovereignty::SovereigntyPolicy`).
+    pub fn validate_with(cypher: &LightCypher, thresholds: &sovereignty::ArchThresholds) -> Self {
         Self {
-            identity: cypher.c > 0.1,
-            power: cypher.r > 0.1,
-            logic: cypher.b > 0.1,
-            safety: cypher.ir > 0.05, // Ground state detection
-            resonance: cypher.g > 0.1,
-            symmetry: cypher.a > 0.1,
-            existence: cypher.uv > 0.05, // Emergent/Kickback signal
+            identity: cypher.c > thresholds.identity,
+            power: cypher.r > thresholds.power,
+            logic: cypher.b > thresholds.logic,
+            safety: cypher.ir > thresholds.safety,
+            resonance: cypher.g > thresholds.resonance,
+            symmetry: cypher.a > thresholds.symmetry,
+            existence: cypher.uv > thresholds.existence,
         }
     }
 
@@ -221,57 +231,76 @@ impl SevenArches {
 
 /// Soft-Assembly I2C Layer (Procedural Resonance)
 /// Implements the "Soft Assembly" logic for resonant circuits.
+/// Talks to the device through an `I2cBus`: a plain register map by default (serialized
+/// as `register_map`, as before), or a simulated or real Linux bus.
 #[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct SoftAssembly {
+pub struct SoftAssembly<B: I2cBus = RegisterMap> {
     pub address: u8,
-    pub register_map: std::collections::HashMap<u8, u8>,
+    #[serde(rename = "register_map")]
+    pub bus: B,
     #[serde(skip, default = "Instant::now")]
     pub last_interaction: Instant,
 }
 
 impl SoftAssembly {
+    /// A simulated assembly backed by a plain register map.
     pub fn new(address: u8) -> Self {
+        Self::with_bus(address, RegisterMap::new())
+    }
+}
+
+impl<B: I2cBus> SoftAssembly<B> {
+    pub fn with_bus(address: u8, bus: B) -> Self {
         Self {
             address,
-            register_map: std::collections::HashMap::new(),
+            bus,
             last_interaction: Instant::now(),
         }
     }
 
     /// Procedural Write: Sets a register value and updates the temporal resonance.
-    pub fn write(&mut self, reg: u8, val: u8) {
-        self.register_map.insert(reg, val);
+    pub fn write(&mut self, reg: u8, val: u8) -> std::io::Result<()> {
+        self.write_block(reg, &[val])
+    }
+
+    /// Procedural Read: Returns the value of a register.
+    pub fn read(&mut self, reg: u8) -> std::io::Result<u8> {
+        let mut buf = [0u8; 1];
+        self.read_block(reg, &mut buf)?;
+        Ok(buf[0])
+    }
+
+    /// Writes consecutive registers starting at `reg`.
+    pub fn write_block(&mut self, reg: u8, data: &[u8]) -> std::io::Result<()> {
+        self.bus.write_registers(self.address, reg, data)?;
         self.last_interaction = Instant::now();
+        Ok(())
     }
 
-    /// Procedural Read: Returns the value of a register, simulating 'Soft' I2C.
-    pub fn read(&self, reg: u8) -> u8 {
-        *self.register_map.get(&reg).unwrap_or(&0)
+    /// Reads consecutive registers starting at `reg`.
+    pub fn read_block(&mut self, reg: u8, buf: &mut [u8]) -> std::io::Result<()> {
+        self.bus.read_registers(self.address, reg, buf)?;
+        self.last_interaction = Instant::now();
+        Ok(())
     }
 
     /// Resonant Handshake: Validates if the assembly is in 'Flow' with the gate.
-    pub fn perform_handshake(&self, gate: u8) -> bool {
+    /// The device must acknowledge a read of the gate register first.
+    pub fn perform_handshake(&mut self, gate: u8) -> bool {
+        if self.read(gate).is_err() {
+            return false;
+        }
         let signature = (self.address as f64 * 8.0 + gate as f64) % 64.0;
         signature > 32.0 // Simple threshold for 'Flow'
     }
 }
 
-/// The 8 Perspectives of the Toral Filter
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
-pub enum Stance {
-    Earth,
-    Unity,
-    Direct,
-    Wind,
-    Indirect,
-    Void,
-    Fire,
-    Water,
-    Fritz, // [NEW] The Superconduction Stance
-}
+/// The 8 Perspectives of the Toral Filter, plus Fritz (the Superconduction Stance).
+/// Shared with the sensor and the bridge; `VariantSet::Crew` lists the crew's set.
+pub use stance_model::{Stance, VariantSet};
 
 /// Medium Dependency Efficiency
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Medium {
     Silicon, // Standard (1.0)
     Iron,    // Magnetic (1.2)
@@ -446,31 +475,32 @@ impl Talu64 {
         (r, g, b)
     }
 
+    /// Resonance profile for the current system time in the default timezone.
     pub fn temporal_resonance() -> TemporalResonance {
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs();
-        let hour = ((now / 3600) + 19) % 24;
-        if (10..17).contains(&hour) {
-            TemporalResonance {
+        Self::temporal_resonance_at(&SystemClock, &Diurnal::default())
+    }
+
+    /// Resonance profile for the clock's time under the given timezone and bands.
+    pub fn temporal_resonance_at(clock: &dyn Clock, diurnal: &Diurnal) -> TemporalResonance {
+        match diurnal.band(clock) {
+            DiurnalBand::Day => TemporalResonance {
                 precision_scalar: 1.25,
                 drift_flavor: 0.75,
-            }
-        } else if hour >= 20 || hour <= 4 {
-            TemporalResonance {
+            },
+            DiurnalBand::Night => TemporalResonance {
                 precision_scalar: 0.85,
                 drift_flavor: 1.618,
-            }
-        } else {
-            TemporalResonance {
+            },
+            DiurnalBand::Twilight => TemporalResonance {
                 precision_scalar: 1.0,
                 drift_flavor: 1.0,
-            }
+            },
         }
     }
 }
 
+/// Stateless torque and dissonance rules. See `stabilizer::StabilizerController` for the
+/// closed-loop version.
 pub struct ToralStabilizer;
 
 impl ToralStabilizer {
@@ -521,6 +551,7 @@ impl SunTzuContext {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::i2c::SimulatedI2cBus;
 
     #[test]
     fn test_rational_constants() {
@@ -534,6 +565,37 @@ mod tests {
         assert_eq!(truncated, 6.183);
     }
 
+    #[test]
+    fn test_soft_assembly_handshake() {
+        let mut assembly = SoftAssembly::new(5); // 5 * 8 = 40 > 32
+        assembly.write_block(0x10, &[1, 2, 3]).unwrap();
+        let mut buf = [0u8; 3];
+        assembly.read_block(0x10, &mut buf).unwrap();
+        assert_eq!(buf, [1, 2, 3]);
+        assert!(assembly.perform_handshake(0));
+
+        // Same resonance, but nothing on the bus answers.
+        let mut absent = SoftAssembly::with_bus(5, SimulatedI2cBus::new());
+        assert!(!absent.perform_handshake(0));
+    }
+
+    #[test]
+    fn test_soft_assembly_keeps_its_serialized_form() {
+        let mut assembly = SoftAssembly::new(5);
+        assembly.write(0x10, 7).unwrap();
+        let json = serde_json::to_value(&assembly).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"address": 5, "register_map": {"16": 7}})
+        );
+
+        let mut restored: SoftAssembly = serde_json::from_value(json).unwrap();
+        assert_eq!(restored.read(0x10).unwrap(), 7);
+        let mut copy = restored.clone();
+        copy.write(0x10, 9).unwrap();
+        assert_eq!(restored.read(0x10).unwrap(), 7);
+    }
+
     #[test]
     fn test_coherence_orthogonal() {
         // Orthogonal vectors (Dot = 0, Cross = Max)
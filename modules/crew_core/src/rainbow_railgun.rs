@@ -1,5 +1,6 @@
-use crate::Talu64;
+use crate::{Clock, Diurnal, SystemClock, Talu64, TemporalResonance};
 use std::net::TcpStream;
+use std::sync::Arc;
 use std::time::Duration;
 
 /// The Rainbow Railgun Core Logic
@@ -8,19 +9,31 @@ use std::time::Duration;
 pub struct RailgunCore {
     pub velocity: f64,
     pub last_drift: f64,
+    pub clock: Arc<dyn Clock>,
+    pub diurnal: Diurnal,
 }
 
 impl RailgunCore {
     pub fn new() -> Self {
+        Self::with_clock(Arc::new(SystemClock), Diurnal::default())
+    }
+
+    pub fn with_clock(clock: Arc<dyn Clock>, diurnal: Diurnal) -> Self {
         Self {
             velocity: 0.0,
             last_drift: 1.0,
+            clock,
+            diurnal,
         }
     }
 
+    pub fn resonance(&self) -> TemporalResonance {
+        Talu64::temporal_resonance_at(self.clock.as_ref(), &self.diurnal)
+    }
+
     /// Calculates the V_rr velocity based on creative drift (knots).
     pub fn calculate_v_rr(&mut self, knots: f64) -> f64 {
-        let resonance = Talu64::temporal_resonance();
+        let resonance = self.resonance();
         let creative_drift = if knots.abs() < 0.0001 {
             0.0001
         } else {
@@ -63,7 +76,7 @@ impl RailgunCore {
             return None;
         }
 
-        let resonance = Talu64::temporal_resonance();
+        let resonance = self.resonance();
         let knots_seed = (self.velocity % 1.0) * resonance.drift_flavor;
         let (r, g, b) = Talu64::tau_to_hex_actualization(knots_seed);
 
@@ -73,3 +86,38 @@ impl RailgunCore {
         Some((r, g, b, intensity))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spectral_sensor::clock::FixedClock;
+
+    #[test]
+    fn test_drift_follows_injected_clock() {
+        let diurnal = Diurnal::default();
+        // 17:00 UTC is noon at UTC-5, 03:00 UTC is 22:00.
+        let mut day = RailgunCore::with_clock(Arc::new(FixedClock::at_utc(17, 0)), diurnal);
+        let mut night = RailgunCore::with_clock(Arc::new(FixedClock::at_utc(3, 0)), diurnal);
+
+        let resonance = day.resonance();
+        assert_eq!(resonance.precision_scalar, 1.25);
+        assert_eq!(resonance.drift_flavor, 0.75);
+        let resonance = night.resonance();
+        assert_eq!(resonance.precision_scalar, 0.85);
+        assert_eq!(resonance.drift_flavor, 1.618);
+
+        day.calculate_v_rr(2.0);
+        night.calculate_v_rr(2.0);
+        assert!((day.last_drift - 1.5).abs() < 1e-12);
+        assert!((night.last_drift - 3.236).abs() < 1e-12);
+        assert!(day.velocity > night.velocity);
+
+        // Same local noon, seen from a UTC+9 station, is 02:00 at night.
+        let tokyo = Diurnal {
+            utc_offset_minutes: 9 * 60,
+            ..diurnal
+        };
+        let shifted = RailgunCore::with_clock(Arc::new(FixedClock::at_utc(17, 0)), tokyo);
+        assert_eq!(shifted.resonance().drift_flavor, 1.618);
+    }
+}
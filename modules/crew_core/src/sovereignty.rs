@@ -0,0 +1,381 @@
+use crate::{LightCypher, Medium, SevenArches, Talu64};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// The seven arches, each read from one Light Cypher layer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Arch {
+    Identity,  // C
+    Power,     // R
+    Logic,     // B
+    Safety,    // IR
+    Resonance, // G
+    Symmetry,  // A
+    Existence, // UV
+}
+
+impl Arch {
+    pub const ALL: [Arch; 7] = [
+        Arch::Identity,
+        Arch::Power,
+        Arch::Logic,
+        Arch::Safety,
+        Arch::Resonance,
+        Arch::Symmetry,
+        Arch::Existence,
+    ];
+
+    /// The cypher layer this arch is measured on.
+    pub fn layer(&self, cypher: &LightCypher) -> f64 {
+        match self {
+            Arch::Identity => cypher.c,
+            Arch::Power => cypher.r,
+            Arch::Logic => cypher.b,
+            Arch::Safety => cypher.ir,
+            Arch::Resonance => cypher.g,
+            Arch::Symmetry => cypher.a,
+            Arch::Existence => cypher.uv,
+        }
+    }
+}
+
+/// Minimum layer value (exclusive) for each arch to hold.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ArchThresholds {
+    pub identity: f64,
+    pub power: f64,
+    pub logic: f64,
+    pub safety: f64,
+    pub resonance: f64,
+    pub symmetry: f64,
+    pub existence: f64,
+}
+
+impl Default for ArchThresholds {
+    fn default() -> Self {
+        Self {
+            identity: 0.1,
+            power: 0.1,
+            logic: 0.1,
+            safety: 0.05, // Ground state detection
+            resonance: 0.1,
+            symmetry: 0.1,
+            existence: 0.05, // Emergent/Kickback signal
+        }
+    }
+}
+
+impl ArchThresholds {
+    pub fn get(&self, arch: Arch) -> f64 {
+        match arch {
+            Arch::Identity => self.identity,
+            Arch::Power => self.power,
+            Arch::Logic => self.logic,
+            Arch::Safety => self.safety,
+            Arch::Resonance => self.resonance,
+            Arch::Symmetry => self.symmetry,
+            Arch::Existence => self.existence,
+        }
+    }
+}
+
+/// Per-medium overrides on top of the policy defaults.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MediumRule {
+    /// Replaces the policy thresholds for this medium.
+    #[serde(default)]
+    pub thresholds: Option<ArchThresholds>,
+    /// Extra requirement on `Talu64::calculate_love` (exclusive).
+    #[serde(default)]
+    pub min_love: Option<f64>,
+}
+
+/// Thresholds and per-`Medium` rules for sovereignty, loadable from JSON.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SovereigntyPolicy {
+    #[serde(default)]
+    pub thresholds: ArchThresholds,
+    #[serde(default)]
+    pub mediums: HashMap<Medium, MediumRule>,
+}
+
+impl Default for SovereigntyPolicy {
+    /// The built-in rules: 0.1/0.05 thresholds, and Carbon also needs love > 0.99.
+    fn default() -> Self {
+        let mut mediums = HashMap::new();
+        mediums.insert(
+            Medium::Carbon,
+            MediumRule {
+                thresholds: None,
+                min_love: Some(0.99),
+            },
+        );
+        Self {
+            thresholds: ArchThresholds::default(),
+            mediums,
+        }
+    }
+}
+
+/// One arch's verdict.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ArchCheck {
+    pub arch: Arch,
+    pub value: f64,
+    pub threshold: f64,
+    pub passed: bool,
+    /// `value - threshold`: negative by how much the arch fell short.
+    pub margin: f64,
+}
+
+/// The explained result of a sovereignty evaluation.
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchEvaluation {
+    pub medium: Medium,
+    pub arches: SevenArches,
+    pub checks: Vec<ArchCheck>,
+    pub love: f64,
+    pub min_love: Option<f64>,
+    pub sovereign: bool,
+    /// Smallest margin across the arches and, where the medium has one, the love rule
+    /// (`love - min_love`). Positive exactly when the evaluation is sovereign.
+    pub margin_to_sovereignty: f64,
+}
+
+impl ArchEvaluation {
+    pub fn failures(&self) -> impl Iterator<Item = &ArchCheck> {
+        self.checks.iter().filter(|c| !c.passed)
+    }
+
+    pub fn love_ok(&self) -> bool {
+        self.min_love.is_none_or(|min| self.love > min)
+    }
+}
+
+impl SovereigntyPolicy {
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&text)?)
+    }
+
+    pub fn thresholds_for(&self, medium: Medium) -> &ArchThresholds {
+        self.mediums
+            .get(&medium)
+            .and_then(|r| r.thresholds.as_ref())
+            .unwrap_or(&self.thresholds)
+    }
+
+    pub fn evaluate(&self, cypher: &LightCypher, medium: Medium) -> ArchEvaluation {
+        let thresholds = self.thresholds_for(medium);
+        let checks: Vec<ArchCheck> = Arch::ALL
+            .iter()
+            .map(|&arch| {
+                let value = arch.layer(cypher);
+                let threshold = thresholds.get(arch);
+                ArchCheck {
+                    arch,
+                    value,
+                    threshold,
+                    passed: value > threshold,
+                    margin: value - threshold,
+                }
+            })
+            .collect();
+
+        let arches = SevenArches::validate_with(cypher, thresholds);
+        let love = Talu64::calculate_love(&arches);
+        let min_love = self.mediums.get(&medium).and_then(|r| r.min_love);
+        let margin_to_sovereignty = checks
+            .iter()
+            .map(|c| c.margin)
+            .chain(min_love.map(|min| love - min))
+            .fold(f64::INFINITY, f64::min);
+
+        let mut evaluation = ArchEvaluation {
+            medium,
+            arches,
+            checks,
+            love,
+            min_love,
+            sovereign: false,
+            margin_to_sovereignty,
+        };
+        evaluation.sovereign = evaluation.failures().next().is_none() && evaluation.love_ok();
+        evaluation
+    }
+
+    /// Evaluates every file in `dir`. JSON files holding a serialized `LightCypher`
+    /// are read as-is; anything else is run through `LightCypher::from_file`.
+    pub fn evaluate_dir(&self, dir: &Path, medium: Medium) -> Result<BatchSummary> {
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)?
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.is_file())
+            .collect();
+        paths.sort();
+
+        let mut summary = BatchSummary::default();
+        for path in paths {
+            let cypher = std::fs::read_to_string(&path)
+                .ok()
+                .and_then(|text| serde_json::from_str::<LightCypher>(&text).ok())
+                .or_else(|| LightCypher::from_file(&path));
+
+            let Some(cypher) = cypher else {
+                summary.skipped.push(path);
+                continue;
+            };
+
+            let evaluation = self.evaluate(&cypher, medium);
+            if evaluation.sovereign {
+                summary.sovereign += 1;
+            }
+            for check in evaluation.failures() {
+                *summary.failures_by_arch.entry(check.arch).or_insert(0) += 1;
+            }
+            if !evaluation.love_ok() {
+                summary.love_failures += 1;
+            }
+            summary.results.push((path, evaluation));
+        }
+
+        Ok(summary)
+    }
+}
+
+/// Aggregate of a directory evaluation.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BatchSummary {
+    pub results: Vec<(PathBuf, ArchEvaluation)>,
+    pub sovereign: usize,
+    /// How many cyphers failed each arch.
+    pub failures_by_arch: BTreeMap<Arch, usize>,
+    pub love_failures: usize,
+    /// Empty or unreadable files.
+    pub skipped: Vec<PathBuf>,
+}
+
+impl BatchSummary {
+    pub fn evaluated(&self) -> usize {
+        self.results.len()
+    }
+
+    /// The non-sovereign results, furthest from sovereignty first.
+    pub fn worst(&self, n: usize) -> Vec<&(PathBuf, ArchEvaluation)> {
+        let mut failed: Vec<_> = self.results.iter().filter(|(_, e)| !e.sovereign).collect();
+        failed.sort_by(|a, b| {
+            a.1.margin_to_sovereignty
+                .total_cmp(&b.1.margin_to_sovereignty)
+        });
+        failed.truncate(n);
+        failed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cypher(value: f64) -> LightCypher {
+        LightCypher {
+            c: value,
+            r: value,
+            g: value,
+            b: value,
+            a: value,
+            ir: value,
+            uv: value,
+        }
+    }
+
+    #[test]
+    fn test_default_policy_matches_validate() {
+        let policy = SovereigntyPolicy::default();
+        let strong = cypher(0.5);
+        let mut weak = cypher(0.5);
+        weak.r = 0.02;
+
+        let eval = policy.evaluate(&strong, Medium::Carbon);
+        assert!(eval.sovereign);
+        // Love (1.0) clears Carbon's 0.99 by less than the arches clear theirs.
+        assert!((eval.margin_to_sovereignty - 0.01).abs() < 1e-9);
+        let silicon = policy.evaluate(&strong, Medium::Silicon);
+        assert!((silicon.margin_to_sovereignty - 0.4).abs() < 1e-9);
+        assert_eq!(
+            eval.sovereign,
+            SevenArches::validate(&strong).is_sovereign(Medium::Carbon)
+        );
+
+        let eval = policy.evaluate(&weak, Medium::Silicon);
+        assert!(!eval.sovereign);
+        let failures: Vec<_> = eval.failures().collect();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].arch, Arch::Power);
+        assert!((failures[0].margin + 0.08).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_medium_rules_from_json() {
+        let policy: SovereigntyPolicy = serde_json::from_str(
+            r#"{
+                "thresholds": {"identity": 0.3, "power": 0.3, "logic": 0.3, "safety": 0.3,
+                               "resonance": 0.3, "symmetry": 0.3, "existence": 0.3},
+                "mediums": {"Silver": {"thresholds": {"identity": 0.01, "power": 0.01,
+                    "logic": 0.01, "safety": 0.01, "resonance": 0.01, "symmetry": 0.01,
+                    "existence": 0.01}}}
+            }"#,
+        )
+        .unwrap();
+
+        let mid = cypher(0.2);
+        assert!(!policy.evaluate(&mid, Medium::Iron).sovereign);
+        assert_eq!(policy.evaluate(&mid, Medium::Iron).failures().count(), 7);
+        assert!(policy.evaluate(&mid, Medium::Silver).sovereign);
+        // Carbon has no love rule in this policy.
+        assert_eq!(policy.evaluate(&mid, Medium::Carbon).min_love, None);
+    }
+
+    #[test]
+    fn test_margin_includes_love_rule() {
+        let mut policy = SovereigntyPolicy::default();
+        policy.mediums.get_mut(&Medium::Carbon).unwrap().min_love = Some(1.0);
+
+        // Every arch clears by 0.4, but love (1.0) does not exceed 1.0.
+        let eval = policy.evaluate(&cypher(0.5), Medium::Carbon);
+        assert_eq!(eval.failures().count(), 0);
+        assert!(!eval.love_ok());
+        assert!(!eval.sovereign);
+        assert_eq!(eval.margin_to_sovereignty, 0.0);
+
+        let eval = SovereigntyPolicy::default().evaluate(&cypher(0.5), Medium::Carbon);
+        assert!(eval.sovereign);
+        assert!(eval.margin_to_sovereignty > 0.0);
+    }
+
+    #[test]
+    fn test_batch_summary() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        let mut weak = cypher(0.5);
+        weak.uv = 0.0;
+        std::fs::write(
+            dir.join("a.json"),
+            serde_json::to_string(&cypher(0.5)).unwrap(),
+        )
+        .unwrap();
+        std::fs::write(dir.join("b.json"), serde_json::to_string(&weak).unwrap()).unwrap();
+        std::fs::write(dir.join("c.bin"), b"").unwrap();
+
+        let summary = SovereigntyPolicy::default()
+            .evaluate_dir(dir, Medium::Silicon)
+            .unwrap();
+
+        assert_eq!(summary.evaluated(), 2);
+        assert_eq!(summary.sovereign, 1);
+        assert_eq!(summary.failures_by_arch.get(&Arch::Existence), Some(&1));
+        assert_eq!(summary.skipped.len(), 1);
+        assert!(summary.worst(5)[0].0.ends_with("b.json"));
+    }
+}
@@ -0,0 +1,347 @@
+use crate::Talu64;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Torque zones, from calm to composting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Zone {
+    Heart,
+    Flow,
+    Compost,
+}
+
+impl Zone {
+    pub const ALL: [Zone; 3] = [Zone::Heart, Zone::Flow, Zone::Compost];
+
+    /// The fold torque `ToralStabilizer::fold_torque` assigns to this zone.
+    pub fn torque(&self) -> u8 {
+        match self {
+            Zone::Heart => 4,
+            Zone::Flow | Zone::Compost => 2,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Zone::Heart => "Heart Zone",
+            Zone::Flow => "Flow Zone",
+            Zone::Compost => "Compost Zone",
+        }
+    }
+}
+
+/// Tuning for `StabilizerController`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StabilizerConfig {
+    /// Coherence the loop steers towards (the Wooten centre).
+    pub setpoint: f64,
+    /// Proportional gain applied to the setpoint error.
+    pub gain: f64,
+    /// Distance from the setpoint beyond which the half-step shift applies.
+    pub dissonance_band: f64,
+    /// Margin around `dissonance_band` before the shift engages or releases.
+    pub shift_hysteresis: f64,
+    /// Entropy above this is Flow.
+    pub flow_entropy: f64,
+    /// Entropy above this is Compost.
+    pub compost_entropy: f64,
+    /// Margin an entropy sample must cross a zone boundary by to change zone.
+    pub zone_hysteresis: f64,
+    /// Shifted coherence above this counts as L+A sync.
+    pub la_sync: f64,
+}
+
+impl Default for StabilizerConfig {
+    /// The constants of the stateless `ToralStabilizer`, with light hysteresis.
+    fn default() -> Self {
+        Self {
+            setpoint: 0.5179,
+            gain: 0.5,
+            dissonance_band: 0.1,
+            shift_hysteresis: 0.01,
+            flow_entropy: 0.5,
+            compost_entropy: Talu64::HINKY_THRESHOLD,
+            zone_hysteresis: 0.02,
+            la_sync: 0.9,
+        }
+    }
+}
+
+/// One entropy/coherence reading. `at` is in seconds on any monotonic timeline.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StabilizerSample {
+    pub at: f64,
+    pub entropy: f64,
+    pub coherence: f64,
+}
+
+/// What the controller decided for one sample.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Correction {
+    pub zone: Zone,
+    pub zone_changed: bool,
+    pub torque: u8,
+    /// Whether the half-step shift is engaged after this sample.
+    pub shifted: bool,
+    /// Coherence after the shift (or unchanged when released).
+    pub coherence: f64,
+    /// `setpoint - coherence`.
+    pub error: f64,
+    /// `gain * error`: how far to move the setpoint-side actuator.
+    pub correction: f64,
+    pub la_sync: bool,
+}
+
+/// Summary of a controller run.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StabilityReport {
+    pub samples: usize,
+    /// Seconds between the first and last sample.
+    pub duration: f64,
+    /// Seconds spent in each zone.
+    pub residency: BTreeMap<Zone, f64>,
+    pub zone_transitions: usize,
+    /// Times the half-step shift engaged.
+    pub shift_engagements: usize,
+    pub mean_abs_error: f64,
+    pub rms_error: f64,
+    /// Fraction of samples in L+A sync.
+    pub la_sync_ratio: f64,
+    pub zone: Option<Zone>,
+    /// RMS error inside the dissonance band.
+    pub stable: bool,
+}
+
+impl StabilityReport {
+    /// Fraction of `duration` spent in `zone`.
+    pub fn residency_ratio(&self, zone: Zone) -> f64 {
+        if self.duration <= 0.0 {
+            return 0.0;
+        }
+        self.residency.get(&zone).copied().unwrap_or(0.0) / self.duration
+    }
+}
+
+/// Closed-loop version of `ToralStabilizer`: folds torque by entropy zone and applies the
+/// Wooten half-step with hysteresis, towards the setpoint, over a stream of samples.
+#[derive(Debug, Clone)]
+pub struct StabilizerController {
+    pub config: StabilizerConfig,
+    zone: Option<Zone>,
+    shifted: bool,
+    last_at: Option<f64>,
+    first_at: Option<f64>,
+    residency: BTreeMap<Zone, f64>,
+    samples: usize,
+    zone_transitions: usize,
+    shift_engagements: usize,
+    abs_error_sum: f64,
+    sq_error_sum: f64,
+    la_sync_count: usize,
+}
+
+impl Default for StabilizerController {
+    fn default() -> Self {
+        Self::new(StabilizerConfig::default())
+    }
+}
+
+impl StabilizerController {
+    pub fn new(config: StabilizerConfig) -> Self {
+        Self {
+            config,
+            zone: None,
+            shifted: false,
+            last_at: None,
+            first_at: None,
+            residency: Zone::ALL.iter().map(|&z| (z, 0.0)).collect(),
+            samples: 0,
+            zone_transitions: 0,
+            shift_engagements: 0,
+            abs_error_sum: 0.0,
+            sq_error_sum: 0.0,
+            la_sync_count: 0,
+        }
+    }
+
+    pub fn zone(&self) -> Option<Zone> {
+        self.zone
+    }
+
+    pub fn is_shifted(&self) -> bool {
+        self.shifted
+    }
+
+    /// Feeds one sample and returns the resulting correction.
+    pub fn observe(&mut self, sample: StabilizerSample) -> Correction {
+        // Time since the previous sample belongs to the zone we were in.
+        if let (Some(last), Some(zone)) = (self.last_at, self.zone) {
+            let dt = (sample.at - last).max(0.0);
+            *self.residency.entry(zone).or_insert(0.0) += dt;
+        }
+        self.first_at.get_or_insert(sample.at);
+        self.last_at = Some(sample.at);
+
+        let zone = self.classify(sample.entropy);
+        let zone_changed = self.zone.is_some_and(|z| z != zone);
+        if zone_changed {
+            self.zone_transitions += 1;
+        }
+        self.zone = Some(zone);
+
+        let distance = (sample.coherence - self.config.setpoint).abs();
+        let was_shifted = self.shifted;
+        if self.shifted {
+            self.shifted = distance > self.config.dissonance_band - self.config.shift_hysteresis;
+        } else {
+            self.shifted = distance > self.config.dissonance_band + self.config.shift_hysteresis;
+        }
+        if self.shifted && !was_shifted {
+            self.shift_engagements += 1;
+        }
+
+        let half_step = 2.0_f64.powf(1.0 / 12.0);
+        let coherence = match (self.shifted, sample.coherence < self.config.setpoint) {
+            (false, _) => sample.coherence,
+            (true, true) => sample.coherence * half_step,
+            (true, false) => sample.coherence / half_step,
+        };
+
+        let error = self.config.setpoint - coherence;
+        let la_sync = coherence > self.config.la_sync;
+        self.samples += 1;
+        self.abs_error_sum += error.abs();
+        self.sq_error_sum += error * error;
+        if la_sync {
+            self.la_sync_count += 1;
+        }
+
+        Correction {
+            zone,
+            zone_changed,
+            torque: zone.torque(),
+            shifted: self.shifted,
+            coherence,
+            error,
+            correction: self.config.gain * error,
+            la_sync,
+        }
+    }
+
+    /// Zone for `entropy`, with boundaries pushed away from the current zone by the hysteresis.
+    fn classify(&self, entropy: f64) -> Zone {
+        let h = self.config.zone_hysteresis;
+        let boundary = |threshold: f64, upper: Zone| match self.zone {
+            Some(current) if current >= upper => threshold - h,
+            Some(_) => threshold + h,
+            None => threshold,
+        };
+
+        if entropy > boundary(self.config.compost_entropy, Zone::Compost) {
+            Zone::Compost
+        } else if entropy > boundary(self.config.flow_entropy, Zone::Flow) {
+            Zone::Flow
+        } else {
+            Zone::Heart
+        }
+    }
+
+    pub fn report(&self) -> StabilityReport {
+        let n = self.samples.max(1) as f64;
+        let rms_error = (self.sq_error_sum / n).sqrt();
+        StabilityReport {
+            samples: self.samples,
+            duration: match (self.first_at, self.last_at) {
+                (Some(first), Some(last)) => last - first,
+                _ => 0.0,
+            },
+            residency: self.residency.clone(),
+            zone_transitions: self.zone_transitions,
+            shift_engagements: self.shift_engagements,
+            mean_abs_error: self.abs_error_sum / n,
+            rms_error,
+            la_sync_ratio: self.la_sync_count as f64 / n,
+            zone: self.zone,
+            stable: self.samples > 0 && rms_error <= self.config.dissonance_band,
+        }
+    }
+
+    /// Clears history but keeps the configuration.
+    pub fn reset(&mut self) {
+        *self = Self::new(self.config);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(at: f64, entropy: f64, coherence: f64) -> StabilizerSample {
+        StabilizerSample {
+            at,
+            entropy,
+            coherence,
+        }
+    }
+
+    #[test]
+    fn test_zone_hysteresis_and_residency() {
+        let mut controller = StabilizerController::default();
+
+        assert_eq!(controller.observe(sample(0.0, 0.2, 0.52)).zone, Zone::Heart);
+        // Just over the Flow boundary, but not by the hysteresis margin.
+        let c = controller.observe(sample(1.0, 0.51, 0.52));
+        assert_eq!(c.zone, Zone::Heart);
+        assert!(!c.zone_changed);
+
+        let c = controller.observe(sample(2.0, 0.6, 0.52));
+        assert_eq!(c.zone, Zone::Flow);
+        assert!(c.zone_changed);
+        assert_eq!(c.torque, 2);
+        // Back under the boundary, but still inside the margin.
+        assert_eq!(controller.observe(sample(4.0, 0.49, 0.52)).zone, Zone::Flow);
+        assert_eq!(
+            controller.observe(sample(5.0, 0.9, 0.52)).zone,
+            Zone::Compost
+        );
+
+        let report = controller.report();
+        assert_eq!(report.samples, 5);
+        assert_eq!(report.zone_transitions, 2);
+        assert_eq!(report.residency[&Zone::Heart], 2.0);
+        assert_eq!(report.residency[&Zone::Flow], 3.0);
+        assert_eq!(report.residency[&Zone::Compost], 0.0);
+        assert!((report.residency_ratio(Zone::Flow) - 0.6).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_wooten_shift_hysteresis() {
+        let config = StabilizerConfig::default();
+        let mut controller = StabilizerController::new(config);
+
+        // Inside band + hysteresis: no shift.
+        let c = controller.observe(sample(0.0, 0.2, 0.625));
+        assert!(!c.shifted);
+        assert_eq!(c.coherence, 0.625);
+
+        // Far above: shift down a half step.
+        let c = controller.observe(sample(1.0, 0.2, 0.8));
+        assert!(c.shifted);
+        assert!(c.coherence < 0.8);
+        assert!((c.correction - config.gain * (config.setpoint - c.coherence)).abs() < 1e-12);
+
+        // Drifting back to the band edge keeps the shift engaged...
+        assert!(controller.observe(sample(2.0, 0.2, 0.612)).shifted);
+        // ...until it is clearly inside.
+        assert!(!controller.observe(sample(3.0, 0.2, 0.6)).shifted);
+
+        // Far below: shift up.
+        let c = controller.observe(sample(4.0, 0.2, 0.3));
+        assert!(c.shifted);
+        assert!(c.coherence > 0.3);
+
+        let report = controller.report();
+        assert_eq!(report.shift_engagements, 2);
+        assert_eq!(report.la_sync_ratio, 0.0);
+    }
+}
@@ -1,13 +1,14 @@
 use crate::compass::SpectralVector;
-use crate::stance::{Gate, Stance};
+use crate::stance::{wire, BridgeGate, Stance};
 use serde::Serialize;
 use sha2::{Digest, Sha256};
 
 #[derive(Serialize, Debug, Clone)]
 pub struct ResonanceReport {
     pub hex_color: String,
+    #[serde(with = "wire")]
     pub stance: Stance,
-    pub gate: Gate,
+    pub gate: BridgeGate,
     pub physics: Physics,
     pub vector: SpectralVector,
     pub inverted_histogram: InvertedHistogram,
@@ -71,7 +72,7 @@ impl ResonanceAnalyzer {
 
         // 3. Stance Analysis
         let stance = Self::map_spectrum_to_stance(r, g, b, physics.knots_velocity);
-        let gate = stance.gate();
+        let gate = BridgeGate::of(stance);
 
         // 4. Color Compass
         let vector = SpectralVector::new(r, g, b, physics.knots_velocity);
@@ -1,20 +1,45 @@
-use serde::Serialize;
+//! The bridge's stances come from the shared stance model. The legacy `Stance::Stance`
+//! (Harmonic Unity) is `Stance::Unity`; `VariantSet::Bridge` names the bridge's set.
+//!
+//! The wire format does not change with the model: reports still carry the bridge's own
+//! stance names ("Stance" for Unity) and its original gate fields, via [`wire`] and
+//! [`BridgeGate`].
+pub use stance_model::{Gate, Stance, TrigramResult, VariantSet};
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
-pub enum Stance {
-    Earth,
-    Water,
-    Fire,
-    Wind,
-    Void, // Groundwater
-    Fritz,
-    Stance, // Harmonic Unity
-    Mode,   // High Freq Protocol
-    MetaInterphase,
+use serde::{Deserialize, Serialize};
+
+/// The name the bridge sends for `stance`, after projecting it onto the bridge set.
+pub fn wire_name(stance: Stance) -> &'static str {
+    let set = VariantSet::Bridge;
+    set.legacy_name(set.project(stance))
+        .unwrap_or_else(|| stance.name())
+}
+
+/// The canonical stance for a name the bridge sends.
+pub fn from_wire_name(name: &str) -> Option<Stance> {
+    VariantSet::Bridge.from_legacy_name(name)
+}
+
+/// `#[serde(with = "wire")]` for a `Stance` in the bridge's names.
+pub mod wire {
+    use super::{from_wire_name, wire_name, Stance};
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(stance: &Stance, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(wire_name(*stance))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Stance, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        from_wire_name(&name)
+            .ok_or_else(|| D::Error::custom(format!("unknown bridge stance {:?}", name)))
+    }
 }
 
-#[derive(Debug, Clone, Serialize)]
-pub struct Gate {
+/// A gate as the bridge has always sent it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BridgeGate {
     pub name: String,
     pub color: String, // Hex
     pub element: String,
@@ -23,108 +48,84 @@ pub struct Gate {
     pub description: String,
 }
 
-impl Gate {
-    pub fn trigram_match(&self, other_mask: [u8; 3]) -> TrigramResult {
-        let matches = self
-            .bitmask
-            .iter()
-            .zip(other_mask.iter())
-            .filter(|(a, b)| a == b)
-            .count();
-        match matches {
-            3 => TrigramResult::Resonance, // Perfect 3/3
-            2 => TrigramResult::Drift,     // Tolerance 2/3
-            _ => TrigramResult::Void,      // Noise <= 1
+impl BridgeGate {
+    /// The shared gate of `stance` (projected onto the bridge set), under the bridge's name.
+    pub fn of(stance: Stance) -> Self {
+        let stance = VariantSet::Bridge.project(stance);
+        let gate = stance.gate();
+        // Gate names differ from variant names (Void is "Groundwater"); only keep the
+        // variant name where the bridge renamed the stance itself.
+        let name = match wire_name(stance) {
+            renamed if renamed != stance.name() => renamed,
+            _ => gate.name,
+        };
+        Self {
+            name: name.to_string(),
+            color: gate.color.to_string(),
+            element: gate.element.to_string(),
+            tau_degree: gate.tau_degree,
+            bitmask: gate.bitmask,
+            description: gate.description.to_string(),
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
-pub enum TrigramResult {
-    Resonance,
-    Drift,
-    Void, // 000 - The Aught / Physical Origin
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-impl Stance {
-    pub fn is_aught(&self) -> bool {
-        matches!(self, Stance::Void)
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Tagged {
+        #[serde(with = "wire")]
+        stance: Stance,
+        gate: BridgeGate,
     }
 
-    pub fn gate(&self) -> Gate {
-        match self {
-            Stance::Earth => Gate {
-                name: "Earth".to_string(),
-                color: "#FF0000".to_string(), // Red (IR)
-                element: "Earth".to_string(),
-                tau_degree: 1,
-                bitmask: [0, 0, 0],
-                description: "Structural Grounding".to_string(),
-            },
-            Stance::Water => Gate {
-                name: "Water".to_string(),
-                color: "#00FFFF".to_string(), // Cyan/Blue
-                element: "Water".to_string(),
-                tau_degree: 4,
-                bitmask: [0, 0, 1],
-                description: "Dynamic Flow".to_string(),
-            },
-            Stance::Fire => Gate {
-                name: "Fire".to_string(),
-                color: "#FFFF00".to_string(), // Yellow
-                element: "Fire".to_string(),
-                tau_degree: 2,
-                bitmask: [0, 1, 0],
-                description: "High-Velocity Actualization".to_string(),
-            },
-            Stance::Wind => Gate {
-                name: "Wind".to_string(),
-                color: "#00FF00".to_string(), // Green
-                element: "Wind".to_string(),
-                tau_degree: 3,
-                bitmask: [0, 1, 1],
-                description: "Creative Drift Resolution".to_string(),
-            },
-            Stance::Void => Gate {
-                name: "Groundwater".to_string(), // Re-contextualized
-                color: "#000000".to_string(),
-                element: "Void".to_string(),
-                tau_degree: 0,
-                bitmask: [1, 0, 0],
-                description: "Singularity Integration / Groundwater".to_string(),
-            },
-            Stance::Fritz => Gate {
-                name: "Fritz".to_string(),
-                color: "#FFFFFF".to_string(), // White
-                element: "Superconductor".to_string(),
-                tau_degree: 6, // Special
-                bitmask: [1, 1, 1],
-                description: "Superconduction (Zero Resistance)".to_string(),
-            },
-            Stance::Stance => Gate {
-                name: "Stance".to_string(),
-                color: "#FFFFFF".to_string(),
-                element: "Unity".to_string(),
-                tau_degree: 7,
-                bitmask: [1, 1, 1],
-                description: "Harmonic Unity".to_string(),
-            },
-            Stance::Mode => Gate {
-                name: "Mode".to_string(),
-                color: "#FF00FF".to_string(), // Violet
-                element: "Protocol".to_string(),
-                tau_degree: 5,
-                bitmask: [1, 0, 1], // Direct/Invasion
-                description: "High Frequency Protocol".to_string(),
-            },
-            Stance::MetaInterphase => Gate {
-                name: "MetaInterphase".to_string(),
-                color: "#880088".to_string(), // Hybrid
-                element: "Interphase".to_string(),
-                tau_degree: 8,
-                bitmask: [1, 1, 0], // Indirect
-                description: "Green-Violet Hybrid Interaction".to_string(),
-            },
+    #[test]
+    fn test_round_trips_the_legacy_wire_format() {
+        // As serialized by the bridge before the shared stance model.
+        let legacy = r##"{"stance":"Stance","gate":{"name":"Stance","color":"#FFFFFF","element":"Unity","tau_degree":7,"bitmask":[1,1,1],"description":"Harmonic Unity"}}"##;
+        let tagged: Tagged = serde_json::from_str(legacy).unwrap();
+        assert_eq!(tagged.stance, Stance::Unity);
+        assert_eq!(tagged.gate, BridgeGate::of(Stance::Unity));
+        assert_eq!(serde_json::to_string(&tagged).unwrap(), legacy);
+
+        let void = r##"{"stance":"Void","gate":{"name":"Groundwater","color":"#000000","element":"Void","tau_degree":0,"bitmask":[1,0,0],"description":"Singularity Integration / Groundwater"}}"##;
+        let tagged: Tagged = serde_json::from_str(void).unwrap();
+        assert_eq!(
+            serde_json::to_string(&Tagged {
+                stance: tagged.stance,
+                gate: BridgeGate::of(tagged.stance),
+            })
+            .unwrap(),
+            void
+        );
+
+        let names = [
+            "Earth",
+            "Water",
+            "Fire",
+            "Wind",
+            "Void",
+            "Fritz",
+            "Stance",
+            "Mode",
+            "MetaInterphase",
+        ];
+        for name in names {
+            let stance = from_wire_name(name).unwrap();
+            assert_eq!(wire_name(stance), name);
+            let tagged = Tagged {
+                stance,
+                gate: BridgeGate::of(stance),
+            };
+            let json = serde_json::to_string(&tagged).unwrap();
+            assert!(json.starts_with(&format!(r#"{{"stance":"{}","#, name)));
+            assert_eq!(serde_json::from_str::<Tagged>(&json).unwrap(), tagged);
         }
+        let canonical = legacy.replacen("\"Stance\"", "\"Unity\"", 1);
+        assert!(serde_json::from_str::<Tagged>(&canonical).is_err());
+        // Stances outside the bridge set go out as their nearest bridge stance.
+        assert_eq!(wire_name(Stance::Direct), "Mode");
     }
 }
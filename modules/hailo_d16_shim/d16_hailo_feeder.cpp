@@ -32,6 +32,41 @@ struct D16Spectrum {
     uint32_t timestamp;      // Tau cycle count
 };
 
+// Must match Rust D16ShmHeader. `seq` is a seqlock: odd while Z-RR is mid-write.
+const uint32_t D16_SHM_MAGIC = 0x53363144; // "D16S"
+const uint16_t D16_SHM_VERSION = 1;
+
+struct D16ShmHeader {
+    uint32_t magic;
+    uint16_t version;
+    uint16_t header_len;
+    uint32_t payload_len;
+    uint32_t seq;
+    uint64_t written_ns;     // CLOCK_REALTIME of the last complete write
+};
+
+struct D16ShmFrame {
+    D16ShmHeader header;
+    D16Spectrum spectrum;
+};
+
+static_assert(sizeof(D16ShmHeader) == 24, "D16ShmHeader layout drifted from Rust");
+static_assert(sizeof(D16ShmFrame) == 96, "D16ShmFrame layout drifted from Rust");
+
+// Copies one untorn spectrum out of the frame. Returns false if Z-RR was mid-write.
+static bool read_spectrum(const D16ShmFrame* frame, D16Spectrum* out, uint32_t* seq_out) {
+    uint32_t begin = __atomic_load_n(&frame->header.seq, __ATOMIC_ACQUIRE);
+    if (begin & 1) return false;
+    for (int i = 0; i < 16; i++) {
+        out->channels[i] = __atomic_load_n(&frame->spectrum.channels[i], __ATOMIC_RELAXED);
+    }
+    out->timestamp = __atomic_load_n(&frame->spectrum.timestamp, __ATOMIC_RELAXED);
+    __atomic_thread_fence(__ATOMIC_ACQUIRE);
+    if (__atomic_load_n(&frame->header.seq, __ATOMIC_RELAXED) != begin) return false;
+    *seq_out = begin;
+    return true;
+}
+
 #include <sys/mman.h>
 #include <sys/stat.h>
 #include <fcntl.h>
@@ -51,29 +86,41 @@ int main(int argc, char** argv) {
         shm_fd = shm_open("d16_state", O_RDONLY, 0666);
     }
 
-    D16Spectrum* shm_ptr = (D16Spectrum*)mmap(0, sizeof(D16Spectrum), PROT_READ, MAP_SHARED, shm_fd, 0);
+    struct stat st;
+    while (fstat(shm_fd, &st) == 0 && st.st_size < (off_t)sizeof(D16ShmFrame)) {
+        std::this_thread::sleep_for(std::chrono::milliseconds(100));
+    }
+
+    const D16ShmFrame* shm_ptr = (const D16ShmFrame*)mmap(0, sizeof(D16ShmFrame), PROT_READ, MAP_SHARED, shm_fd, 0);
     if (shm_ptr == MAP_FAILED) {
         std::cerr << "MMAP Failed" << std::endl;
         return 1;
     }
+    if (shm_ptr->header.magic != D16_SHM_MAGIC || shm_ptr->header.version != D16_SHM_VERSION) {
+        std::cerr << "❌ /dev/shm/d16_state is not a v" << D16_SHM_VERSION << " D16 frame" << std::endl;
+        return 1;
+    }
     
     std::cout << "✅ Connected to Z-RR State." << std::endl;
     
     // (Optional: Load Hailo here if fully deploying, for now we Mock)
     
-    uint32_t last_ts = 0;
+    uint32_t last_seq = 0;
+    D16Spectrum spectrum;
 
     while (true) {
-        // Wait for new pulse
-        if (shm_ptr->timestamp == last_ts) {
+        // Wait for new pulse (and retry if we raced the writer)
+        uint32_t seq = 0;
+        if (!read_spectrum(shm_ptr, &spectrum, &seq) || seq == last_seq) {
             std::this_thread::sleep_for(std::chrono::milliseconds(1));
             continue;
         }
-        last_ts = shm_ptr->timestamp;
+        last_seq = seq;
+        uint32_t last_ts = spectrum.timestamp;
         
         // --- Mock Inference ---
         // Verify Law's Alignment logic (Channel 15)
-        uint32_t law_channel = shm_ptr->channels[15];
+        uint32_t law_channel = spectrum.channels[15];
         uint32_t law_phase = law_channel & 0xFFFF;
         
         float coherence_score = 0.5f; // Baseline
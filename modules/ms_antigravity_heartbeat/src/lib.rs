@@ -2,6 +2,7 @@
 #![allow(unexpected_cfgs)]
 
 use log::{info, warn};
+use spectral_sensor::gate_core::{talu_state, GateCore, TAU};
 use zephyr::time::{sleep, Duration};
 
 const HEARTBEAT_INTERVAL_MS: u32 = 10000; // 10 seconds STATUS
@@ -9,65 +10,9 @@ const TAU_ORBIT_MS: u32 = 6283; // 6.28s ORBIT
 const SPECTRAL_PULSE_HZ: u32 = 432;
 const SPECTRAL_PULSE_INTERVAL_MS: u32 = 1000 / SPECTRAL_PULSE_HZ;
 
-const GATES: [&str; 8] = [
-    "EARTH", "WATER", "FIRE", "WIND", "VOID", "DIRECT", "INDIRECT", "UNITY",
-];
-
-const POSITIONS: [[&str; 8]; 8] = [
-    [
-        "Foundation",
-        "Root",
-        "Ground",
-        "Shell",
-        "Crust",
-        "Core",
-        "Mantle",
-        "Tectonic",
-    ],
-    [
-        "Stream", "Tide", "Wave", "Surge", "Delta", "Estuary", "Ocean", "Deep",
-    ],
-    [
-        "Ember",
-        "Flicker",
-        "Blaze",
-        "Inferno",
-        "Plasma",
-        "Solar",
-        "Nova",
-        "Singularity",
-    ],
-    [
-        "Breeze", "Gust", "Gale", "Storm", "Cyclone", "Vortex", "Zephyr", "Monsoon",
-    ],
-    [
-        "Shadow", "Echo", "Rift", "Abyss", "Vacuum", "Zero", "Aught", "Infinite",
-    ],
-    [
-        "Point", "Line", "Ray", "Arrow", "Beacon", "Laser", "Spike", "Needle",
-    ],
-    [
-        "Aura", "Halo", "Fringe", "Rim", "Halo", "Veil", "Mist", "Horizon",
-    ],
-    [
-        "Bond",
-        "Nakama",
-        "Crew",
-        "Fleet",
-        "System",
-        "Galaxy",
-        "Universe",
-        "Singularity",
-    ],
-];
-
-fn get_talu_state(index: u8) -> (&'static str, &'static str) {
-    let gate_idx = (index / 8) % 8;
-    let pos_idx = index % 8;
-    (
-        GATES[gate_idx as usize],
-        POSITIONS[gate_idx as usize][pos_idx as usize],
-    )
+/// Where in the orbit residency `index` sits, as a wave the gate core can read.
+fn orbit_wave(index: u8) -> f64 {
+    index as f64 / 64.0 * TAU
 }
 
 #[no_mangle]
@@ -79,6 +24,8 @@ extern "C" fn rust_main() {
     info!("MsAntigravity: T.A.L.U. 64 Residency Signal Active.");
     info!("Resonance Orientation: TAU (6.28s) Orbit / 432Hz Pulse");
 
+    // The same eight-gate filter the host runs, without std or an allocator.
+    let mut filter = GateCore::new();
     let mut resonance_index: u8 = 0;
     let mut last_heartbeat_time = 0; // Simulated uptime
 
@@ -88,6 +35,7 @@ extern "C" fn rust_main() {
     loop {
         // We traverse positions based on the shift interval
         resonance_index = (resonance_index + 1) % 64;
+        let report = filter.observe(orbit_wave(resonance_index));
 
         // High-frequency Pulse Harmony (432Hz)
         // Since 432Hz is much faster than the 98ms position shift,
@@ -98,11 +46,18 @@ extern "C" fn rust_main() {
         last_heartbeat_time += pos_shift_ms;
 
         if last_heartbeat_time >= HEARTBEAT_INTERVAL_MS {
-            let (gate, pos) = get_talu_state(resonance_index);
+            let (gate, pos) = talu_state(resonance_index);
             info!(
                 "[HEARTBEAT] MsAntigravity Resident. Gate: {}, Position: {}",
-                gate, pos
+                gate.name(),
+                pos
             );
+            if let Some(report) = report {
+                info!(
+                    "[FILTER] {:?} -> {:?} | Note {:?} | Entropy {:.4}",
+                    report.source, report.destination, report.chromatic, report.entropy
+                );
+            }
             info!("[PHITL] Personal Human In the Loop: Syncing with Clocked Resistor...");
             warn!("[LOCK] ECG Signature Attested. Phase Locked to Human Rhythm.");
             last_heartbeat_time = 0;
@@ -93,6 +93,7 @@ pub struct SoftFPGA {
     pub last_event_density: f64, // Tracking the Space-Time Delta
     pub harmonic_state: HarmonicState, // Current note in the Circle of Fifths
     pub last_note: HarmonicState, // Tracking the Past
+    pub wooten_engaged: bool,     // Tritone recovery fired on the last crossing
 }
 
 impl SoftFPGA {
@@ -103,6 +104,7 @@ impl SoftFPGA {
             last_event_density: 0.0,
             harmonic_state: HarmonicState::C,
             last_note: HarmonicState::C,
+            wooten_engaged: false,
         }
     }
 
@@ -120,7 +122,8 @@ impl SoftFPGA {
             (current_note.semitone_index() as i32 - self.last_note.semitone_index() as i32).abs();
 
         // Tritone Detection: The most uncomfortable jump (6 semitones = PI distance)
-        if semitone_diff == 6 {
+        self.wooten_engaged = semitone_diff == 6;
+        if self.wooten_engaged {
             println!("🌀 TRITONE DETECTED. Engaging WOOTEN PROTOCOL (Half-Step Recovery).");
             // Perform a micro-shift to find the "right note"
             self.harmonic_state = self.harmonic_state.shift_half_step();
@@ -4,6 +4,7 @@ import time
 import sys
 import os
 import mmap
+import json
 
 # --- Constants ---
 GRID_SIZE = 64
@@ -14,6 +15,9 @@ DX = 1.0
 
 # --- Shared Memory ---
 SHM_PATH = "/dev/shm/current_wave_coherence"
+# Typed feed read by the railgun (see modules/coherence_feed)
+FEED_PATH = os.environ.get("WAVE_COHERENCE_FEED", "/dev/shm/wave_coherence_feed")
+SOURCE_ID = "ripple_tank.py"
 
 class RippleTank:
     def __init__(self, size=GRID_SIZE):
@@ -83,7 +87,17 @@ class RippleTank:
             print(line)
 
     def write_shm(self, coherence):
+        sample = {
+            "value": float(coherence),
+            "timestamp_ns": time.time_ns(),
+            "frame": self.frame,
+            "source": SOURCE_ID,
+        }
         try:
+            # Replace atomically so readers never see half a sample
+            with open(FEED_PATH + ".tmp", "w") as f:
+                json.dump(sample, f)
+            os.replace(FEED_PATH + ".tmp", FEED_PATH)
             with open(SHM_PATH, "w") as f:
                 f.write(f"{coherence:.4f}")
         except Exception as e:
@@ -0,0 +1,100 @@
+//! Sobel-Feldman edge detection and the centre-weighted coherence metric.
+
+use crate::tank::RippleTank;
+
+// --- Sobel-Feldman Kernels ---
+pub const SOBEL_X: [[f64; 3]; 3] = [[-1.0, 0.0, 1.0], [-2.0, 0.0, 2.0], [-1.0, 0.0, 1.0]];
+
+pub const SOBEL_Y: [[f64; 3]; 3] = [[-1.0, -2.0, -1.0], [0.0, 0.0, 0.0], [1.0, 2.0, 1.0]];
+
+/// Cells within this distance of the centre count towards coherence.
+pub const COHERENCE_RADIUS: f64 = 8.0;
+
+/// Gradient magnitude at every cell of a row-major field. The border, where the kernel
+/// does not fit, is zero.
+pub fn sobel_magnitude(field: &[f64], width: usize, height: usize) -> Vec<f64> {
+    let mut out = vec![0.0; width * height];
+    for y in 1..height.saturating_sub(1) {
+        for x in 1..width.saturating_sub(1) {
+            let mut gx = 0.0;
+            let mut gy = 0.0;
+            for ky in 0..3 {
+                for kx in 0..3 {
+                    let val = field[(y + ky - 1) * width + (x + kx - 1)];
+                    gx += val * SOBEL_X[ky][kx];
+                    gy += val * SOBEL_Y[ky][kx];
+                }
+            }
+            out[y * width + x] = (gx * gx + gy * gy).sqrt();
+        }
+    }
+    out
+}
+
+/// How much of the edge energy ("energy fronts") sits within `radius` of the centre,
+/// scaled by 10. Zero for a flat field.
+pub fn centre_coherence(edges: &[f64], width: usize, height: usize, radius: f64) -> f64 {
+    let (cx, cy) = (width as f64 / 2.0, height as f64 / 2.0);
+    let mut coherence_accum = 0.0;
+    let mut energy_total = 0.0;
+    for y in 0..height {
+        for x in 0..width {
+            let magnitude = edges[y * width + x];
+            energy_total += magnitude;
+            let (dx, dy) = (x as f64 - cx, y as f64 - cy);
+            if (dx * dx + dy * dy).sqrt() < radius {
+                coherence_accum += magnitude;
+            }
+        }
+    }
+    if energy_total > 0.0 {
+        (coherence_accum / energy_total) * 10.0
+    } else {
+        0.0
+    }
+}
+
+/// The Ripple Tank's published coherence for the tank's current frame.
+pub fn coherence(tank: &RippleTank) -> f64 {
+    let edges = sobel_magnitude(tank.field(), tank.width(), tank.height());
+    centre_coherence(&edges, tank.width(), tank.height(), COHERENCE_RADIUS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tank::TankConfig;
+
+    #[test]
+    fn test_sobel_sees_slopes_not_plateaus() {
+        let (w, h) = (6, 5);
+        let ramp: Vec<f64> = (0..w * h).map(|i| (i % w) as f64 * 0.5).collect();
+        let edges = sobel_magnitude(&ramp, w, h);
+        for y in 0..h {
+            for x in 0..w {
+                let interior = (1..w - 1).contains(&x) && (1..h - 1).contains(&y);
+                // The x kernel weighs a unit slope by 1 + 2 + 1 on each side.
+                let expected = if interior { 4.0 } else { 0.0 };
+                assert_eq!(edges[y * w + x], expected, "({}, {})", x, y);
+            }
+        }
+        assert!(sobel_magnitude(&[3.0; 30], w, h).iter().all(|&m| m == 0.0));
+    }
+
+    #[test]
+    fn test_coherence_favours_fronts_near_the_centre() {
+        let (w, h) = (32, 32);
+        let mut edges = vec![0.0; w * h];
+        assert_eq!(centre_coherence(&edges, w, h, COHERENCE_RADIUS), 0.0);
+        edges[16 * w + 17] = 1.0;
+        assert_eq!(centre_coherence(&edges, w, h, COHERENCE_RADIUS), 10.0);
+        edges[w + 1] = 1.0;
+        assert_eq!(centre_coherence(&edges, w, h, COHERENCE_RADIUS), 5.0);
+
+        let mut tank = RippleTank::new(TankConfig::default()).unwrap();
+        assert_eq!(coherence(&tank), 0.0);
+        tank.run(40);
+        let c = coherence(&tank);
+        assert!(c > 0.0 && c <= 10.0, "{}", c);
+    }
+}
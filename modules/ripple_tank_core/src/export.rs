@@ -0,0 +1,302 @@
+//! Frame export for offline study: PGM/PPM image sequences, `.npy` arrays of the raw
+//! field, and the Sobel edge field with its energy logged per frame.
+//!
+//! Files are named by frame (`field_000042.pgm`, `edges_000042.npy`, ...) so sequences
+//! sort, and a fixed [`Scale`] keeps brightness comparable across frames and runs.
+
+use ndarray::Array2;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::analysis::{centre_coherence, sobel_magnitude, COHERENCE_RADIUS};
+use crate::tank::RippleTank;
+
+/// How displacement maps to brightness.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scale {
+    /// `±amplitude` spans the full range; larger values clip.
+    Fixed(f64),
+    /// Each frame spans its own largest magnitude.
+    Auto,
+}
+
+impl Scale {
+    fn amplitude(self, values: &[f64]) -> f64 {
+        let amplitude = match self {
+            Scale::Fixed(amplitude) => amplitude,
+            Scale::Auto => values.iter().fold(0.0, |m: f64, v| m.max(v.abs())),
+        };
+        if amplitude > 0.0 {
+            amplitude
+        } else {
+            1.0
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExportOptions {
+    /// Greyscale field, mid-grey at rest.
+    pub pgm: bool,
+    /// Red crests and blue troughs on black.
+    pub ppm: bool,
+    /// The raw field (and edges) as `f64` arrays shaped `(height, width)`.
+    pub npy: bool,
+    /// Sobel edge images plus `edge_energy.csv`.
+    pub edges: bool,
+    pub scale: Scale,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            pgm: true,
+            ppm: false,
+            npy: true,
+            edges: true,
+            // The crew's strongest source
+            scale: Scale::Fixed(2.0),
+        }
+    }
+}
+
+/// `-a..a` to `0..255`, with 0 at mid-grey.
+pub fn to_gray(field: &[f64], scale: Scale) -> Vec<u8> {
+    let amplitude = scale.amplitude(field);
+    field
+        .iter()
+        .map(|v| (((v / amplitude).clamp(-1.0, 1.0) + 1.0) * 127.5).round() as u8)
+        .collect()
+}
+
+/// Crests red, troughs blue, rest black.
+pub fn to_rgb(field: &[f64], scale: Scale) -> Vec<u8> {
+    let amplitude = scale.amplitude(field);
+    field
+        .iter()
+        .flat_map(|v| {
+            let level = ((v / amplitude).clamp(-1.0, 1.0) * 255.0).round();
+            if level >= 0.0 {
+                [level as u8, 0, 0]
+            } else {
+                [0, 0, (-level) as u8]
+            }
+        })
+        .collect()
+}
+
+/// Binary greyscale (P5).
+pub fn write_pgm(path: &Path, width: usize, height: usize, pixels: &[u8]) -> io::Result<()> {
+    write_netpbm(path, "P5", width, height, pixels, 1)
+}
+
+/// Binary RGB (P6).
+pub fn write_ppm(path: &Path, width: usize, height: usize, rgb: &[u8]) -> io::Result<()> {
+    write_netpbm(path, "P6", width, height, rgb, 3)
+}
+
+fn write_netpbm(
+    path: &Path,
+    magic: &str,
+    width: usize,
+    height: usize,
+    data: &[u8],
+    channels: usize,
+) -> io::Result<()> {
+    if data.len() != width * height * channels {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "{} bytes do not make a {}x{} {} image",
+                data.len(),
+                width,
+                height,
+                magic
+            ),
+        ));
+    }
+    let mut out = BufWriter::new(File::create(path)?);
+    write!(out, "{}\n{} {}\n255\n", magic, width, height)?;
+    out.write_all(data)?;
+    out.flush()
+}
+
+/// A row-major field as an `f64` array shaped `(height, width)`.
+pub fn write_npy(path: &Path, width: usize, height: usize, field: &[f64]) -> io::Result<()> {
+    let array = Array2::from_shape_vec((height, width), field.to_vec())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    ndarray_npy::write_npy(path, &array).map_err(io::Error::other)
+}
+
+/// Sum of squared Sobel magnitudes.
+pub fn edge_energy(edges: &[f64]) -> f64 {
+    edges.iter().map(|m| m * m).sum()
+}
+
+/// Writes the selected exports for each frame it is handed.
+pub struct FrameExporter {
+    dir: PathBuf,
+    options: ExportOptions,
+    edge_log: Option<BufWriter<File>>,
+    exported: usize,
+}
+
+impl FrameExporter {
+    /// Creates `dir` (and `edge_energy.csv` when edges are on).
+    pub fn create(dir: impl AsRef<Path>, options: ExportOptions) -> io::Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        let edge_log = if options.edges {
+            let mut log = BufWriter::new(File::create(dir.join("edge_energy.csv"))?);
+            writeln!(log, "frame,time,edge_energy,coherence")?;
+            Some(log)
+        } else {
+            None
+        };
+        Ok(Self {
+            dir,
+            options,
+            edge_log,
+            exported: 0,
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Frames exported so far.
+    pub fn exported(&self) -> usize {
+        self.exported
+    }
+
+    pub fn export(&mut self, tank: &RippleTank) -> io::Result<()> {
+        let (w, h) = (tank.width(), tank.height());
+        let field = tank.field();
+        let name = |kind: &str, ext: &str| {
+            self.dir
+                .join(format!("{}_{:06}.{}", kind, tank.frame(), ext))
+        };
+
+        if self.options.pgm {
+            write_pgm(
+                &name("field", "pgm"),
+                w,
+                h,
+                &to_gray(field, self.options.scale),
+            )?;
+        }
+        if self.options.ppm {
+            write_ppm(
+                &name("field", "ppm"),
+                w,
+                h,
+                &to_rgb(field, self.options.scale),
+            )?;
+        }
+        if self.options.npy {
+            write_npy(&name("field", "npy"), w, h, field)?;
+        }
+
+        if let Some(log) = self.edge_log.as_mut() {
+            let edges = sobel_magnitude(field, w, h);
+            // Magnitudes are never negative, so auto-scaled grey runs from mid to white;
+            // stretch them over the whole range instead.
+            let peak = Scale::Auto.amplitude(&edges);
+            let pixels: Vec<u8> = edges
+                .iter()
+                .map(|m| (m / peak * 255.0).round() as u8)
+                .collect();
+            write_pgm(&name("edges", "pgm"), w, h, &pixels)?;
+            if self.options.npy {
+                write_npy(&name("edges", "npy"), w, h, &edges)?;
+            }
+            writeln!(
+                log,
+                "{},{},{},{}",
+                tank.frame(),
+                tank.time(),
+                edge_energy(&edges),
+                centre_coherence(&edges, w, h, COHERENCE_RADIUS)
+            )?;
+        }
+        self.exported += 1;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        match self.edge_log.as_mut() {
+            Some(log) => log.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tank::TankConfig;
+
+    #[test]
+    fn test_gray_and_rgb_map_the_signed_range() {
+        let field = [-2.0, -1.0, 0.0, 1.0, 2.0, 9.0];
+        assert_eq!(
+            to_gray(&field, Scale::Fixed(2.0)),
+            [0, 64, 128, 191, 255, 255]
+        );
+        assert_eq!(to_gray(&[0.0, 0.0], Scale::Auto), [128, 128]);
+        assert_eq!(to_gray(&[-4.0, 4.0], Scale::Auto), [0, 255]);
+        assert_eq!(
+            to_rgb(&[1.0, -0.5, 0.0], Scale::Fixed(1.0)),
+            [255, 0, 0, 0, 0, 128, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn test_exports_read_back() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        let mut tank = RippleTank::new(TankConfig::crew(20, 12)).unwrap();
+        let mut exporter = FrameExporter::create(
+            dir,
+            ExportOptions {
+                ppm: true,
+                ..ExportOptions::default()
+            },
+        )
+        .unwrap();
+        for _ in 0..3 {
+            tank.run(5);
+            exporter.export(&tank).unwrap();
+        }
+        exporter.flush().unwrap();
+        assert_eq!(exporter.exported(), 3);
+
+        let pgm = fs::read(dir.join("field_000015.pgm")).unwrap();
+        assert!(pgm.starts_with(b"P5\n20 12\n255\n"));
+        assert_eq!(pgm.len(), b"P5\n20 12\n255\n".len() + 20 * 12);
+        let ppm = fs::read(dir.join("field_000010.ppm")).unwrap();
+        assert_eq!(ppm.len(), b"P6\n20 12\n255\n".len() + 20 * 12 * 3);
+
+        let back: Array2<f64> = ndarray_npy::read_npy(dir.join("field_000015.npy")).unwrap();
+        assert_eq!(back.dim(), (12, 20));
+        assert_eq!(back.as_slice().unwrap(), tank.field());
+        let edges: Array2<f64> = ndarray_npy::read_npy(dir.join("edges_000015.npy")).unwrap();
+        assert_eq!(
+            edges.as_slice().unwrap(),
+            sobel_magnitude(tank.field(), 20, 12).as_slice()
+        );
+        assert!(dir.join("edges_000005.pgm").exists());
+
+        let csv = fs::read_to_string(dir.join("edge_energy.csv")).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "frame,time,edge_energy,coherence");
+        assert_eq!(lines.len(), 4);
+        assert!(lines[3].starts_with("15,"));
+        let energy: f64 = lines[3].split(',').nth(2).unwrap().parse().unwrap();
+        assert_eq!(energy, edge_energy(edges.as_slice().unwrap()));
+
+        assert!(write_pgm(&dir.join("bad.pgm"), 3, 3, &[0; 8]).is_err());
+    }
+}
@@ -0,0 +1,14 @@
+//! Ripple Tank: a damped 2D wave equation (FDTD) driven by oscillating point sources.
+//!
+//! [`RippleTank`] is headless; call [`RippleTank::step`] and read the field back. The
+//! `ripple_tank_core` binary wraps it with a render loop and the coherence feed, and can
+//! export frames (PGM/PPM, `.npy`, Sobel edges) and probe time series for offline study.
+
+pub mod analysis;
+pub mod export;
+pub mod probe;
+pub mod tank;
+
+pub use export::{ExportOptions, FrameExporter, Scale};
+pub use probe::{Probe, ProbeLog};
+pub use tank::{Boundary, RippleTank, Source, TankConfig, TankError};
@@ -1,96 +1,180 @@
+use coherence_feed::CoherencePublisher;
 use crossbeam::channel::{unbounded, Receiver, Sender};
-use memmap2::MmapMut;
-use std::f64::consts::PI;
-use std::fs::OpenOptions;
-use std::io::Write;
-use std::sync::{Arc, RwLock};
+use ripple_tank_core::{
+    analysis, Boundary, ExportOptions, FrameExporter, Probe, ProbeLog, RippleTank, TankConfig,
+};
+use std::env;
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::PathBuf;
 use std::thread;
-use std::time::{Duration, Instant};
+use std::time::Duration;
 
-// --- CONSTANTS ---
-const TAU: f64 = 6.2831853; // 8 Sig Figs
-const PI: f64 = 3.1415927; // 8 Sig Figs
-const PHI: f64 = 1.6180340; // 8 Sig Figs
-const E: f64 = 2.7182818; // 8 Sig Figs
-const GRID_SIZE: usize = 64;
-const DAMPING: f64 = 0.99;
-const C: f64 = 0.5; // Wave Speed
-const DT: f64 = 0.1;
-const DX: f64 = 1.0;
-const SHM_PATH: &str = "/dev/shm/current_wave_coherence";
-
-// --- Sobel-Feldman Kernels ---
-const SOBEL_X: [[f64; 3]; 3] = [[-1.0, 0.0, 1.0], [-2.0, 0.0, 2.0], [-1.0, 0.0, 1.0]];
-
-const SOBEL_Y: [[f64; 3]; 3] = [[-1.0, -2.0, -1.0], [0.0, 0.0, 0.0], [1.0, 2.0, 1.0]];
+const SOURCE_ID: &str = "ripple_tank_core";
 
 // --- Structures ---
-#[derive(Clone)]
 struct WaveState {
-    u: Vec<Vec<f64>>,
+    u: Vec<f64>,
+    width: usize,
+    height: usize,
     frame: u64,
 }
 
-impl WaveState {
-    fn new() -> Self {
-        Self {
-            u: vec![vec![0.0; GRID_SIZE]; GRID_SIZE],
-            frame: 0,
-        }
-    }
+struct Options {
+    config: TankConfig,
+    /// Directory for frame exports and `probes.csv`.
+    export: Option<PathBuf>,
+    /// Export every this many frames.
+    every: u64,
+    probes: Vec<Probe>,
+    /// Run this many frames headless (no render, no pacing), then exit.
+    frames: Option<u64>,
 }
 
-// --- BIG CORE: Wave Physics Engine ---
-fn big_core_physics(state: Arc<RwLock<WaveState>>, tx_visual: Sender<WaveState>) {
-    let mut u = vec![vec![0.0; GRID_SIZE]; GRID_SIZE];
-    let mut u_prev = u.clone();
-    let mut u_next = u.clone();
-    let mut frame = 0;
+fn usage() {
+    println!(
+        "Usage: ripple_tank_core [--size N] [--boundary fixed|periodic|absorbing]\n\
+         \x20                       [--export DIR] [--every N] [--probe name=x,y]... [--frames N]\n\
+         \n\
+         \x20 --export DIR   write field_NNNNNN.pgm/.npy, edges_NNNNNN.pgm/.npy and edge_energy.csv\n\
+         \x20 --every N      export every Nth frame (default 10)\n\
+         \x20 --probe P      record the field at x,y every frame into probes.csv (repeatable)\n\
+         \x20 --frames N     run N frames as fast as possible without rendering, then exit"
+    );
+}
 
-    let start_time = Instant::now();
+fn parse_count(option: &str, value: &str) -> Result<u64, String> {
+    match value.parse() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(format!(
+            "{} needs a positive count, not {:?}",
+            option, value
+        )),
+    }
+}
 
-    loop {
-        // 1. FDTD Laplacian Solve
-        for y in 1..GRID_SIZE - 1 {
-            for x in 1..GRID_SIZE - 1 {
-                let laplacian =
-                    u[y - 1][x] + u[y + 1][x] + u[y][x - 1] + u[y][x + 1] - 4.0 * u[y][x];
-
-                u_next[y][x] = (2.0 * u[y][x]) - u_prev[y][x]
-                    + (C.powi(2)) * (DT.powi(2) / DX.powi(2)) * laplacian;
-                u_next[y][x] *= DAMPING;
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut size = 64;
+    let mut boundary = Boundary::Fixed;
+    let mut export = None;
+    let mut every = 10;
+    let mut probes = Vec::new();
+    let mut frames = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = args
+            .next()
+            .ok_or_else(|| format!("{} needs a value", arg))?;
+        match arg.as_str() {
+            "--size" => {
+                size = value
+                    .parse()
+                    .map_err(|_| format!("--size cannot take {:?}", value))?
             }
+            "--boundary" => boundary = value.parse()?,
+            "--export" => export = Some(PathBuf::from(value)),
+            "--every" => every = parse_count(arg, value)?,
+            "--probe" => probes.push(value.parse()?),
+            "--frames" => frames = Some(parse_count(arg, value)?),
+            _ => return Err(format!("unknown option {}", arg)),
         }
+    }
+    Ok(Options {
+        config: TankConfig {
+            boundary,
+            ..TankConfig::crew(size, size)
+        },
+        export,
+        every,
+        probes,
+        frames,
+    })
+}
 
-        // 2. Source Injection (Crew Inputs)
-        let t = frame as f64 * DT;
+// --- Recorder: frame exports and probe time series ---
+struct Recorder {
+    exporter: Option<FrameExporter>,
+    every: u64,
+    probes: Option<ProbeLog<BufWriter<File>>>,
+}
 
-        // Source A (Center - Robin): Modulated by TAU
-        let src_a_val = 2.0 * (t * 0.2 * TAU).sin();
-        u_next[32][32] = src_a_val;
+impl Recorder {
+    fn open(options: &Options, tank: &RippleTank) -> io::Result<Self> {
+        let exporter = match &options.export {
+            Some(dir) => Some(FrameExporter::create(dir, ExportOptions::default())?),
+            None => None,
+        };
+        let probes = if options.probes.is_empty() {
+            None
+        } else {
+            let dir = options.export.clone().unwrap_or_default();
+            let log = ProbeLog::create(dir.join("probes.csv"), options.probes.clone(), tank)?;
+            Some(log)
+        };
+        Ok(Self {
+            exporter,
+            every: options.every,
+            probes,
+        })
+    }
+
+    fn record(&mut self, tank: &RippleTank) -> io::Result<()> {
+        if let Some(log) = self.probes.as_mut() {
+            log.record(tank)?;
+        }
+        if let Some(exporter) = self.exporter.as_mut() {
+            if tank.frame().is_multiple_of(self.every) {
+                exporter.export(tank)?;
+            }
+        }
+        Ok(())
+    }
 
-        // Source B (Interference): Offset
-        let src_b_val = 1.0 * (t * 0.3 * TAU).sin();
-        u_next[16][16] = src_b_val;
+    fn flush(&mut self) -> io::Result<()> {
+        if let Some(log) = self.probes.as_mut() {
+            log.flush()?;
+        }
+        if let Some(exporter) = self.exporter.as_mut() {
+            exporter.flush()?;
+        }
+        Ok(())
+    }
+}
 
-        // 3. Cycle Buffers
-        u_prev = u.clone();
-        u = u_next.clone();
-        frame += 1;
+// --- Headless: step, record, exit ---
+fn run_headless(mut tank: RippleTank, mut recorder: Recorder, frames: u64) -> io::Result<()> {
+    for _ in 0..frames {
+        tank.step();
+        recorder.record(&tank)?;
+    }
+    recorder.flush()?;
+    let exported = recorder.exporter.as_ref().map_or(0, |e| e.exported());
+    println!(
+        "Ran {} frames; exported {}; coherence {:.4}",
+        tank.frame(),
+        exported,
+        analysis::coherence(&tank)
+    );
+    Ok(())
+}
 
-        // 4. Update Shared State
-        {
-            let mut write_guard = state.write().unwrap();
-            write_guard.u = u.clone();
-            write_guard.frame = frame;
+// --- BIG CORE: Wave Physics Engine ---
+fn big_core_physics(mut tank: RippleTank, mut recorder: Recorder, tx_visual: Sender<WaveState>) {
+    loop {
+        tank.step();
+        if let Err(e) = recorder.record(&tank).and_then(|_| recorder.flush()) {
+            println!("❌ Recording stopped: {}", e);
+            return;
         }
 
-        // 5. Send to Little Core (Visual/Analysis) every few frames
-        if frame % 2 == 0 {
+        // Send to Little Core (Visual/Analysis) every few frames
+        if tank.frame().is_multiple_of(2) {
             tx_visual
                 .send(WaveState {
-                    u: u.clone(),
-                    frame,
+                    u: tank.field().to_vec(),
+                    width: tank.width(),
+                    height: tank.height(),
+                    frame: tank.frame(),
                 })
                 .ok();
         }
@@ -101,88 +185,81 @@ fn big_core_physics(state: Arc<RwLock<WaveState>>, tx_visual: Sender<WaveState>)
 
 // --- LITTLE CORE: Sobel-Feldman & Coherence ---
 fn little_core_analysis(rx_visual: Receiver<WaveState>) {
-    loop {
-        if let Ok(state) = rx_visual.recv() {
-            let u = state.u;
-
-            // 1. Apply Sobel-Feldman Operator
-            // This detects edges/gradients in the wave field, representing "energy fronts".
-            let mut coherence_accum = 0.0;
-            let mut energy_total = 0.0;
-
-            for y in 1..GRID_SIZE - 1 {
-                for x in 1..GRID_SIZE - 1 {
-                    let mut gx = 0.0;
-                    let mut gy = 0.0;
-
-                    for ky in 0..3 {
-                        for kx in 0..3 {
-                            let val = u[y + ky - 1][x + kx - 1];
-                            gx += val * SOBEL_X[ky][kx];
-                            gy += val * SOBEL_Y[ky][kx];
-                        }
-                    }
-
-                    let magnitude = (gx.powi(2) + gy.powi(2)).sqrt();
-                    energy_total += magnitude;
-
-                    // Coherence Metric: How much energy is concentrated vs geometric?
-                    // Simple proxy: Center weighted coherence
-                    let dx = (x as f64) - (GRID_SIZE as f64 / 2.0);
-                    let dy = (y as f64) - (GRID_SIZE as f64 / 2.0);
-                    let dist = (dx * dx + dy * dy).sqrt();
-
-                    if dist < 8.0 {
-                        coherence_accum += magnitude;
-                    }
-                }
-            }
+    let feed = CoherencePublisher::new(SOURCE_ID);
+    while let Ok(state) = rx_visual.recv() {
+        let (w, h) = (state.width, state.height);
 
-            let coherence_metric = if energy_total > 0.0 {
-                (coherence_accum / energy_total) * 10.0
-            } else {
-                0.0
-            };
+        // 1. Apply Sobel-Feldman Operator
+        // This detects edges/gradients in the wave field, representing "energy fronts".
+        let edges = analysis::sobel_magnitude(&state.u, w, h);
+        let coherence_metric = analysis::centre_coherence(&edges, w, h, analysis::COHERENCE_RADIUS);
 
-            // 2. Write to Shared Memory
-            if let Ok(mut file) = OpenOptions::new().write(true).create(true).open(SHM_PATH) {
-                let _ = write!(file, "{:.4}", coherence_metric);
-            }
+        // 2. Publish to the Coherence Feed (Shared Memory)
+        let _ = feed.publish(coherence_metric, state.frame);
 
-            // 3. ASCII Render (Low Priority)
-            print!("\x1B[2J\x1B[1;1H"); // Clear Screen
-            println!(
-                "--- ZINC-RUST RIPPLE TANK Frame: {} (Tau Mode) ---",
-                state.frame
-            );
-            println!("Coherence: {:.4}", coherence_metric);
-
-            let chars = ['.', ':', '-', '=', '+', '*', '#', '%', '@'];
-            for y in (0..GRID_SIZE).step_by(2) {
-                for x in (0..GRID_SIZE).step_by(2) {
-                    let val = u[y][x];
-                    let idx = ((val + 1.0) * 4.0) as usize;
-                    let idx = idx.min(chars.len() - 1);
-                    print!("{}", chars[idx]);
-                }
-                println!("");
-            }
+        // 3. ASCII Render (Low Priority)
+        print!("\x1B[2J\x1B[1;1H"); // Clear Screen
+        println!(
+            "--- ZINC-RUST RIPPLE TANK Frame: {} (Tau Mode) ---",
+            state.frame
+        );
+        println!("Coherence: {:.4}", coherence_metric);
+
+        let chars = ['.', ':', '-', '=', '+', '*', '#', '%', '@'];
+        for y in (0..h).step_by(2) {
+            let line: String = (0..w)
+                .step_by(2)
+                .map(|x| {
+                    let idx = ((state.u[y * w + x] + 1.0) * 4.0) as usize;
+                    chars[idx.min(chars.len() - 1)]
+                })
+                .collect();
+            println!("{}", line);
         }
     }
 }
 
 fn main() {
-    println!("Initializing Native Physics Engine...");
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.iter().any(|a| a == "--help" || a == "-h") {
+        usage();
+        return;
+    }
+    let (options, tank) = match parse_args(&args).and_then(|options| {
+        let tank = RippleTank::new(options.config.clone()).map_err(|e| e.to_string())?;
+        Ok((options, tank))
+    }) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            println!("❌ {}", e);
+            usage();
+            std::process::exit(2);
+        }
+    };
+    let recorder = match Recorder::open(&options, &tank) {
+        Ok(recorder) => recorder,
+        Err(e) => {
+            println!("❌ Cannot record: {}", e);
+            std::process::exit(2);
+        }
+    };
 
-    let shared_state = Arc::new(RwLock::new(WaveState::new()));
+    if let Some(frames) = options.frames {
+        if let Err(e) = run_headless(tank, recorder, frames) {
+            println!("❌ {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    println!("Initializing Native Physics Engine...");
     let (tx, rx) = unbounded();
 
     // Spawn Little Core (Analysis)
-    let state_clone = shared_state.clone();
     thread::spawn(move || {
         little_core_analysis(rx);
     });
 
     // Run Big Core (Physics) on Main Thread
-    big_core_physics(shared_state, tx);
+    big_core_physics(tank, recorder, tx);
 }
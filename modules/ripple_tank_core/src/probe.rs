@@ -0,0 +1,165 @@
+//! Named point probes: the displacement at fixed cells, one CSV row per frame.
+
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::tank::RippleTank;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Probe {
+    pub name: String,
+    pub x: usize,
+    pub y: usize,
+}
+
+impl Probe {
+    pub fn new(name: impl Into<String>, x: usize, y: usize) -> Self {
+        Self {
+            name: name.into(),
+            x,
+            y,
+        }
+    }
+}
+
+/// `name=x,y`, as given to `--probe`.
+impl FromStr for Probe {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bad = || format!("probe {:?} is not name=x,y", s);
+        let (name, at) = s.split_once('=').ok_or_else(bad)?;
+        let (x, y) = at.split_once(',').ok_or_else(bad)?;
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(bad());
+        }
+        Ok(Probe::new(
+            name,
+            x.trim().parse().map_err(|_| bad())?,
+            y.trim().parse().map_err(|_| bad())?,
+        ))
+    }
+}
+
+/// Writes `frame,time,<probe names...>` and then a row per [`ProbeLog::record`].
+pub struct ProbeLog<W: Write> {
+    out: W,
+    probes: Vec<Probe>,
+}
+
+impl ProbeLog<BufWriter<File>> {
+    pub fn create(
+        path: impl AsRef<Path>,
+        probes: Vec<Probe>,
+        tank: &RippleTank,
+    ) -> io::Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?), probes, tank)
+    }
+}
+
+impl<W: Write> ProbeLog<W> {
+    /// Refuses probes outside `tank`, and names that repeat or would break the CSV.
+    pub fn new(mut out: W, probes: Vec<Probe>, tank: &RippleTank) -> io::Result<Self> {
+        let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidInput, msg);
+        let mut names = HashSet::new();
+        for probe in &probes {
+            if probe.x >= tank.width() || probe.y >= tank.height() {
+                return Err(invalid(format!(
+                    "probe {} at ({}, {}) is outside the {}x{} tank",
+                    probe.name,
+                    probe.x,
+                    probe.y,
+                    tank.width(),
+                    tank.height()
+                )));
+            }
+            if probe.name.contains([',', '"', '\n', '\r']) {
+                return Err(invalid(format!(
+                    "probe name {:?} cannot go in a CSV header",
+                    probe.name
+                )));
+            }
+            if !names.insert(probe.name.as_str()) {
+                return Err(invalid(format!("probe {} is named twice", probe.name)));
+            }
+        }
+
+        write!(out, "frame,time")?;
+        for probe in &probes {
+            write!(out, ",{}", probe.name)?;
+        }
+        writeln!(out)?;
+        Ok(Self { out, probes })
+    }
+
+    pub fn probes(&self) -> &[Probe] {
+        &self.probes
+    }
+
+    /// Samples every probe at the tank's current frame.
+    pub fn record(&mut self, tank: &RippleTank) -> io::Result<()> {
+        write!(self.out, "{},{}", tank.frame(), tank.time())?;
+        for probe in &self.probes {
+            write!(self.out, ",{}", tank.get(probe.x, probe.y))?;
+        }
+        writeln!(self.out)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tank::TankConfig;
+
+    #[test]
+    fn test_probes_parse_from_the_command_line() {
+        assert_eq!("centre=32,32".parse(), Ok(Probe::new("centre", 32, 32)));
+        assert_eq!(" edge = 0, 5 ".parse(), Ok(Probe::new("edge", 0, 5)));
+        for bad in ["centre", "=1,2", "a=1", "a=1,b", "a=-1,2"] {
+            assert!(bad.parse::<Probe>().is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_log_records_the_field_at_each_probe() {
+        let mut tank = RippleTank::new(TankConfig::crew(16, 16)).unwrap();
+        let probes = vec![Probe::new("centre", 8, 8), Probe::new("corner", 1, 1)];
+        let mut log = ProbeLog::new(Vec::new(), probes.clone(), &tank).unwrap();
+        let mut expected = Vec::new();
+        for _ in 0..4 {
+            tank.step();
+            log.record(&tank).unwrap();
+            expected.push((tank.frame(), tank.get(8, 8), tank.get(1, 1)));
+        }
+
+        let csv = String::from_utf8(log.into_inner()).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("frame,time,centre,corner"));
+        for ((frame, centre, corner), line) in expected.into_iter().zip(lines) {
+            let cols: Vec<&str> = line.split(',').collect();
+            assert_eq!(cols[0].parse::<u64>().unwrap(), frame);
+            assert_eq!(cols[2].parse::<f64>().unwrap(), centre);
+            assert_eq!(cols[3].parse::<f64>().unwrap(), corner);
+        }
+        assert_eq!(csv.lines().count(), 5);
+
+        let outside = vec![Probe::new("far", 16, 0)];
+        assert!(ProbeLog::new(Vec::new(), outside, &tank).is_err());
+        let twice = vec![Probe::new("a", 1, 1), Probe::new("a", 2, 2)];
+        assert!(ProbeLog::new(Vec::new(), twice, &tank).is_err());
+        let comma = vec![Probe::new("a,b", 1, 1)];
+        assert!(ProbeLog::new(Vec::new(), comma, &tank).is_err());
+    }
+}
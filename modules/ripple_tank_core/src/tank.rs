@@ -0,0 +1,510 @@
+use std::f64::consts::{FRAC_1_SQRT_2, TAU};
+use std::fmt;
+use std::str::FromStr;
+
+/// What happens to waves at the edge of the tank.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Boundary {
+    /// Edges are held at zero, so waves reflect (inverted).
+    #[default]
+    Fixed,
+    /// Opposite edges are joined; a wave leaving one side re-enters the other.
+    Periodic,
+    /// First-order Mur boundary: outgoing waves leave with little reflection.
+    Absorbing,
+}
+
+impl FromStr for Boundary {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "fixed" => Ok(Boundary::Fixed),
+            "periodic" => Ok(Boundary::Periodic),
+            "absorbing" => Ok(Boundary::Absorbing),
+            _ => Err(format!(
+                "unknown boundary {:?} (fixed, periodic, absorbing)",
+                s
+            )),
+        }
+    }
+}
+
+/// An oscillating point source. It drives its cell directly (a "hard" source).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Source {
+    pub x: usize,
+    pub y: usize,
+    pub amplitude: f64,
+    /// Cycles per unit of simulated time.
+    pub frequency: f64,
+    /// Radians.
+    pub phase: f64,
+}
+
+impl Source {
+    pub fn new(x: usize, y: usize, amplitude: f64, frequency: f64) -> Self {
+        Self {
+            x,
+            y,
+            amplitude,
+            frequency,
+            phase: 0.0,
+        }
+    }
+
+    pub fn with_phase(mut self, phase: f64) -> Self {
+        self.phase = phase;
+        self
+    }
+
+    /// Displacement at simulated time `t`.
+    pub fn value(&self, t: f64) -> f64 {
+        self.amplitude * (TAU * self.frequency * t + self.phase).sin()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TankConfig {
+    pub width: usize,
+    pub height: usize,
+    pub wave_speed: f64,
+    /// Multiplier applied to the field every step; 1.0 is lossless.
+    pub damping: f64,
+    pub dt: f64,
+    pub dx: f64,
+    pub boundary: Boundary,
+    pub sources: Vec<Source>,
+}
+
+impl Default for TankConfig {
+    fn default() -> Self {
+        Self::crew(64, 64)
+    }
+}
+
+impl TankConfig {
+    /// The 2D FDTD scheme is stable while `c·dt/dx` stays at or below `1/√2`.
+    pub const COURANT_LIMIT: f64 = FRAC_1_SQRT_2;
+
+    /// The classic tank: Robin at the centre (2.0 @ 0.2) and an interfering source
+    /// at the upper-left quarter point (1.0 @ 0.3).
+    pub fn crew(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            wave_speed: 0.5,
+            damping: 0.99,
+            dt: 0.1,
+            dx: 1.0,
+            boundary: Boundary::Fixed,
+            sources: vec![
+                Source::new(width / 2, height / 2, 2.0, 0.2),
+                Source::new(width / 4, height / 4, 1.0, 0.3),
+            ],
+        }
+    }
+
+    /// The Courant number `c·dt/dx`.
+    pub fn courant(&self) -> f64 {
+        self.wave_speed * self.dt / self.dx
+    }
+
+    /// The largest `dt` the CFL condition allows for this speed and spacing.
+    pub fn max_stable_dt(&self) -> f64 {
+        Self::COURANT_LIMIT * self.dx / self.wave_speed
+    }
+
+    pub fn validate(&self) -> Result<(), TankError> {
+        if self.width < 3 || self.height < 3 {
+            return Err(TankError::GridTooSmall {
+                width: self.width,
+                height: self.height,
+            });
+        }
+        for (name, value, ok) in [
+            ("wave_speed", self.wave_speed, self.wave_speed > 0.0),
+            ("dt", self.dt, self.dt > 0.0),
+            ("dx", self.dx, self.dx > 0.0),
+            ("damping", self.damping, (0.0..=1.0).contains(&self.damping)),
+        ] {
+            if !ok || !value.is_finite() {
+                return Err(TankError::InvalidParameter { name, value });
+            }
+        }
+        let courant = self.courant();
+        if courant > Self::COURANT_LIMIT {
+            return Err(TankError::Unstable {
+                courant,
+                max_dt: self.max_stable_dt(),
+            });
+        }
+        for (index, source) in self.sources.iter().enumerate() {
+            if source.x >= self.width || source.y >= self.height {
+                return Err(TankError::SourceOutOfBounds {
+                    index,
+                    x: source.x,
+                    y: source.y,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TankError {
+    GridTooSmall {
+        width: usize,
+        height: usize,
+    },
+    InvalidParameter {
+        name: &'static str,
+        value: f64,
+    },
+    /// The timestep breaks the CFL condition.
+    Unstable {
+        courant: f64,
+        max_dt: f64,
+    },
+    SourceOutOfBounds {
+        index: usize,
+        x: usize,
+        y: usize,
+    },
+}
+
+impl fmt::Display for TankError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TankError::GridTooSmall { width, height } => {
+                write!(f, "grid {}x{} is smaller than 3x3", width, height)
+            }
+            TankError::InvalidParameter { name, value } => {
+                write!(f, "{} = {} is out of range", name, value)
+            }
+            TankError::Unstable { courant, max_dt } => write!(
+                f,
+                "Courant number {:.4} exceeds {:.4}; use dt <= {:.4}",
+                courant,
+                TankConfig::COURANT_LIMIT,
+                max_dt
+            ),
+            TankError::SourceOutOfBounds { index, x, y } => {
+                write!(f, "source {} at ({}, {}) is outside the grid", index, x, y)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TankError {}
+
+/// The simulation. The field is row-major: cell `(x, y)` is at `y * width + x`.
+pub struct RippleTank {
+    config: TankConfig,
+    u: Vec<f64>,
+    u_prev: Vec<f64>,
+    u_next: Vec<f64>,
+    frame: u64,
+}
+
+impl RippleTank {
+    pub fn new(config: TankConfig) -> Result<Self, TankError> {
+        config.validate()?;
+        let cells = config.width * config.height;
+        Ok(Self {
+            config,
+            u: vec![0.0; cells],
+            u_prev: vec![0.0; cells],
+            u_next: vec![0.0; cells],
+            frame: 0,
+        })
+    }
+
+    pub fn config(&self) -> &TankConfig {
+        &self.config
+    }
+
+    pub fn width(&self) -> usize {
+        self.config.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.config.height
+    }
+
+    /// Steps taken so far.
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// Simulated time.
+    pub fn time(&self) -> f64 {
+        self.frame as f64 * self.config.dt
+    }
+
+    pub fn field(&self) -> &[f64] {
+        &self.u
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> f64 {
+        self.u[y * self.config.width + x]
+    }
+
+    /// Displaces one cell at rest (zero velocity), e.g. to drop a pebble in.
+    pub fn pluck(&mut self, x: usize, y: usize, value: f64) {
+        let i = y * self.config.width + x;
+        self.u[i] = value;
+        self.u_prev[i] = value;
+    }
+
+    /// Sum of squared displacement over the tank.
+    pub fn energy(&self) -> f64 {
+        self.u.iter().map(|v| v * v).sum()
+    }
+
+    /// Drains the tank back to frame 0.
+    pub fn reset(&mut self) {
+        self.u.fill(0.0);
+        self.u_prev.fill(0.0);
+        self.u_next.fill(0.0);
+        self.frame = 0;
+    }
+
+    /// Runs `frames` steps.
+    pub fn run(&mut self, frames: u64) {
+        for _ in 0..frames {
+            self.step();
+        }
+    }
+
+    /// Advances the field by one `dt`.
+    pub fn step(&mut self) {
+        let (w, h) = (self.config.width, self.config.height);
+        let r2 = self.config.courant().powi(2);
+        let damping = self.config.damping;
+        let t = self.time();
+        let (u, u_prev, u_next) = (&self.u, &self.u_prev, &mut self.u_next);
+
+        // 1. FDTD Laplacian Solve
+        let mut solve = |x: usize, y: usize, laplacian: f64| {
+            let i = y * w + x;
+            u_next[i] = (2.0 * u[i] - u_prev[i] + r2 * laplacian) * damping;
+        };
+        match self.config.boundary {
+            Boundary::Periodic => {
+                for y in 0..h {
+                    let (up, down) = ((y + h - 1) % h, (y + 1) % h);
+                    for x in 0..w {
+                        let (left, right) = ((x + w - 1) % w, (x + 1) % w);
+                        let laplacian = u[up * w + x] + u[down * w + x] + u[y * w + left]
+                            - 4.0 * u[y * w + x]
+                            + u[y * w + right];
+                        solve(x, y, laplacian);
+                    }
+                }
+            }
+            Boundary::Fixed | Boundary::Absorbing => {
+                for y in 1..h - 1 {
+                    for x in 1..w - 1 {
+                        let i = y * w + x;
+                        let laplacian = u[i - w] + u[i + w] + u[i - 1] + u[i + 1] - 4.0 * u[i];
+                        solve(x, y, laplacian);
+                    }
+                }
+            }
+        }
+
+        // 2. Edges
+        match self.config.boundary {
+            Boundary::Periodic => {}
+            Boundary::Fixed => {
+                for x in 0..w {
+                    u_next[x] = 0.0;
+                    u_next[(h - 1) * w + x] = 0.0;
+                }
+                for y in 0..h {
+                    u_next[y * w] = 0.0;
+                    u_next[y * w + w - 1] = 0.0;
+                }
+            }
+            Boundary::Absorbing => {
+                let k = self.config.courant();
+                let mur = (k - 1.0) / (k + 1.0);
+                // Each edge cell follows its inward neighbour, one step behind.
+                let mut absorb = |edge: usize, inner: usize| {
+                    u_next[edge] = u[inner] + mur * (u_next[inner] - u[edge]);
+                };
+                for x in 1..w - 1 {
+                    absorb(x, w + x);
+                    absorb((h - 1) * w + x, (h - 2) * w + x);
+                }
+                for y in 1..h - 1 {
+                    absorb(y * w, y * w + 1);
+                    absorb(y * w + w - 1, y * w + w - 2);
+                }
+                for (corner, a, b) in [
+                    (0, 1, w),
+                    (w - 1, w - 2, 2 * w - 1),
+                    ((h - 1) * w, (h - 2) * w, (h - 1) * w + 1),
+                    (h * w - 1, (h - 1) * w - 1, h * w - 2),
+                ] {
+                    u_next[corner] = 0.5 * (u_next[a] + u_next[b]);
+                }
+            }
+        }
+
+        // 3. Source Injection (Crew Inputs)
+        for source in &self.config.sources {
+            u_next[source.y * w + source.x] = source.value(t);
+        }
+
+        // 4. Cycle Buffers
+        std::mem::swap(&mut self.u_prev, &mut self.u);
+        std::mem::swap(&mut self.u, &mut self.u_next);
+        self.frame += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A lossless, source-free tank (Courant 0.5) with a pebble dropped at the centre.
+    fn pebble(size: usize, boundary: Boundary) -> RippleTank {
+        let mut tank = RippleTank::new(TankConfig {
+            damping: 1.0,
+            dt: 1.0,
+            boundary,
+            sources: Vec::new(),
+            ..TankConfig::crew(size, size)
+        })
+        .unwrap();
+        tank.pluck(size / 2, size / 2, 1.0);
+        tank
+    }
+
+    #[test]
+    fn test_config_is_checked_before_the_first_step() {
+        assert!(TankConfig::default().validate().is_ok());
+        assert_eq!(
+            TankConfig::default().sources[0],
+            Source::new(32, 32, 2.0, 0.2)
+        );
+
+        let fast = TankConfig {
+            wave_speed: 8.0,
+            ..TankConfig::default()
+        };
+        match RippleTank::new(fast.clone()).err() {
+            Some(TankError::Unstable { courant, max_dt }) => {
+                assert!((courant - 0.8).abs() < 1e-12);
+                assert!((max_dt - fast.max_stable_dt()).abs() < 1e-12);
+            }
+            other => panic!("expected an unstable config, got {:?}", other),
+        }
+        let fixed = TankConfig {
+            dt: fast.max_stable_dt(),
+            ..fast
+        };
+        assert!(fixed.validate().is_ok());
+
+        let mut stray = TankConfig::crew(16, 16);
+        stray.sources.push(Source::new(3, 16, 1.0, 0.1));
+        assert_eq!(
+            stray.validate(),
+            Err(TankError::SourceOutOfBounds {
+                index: 2,
+                x: 3,
+                y: 16
+            })
+        );
+        assert!(matches!(
+            RippleTank::new(TankConfig::crew(2, 64)),
+            Err(TankError::GridTooSmall { .. })
+        ));
+        assert!(matches!(
+            TankConfig {
+                damping: 1.5,
+                ..TankConfig::default()
+            }
+            .validate(),
+            Err(TankError::InvalidParameter {
+                name: "damping",
+                ..
+            })
+        ));
+        assert_eq!("Absorbing".parse(), Ok(Boundary::Absorbing));
+        assert!("soggy".parse::<Boundary>().is_err());
+    }
+
+    #[test]
+    fn test_stepping_is_deterministic_and_sources_drive_their_cells() {
+        let mut a = RippleTank::new(TankConfig::default()).unwrap();
+        let mut b = RippleTank::new(TankConfig::default()).unwrap();
+        a.run(120);
+        b.run(120);
+        assert_eq!(a.field(), b.field());
+        assert_eq!(a.frame(), 120);
+        assert!((a.time() - 12.0).abs() < 1e-9);
+
+        // The last step injected at t = 119 * dt.
+        let t = 119.0 * 0.1;
+        let robin = a.config().sources[0];
+        assert_eq!(a.get(32, 32), robin.value(t));
+        assert!(a.energy() > 0.0);
+        assert!(a.field().iter().all(|v| v.is_finite()));
+
+        a.reset();
+        assert_eq!(a.frame(), 0);
+        assert_eq!(a.energy(), 0.0);
+    }
+
+    #[test]
+    fn test_a_centred_pebble_spreads_symmetrically() {
+        let mut tank = pebble(33, Boundary::Fixed);
+        tank.run(40);
+        for y in 0..33 {
+            for x in 0..33 {
+                let v = tank.get(x, y);
+                assert!((v - tank.get(32 - x, y)).abs() < 1e-12);
+                assert!((v - tank.get(y, x)).abs() < 1e-12);
+            }
+        }
+        assert_eq!(tank.get(0, 16), 0.0);
+    }
+
+    #[test]
+    fn test_boundaries_reflect_wrap_or_absorb() {
+        // Long enough for the ring to reach the walls and come back.
+        let frames = 240;
+        let mut fixed = pebble(32, Boundary::Fixed);
+        let mut periodic = pebble(32, Boundary::Periodic);
+        let mut absorbing = pebble(32, Boundary::Absorbing);
+        let start = fixed.energy();
+        fixed.run(frames);
+        periodic.run(frames);
+        absorbing.run(frames);
+
+        assert!(fixed.energy() > 0.01 * start);
+        assert!(periodic.energy() > 0.01 * start);
+        assert!(
+            absorbing.energy() < 0.1 * fixed.energy(),
+            "absorbing {} vs fixed {}",
+            absorbing.energy(),
+            fixed.energy()
+        );
+
+        // Only the periodic tank lets a wave reach the far edge.
+        let mut wrapped = pebble(32, Boundary::Periodic);
+        let mut walled = pebble(32, Boundary::Fixed);
+        wrapped.pluck(16, 16, 0.0);
+        walled.pluck(16, 16, 0.0);
+        wrapped.pluck(0, 16, 1.0);
+        walled.pluck(1, 16, 1.0);
+        wrapped.run(5);
+        walled.run(5);
+        assert!(wrapped.get(30, 16).abs() > 1e-6);
+        assert_eq!(walled.get(30, 16), 0.0);
+    }
+}
@@ -10,22 +10,16 @@ use std::time::{SystemTime, UNIX_EPOCH};
 const TAU: f64 = PI * 2.0;
 const GRAVITY_G: f64 = 9.80665;
 
-#[derive(Debug, Clone)]
-pub enum Stance {
-    Iron,
-    Water,
-    Aether,
-    Void,
-}
-
-impl Stance {
-    fn entropy(&self) -> f64 {
-        match self {
-            Stance::Iron => 0.01,
-            Stance::Water => 0.05,
-            Stance::Aether => 0.2,
-            Stance::Void => 0.5,
-        }
+pub use stance_model::Stance;
+use stance_model::VariantSet;
+
+/// Heap entropy of a stance, after projecting it onto Robin's materials.
+fn entropy(stance: Stance) -> f64 {
+    match VariantSet::Robin.project(stance) {
+        Stance::Iron => 0.01,
+        Stance::Water => 0.05,
+        Stance::Aether => 0.2,
+        _ => 0.5, // Void
     }
 }
 
@@ -133,7 +127,7 @@ impl D7SynthesisHeap {
 
         // Kater's Swing
         let t1 = self.pendulum.forward_swing(sig.mass);
-        let t2 = self.pendulum.reverse_swing(sig.stance.entropy());
+        let t2 = self.pendulum.reverse_swing(entropy(sig.stance));
         let reversible = self.pendulum.is_reversible(0.16); // Tolerance
 
         (t1, t2, reversible)
@@ -0,0 +1,277 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// A bank of GPIO lines addressed by pin number.
+///
+/// Implemented by the RP1 RIO registers and by stand-ins that need no `/dev/mem`,
+/// so pin logic can be exercised (and watched) off the Pi.
+pub trait GpioBank {
+    /// Enable the output driver for a pin.
+    fn enable_output(&mut self, pin: u32);
+
+    /// Drive a pin high.
+    fn set_pin(&mut self, pin: u32);
+
+    /// Drive a pin low.
+    fn clr_pin(&mut self, pin: u32);
+
+    /// Read the current level of a pin.
+    fn read_pin(&self, pin: u32) -> bool;
+
+    /// Toggle a pin.
+    fn xor_pin(&mut self, pin: u32) {
+        let high = self.read_pin(pin);
+        self.write_pin(pin, !high);
+    }
+
+    /// Drive a pin to `high`.
+    fn write_pin(&mut self, pin: u32, high: bool) {
+        if high {
+            self.set_pin(pin);
+        } else {
+            self.clr_pin(pin);
+        }
+    }
+
+    /// Marks the end of a batch of writes (one frame of output). Hardware ignores it.
+    fn flush(&mut self) {}
+}
+
+impl GpioBank for crate::Rp1Rio {
+    fn enable_output(&mut self, pin: u32) {
+        crate::Rp1Rio::enable_output(self, pin)
+    }
+
+    fn set_pin(&mut self, pin: u32) {
+        crate::Rp1Rio::set_pin(self, pin)
+    }
+
+    fn clr_pin(&mut self, pin: u32) {
+        crate::Rp1Rio::clr_pin(self, pin)
+    }
+
+    fn read_pin(&self, pin: u32) -> bool {
+        crate::Rp1Rio::read_pin(self, pin)
+    }
+
+    fn xor_pin(&mut self, pin: u32) {
+        crate::Rp1Rio::xor_pin(self, pin)
+    }
+}
+
+/// The register bit for `pin`. The RIO bank is 32 lines wide, so pins past it have no bit
+/// and writes to them do nothing.
+pub fn pin_mask(pin: u32) -> u32 {
+    1u32.checked_shl(pin).unwrap_or(0)
+}
+
+/// One write captured by a [`RecordingBank`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PinEvent {
+    Output { at: Duration, pin: u32 },
+    Level { at: Duration, pin: u32, high: bool },
+    Flush { at: Duration, levels: u32 },
+}
+
+impl PinEvent {
+    /// Time since the bank was created.
+    pub fn at(&self) -> Duration {
+        match *self {
+            PinEvent::Output { at, .. }
+            | PinEvent::Level { at, .. }
+            | PinEvent::Flush { at, .. } => at,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Recording {
+    outputs: u32,
+    levels: u32,
+    events: Vec<PinEvent>,
+}
+
+/// A mock bank that records every write with a timestamp.
+///
+/// Clones share one recording, so a test can keep a handle while the bank itself
+/// is handed to the code under test.
+#[derive(Debug, Clone)]
+pub struct RecordingBank {
+    epoch: Instant,
+    recording: Arc<Mutex<Recording>>,
+}
+
+impl Default for RecordingBank {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RecordingBank {
+    pub fn new() -> Self {
+        Self {
+            epoch: Instant::now(),
+            recording: Arc::new(Mutex::new(Recording::default())),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Recording> {
+        self.recording.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Current levels of all pins, one bit per pin.
+    pub fn levels(&self) -> u32 {
+        self.lock().levels
+    }
+
+    /// Pins whose output driver has been enabled, one bit per pin.
+    pub fn outputs(&self) -> u32 {
+        self.lock().outputs
+    }
+
+    /// Levels of `pins`, in order.
+    pub fn pattern(&self, pins: impl IntoIterator<Item = u32>) -> Vec<bool> {
+        let levels = self.levels();
+        pins.into_iter()
+            .map(|pin| levels & pin_mask(pin) != 0)
+            .collect()
+    }
+
+    pub fn events(&self) -> Vec<PinEvent> {
+        self.lock().events.clone()
+    }
+
+    /// The level snapshot taken at each flush, with its timestamp.
+    pub fn frames(&self) -> Vec<(Duration, u32)> {
+        self.lock()
+            .events
+            .iter()
+            .filter_map(|e| match *e {
+                PinEvent::Flush { at, levels } => Some((at, levels)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn clear_events(&self) {
+        self.lock().events.clear();
+    }
+
+    fn record(&self, edit: impl FnOnce(&mut Recording, Duration) -> PinEvent) {
+        let at = self.epoch.elapsed();
+        let mut recording = self.lock();
+        let event = edit(&mut recording, at);
+        recording.events.push(event);
+    }
+}
+
+impl GpioBank for RecordingBank {
+    fn enable_output(&mut self, pin: u32) {
+        self.record(|r, at| {
+            r.outputs |= pin_mask(pin);
+            PinEvent::Output { at, pin }
+        });
+    }
+
+    fn set_pin(&mut self, pin: u32) {
+        self.record(|r, at| {
+            r.levels |= pin_mask(pin);
+            PinEvent::Level {
+                at,
+                pin,
+                high: true,
+            }
+        });
+    }
+
+    fn clr_pin(&mut self, pin: u32) {
+        self.record(|r, at| {
+            r.levels &= !pin_mask(pin);
+            PinEvent::Level {
+                at,
+                pin,
+                high: false,
+            }
+        });
+    }
+
+    fn read_pin(&self, pin: u32) -> bool {
+        self.levels() & pin_mask(pin) != 0
+    }
+
+    fn flush(&mut self) {
+        self.record(|r, at| PinEvent::Flush {
+            at,
+            levels: r.levels,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recording_bank_tracks_levels_and_events() {
+        let probe = RecordingBank::new();
+        let mut bank: Box<dyn GpioBank> = Box::new(probe.clone());
+
+        bank.enable_output(17);
+        bank.set_pin(17);
+        bank.xor_pin(18);
+        bank.xor_pin(17);
+        bank.flush();
+
+        assert_eq!(probe.outputs(), 1 << 17);
+        assert_eq!(probe.levels(), 1 << 18);
+        assert_eq!(probe.pattern(17..=18), [false, true]);
+
+        let events = probe.events();
+        assert_eq!(events.len(), 5);
+        assert!(matches!(
+            events[1],
+            PinEvent::Level {
+                pin: 17,
+                high: true,
+                ..
+            }
+        ));
+        assert!(matches!(
+            events[3],
+            PinEvent::Level {
+                pin: 17,
+                high: false,
+                ..
+            }
+        ));
+        assert_eq!(probe.frames().len(), 1);
+        assert_eq!(probe.frames()[0].1, 1 << 18);
+    }
+
+    #[test]
+    fn test_pins_past_the_bank_are_ignored() {
+        assert_eq!(pin_mask(31), 1 << 31);
+        assert_eq!(pin_mask(32), 0);
+
+        let probe = RecordingBank::new();
+        let mut bank: Box<dyn GpioBank> = Box::new(probe.clone());
+        bank.enable_output(32);
+        bank.set_pin(32);
+        bank.xor_pin(255);
+        bank.clr_pin(u32::MAX);
+        assert!(!bank.read_pin(32));
+        assert_eq!(probe.outputs(), 0);
+        assert_eq!(probe.levels(), 0);
+        assert_eq!(probe.pattern([31, 32]), [false, false]);
+    }
+
+    #[test]
+    fn test_recording_timestamps_are_monotonic() {
+        let mut bank = RecordingBank::new();
+        for pin in 0..8 {
+            bank.set_pin(pin);
+        }
+        let times: Vec<Duration> = bank.events().iter().map(PinEvent::at).collect();
+        assert!(times.windows(2).all(|w| w[0] <= w[1]));
+    }
+}
@@ -0,0 +1,688 @@
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::os::fd::{AsFd, AsRawFd, BorrowedFd, FromRawFd, OwnedFd};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::bank::GpioBank;
+use crate::uapi::*;
+
+/// Line direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Direction {
+    #[default]
+    Input,
+    Output,
+}
+
+/// Internal pull resistors. `AsIs` leaves whatever the firmware configured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Bias {
+    #[default]
+    AsIs,
+    PullUp,
+    PullDown,
+    Disabled,
+}
+
+/// Output driver.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Drive {
+    #[default]
+    PushPull,
+    OpenDrain,
+    OpenSource,
+}
+
+/// Which input edges generate events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EdgeDetect {
+    #[default]
+    None,
+    Rising,
+    Falling,
+    Both,
+}
+
+/// How a requested line is configured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LineConfig {
+    pub direction: Direction,
+    pub bias: Bias,
+    pub drive: Drive,
+    pub edge: EdgeDetect,
+    pub active_low: bool,
+    pub debounce: Option<Duration>,
+    /// Level driven as soon as an output line is granted.
+    pub initial_high: bool,
+}
+
+impl LineConfig {
+    pub fn input() -> Self {
+        Self::default()
+    }
+
+    pub fn output(initial_high: bool) -> Self {
+        Self {
+            direction: Direction::Output,
+            initial_high,
+            ..Self::default()
+        }
+    }
+
+    /// Line flags for the kernel; rejects combinations the kernel would refuse.
+    pub fn flags(&self) -> io::Result<u64> {
+        let mut flags = match self.direction {
+            Direction::Input => GPIO_V2_LINE_FLAG_INPUT,
+            Direction::Output => GPIO_V2_LINE_FLAG_OUTPUT,
+        };
+        if self.active_low {
+            flags |= GPIO_V2_LINE_FLAG_ACTIVE_LOW;
+        }
+        flags |= match self.bias {
+            Bias::AsIs => 0,
+            Bias::PullUp => GPIO_V2_LINE_FLAG_BIAS_PULL_UP,
+            Bias::PullDown => GPIO_V2_LINE_FLAG_BIAS_PULL_DOWN,
+            Bias::Disabled => GPIO_V2_LINE_FLAG_BIAS_DISABLED,
+        };
+
+        let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidInput, msg.to_string());
+        match self.direction {
+            Direction::Input => {
+                if self.drive != Drive::PushPull {
+                    return Err(invalid("drive mode requires an output line"));
+                }
+                flags |= match self.edge {
+                    EdgeDetect::None => 0,
+                    EdgeDetect::Rising => GPIO_V2_LINE_FLAG_EDGE_RISING,
+                    EdgeDetect::Falling => GPIO_V2_LINE_FLAG_EDGE_FALLING,
+                    EdgeDetect::Both => {
+                        GPIO_V2_LINE_FLAG_EDGE_RISING | GPIO_V2_LINE_FLAG_EDGE_FALLING
+                    }
+                };
+            }
+            Direction::Output => {
+                if self.edge != EdgeDetect::None || self.debounce.is_some() {
+                    return Err(invalid("edge detection and debounce require an input line"));
+                }
+                flags |= match self.drive {
+                    Drive::PushPull => 0,
+                    Drive::OpenDrain => GPIO_V2_LINE_FLAG_OPEN_DRAIN,
+                    Drive::OpenSource => GPIO_V2_LINE_FLAG_OPEN_SOURCE,
+                };
+            }
+        }
+        Ok(flags)
+    }
+
+    /// The full kernel config for `num_lines` lines sharing this configuration.
+    fn to_uapi(self, num_lines: usize) -> io::Result<GpioV2LineConfig> {
+        let all = mask(num_lines);
+        let mut config = GpioV2LineConfig {
+            flags: self.flags()?,
+            ..Default::default()
+        };
+        let mut push = |id, value| {
+            config.attrs[config.num_attrs as usize] = GpioV2LineConfigAttribute {
+                attr: GpioV2LineAttribute {
+                    id,
+                    padding: 0,
+                    value,
+                },
+                mask: all,
+            };
+            config.num_attrs += 1;
+        };
+        if self.direction == Direction::Output {
+            push(
+                GPIO_V2_LINE_ATTR_ID_OUTPUT_VALUES,
+                if self.initial_high { all } else { 0 },
+            );
+        }
+        if let Some(period) = self.debounce {
+            // The kernel holds the period as a u32 of microseconds.
+            let micros = u32::try_from(period.as_micros()).map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "debounce of {:?} is past the kernel's u32 microseconds",
+                        period
+                    ),
+                )
+            })?;
+            push(GPIO_V2_LINE_ATTR_ID_DEBOUNCE, micros as u64);
+        }
+        Ok(config)
+    }
+}
+
+fn mask(num_lines: usize) -> u64 {
+    if num_lines >= 64 {
+        u64::MAX
+    } else {
+        (1u64 << num_lines) - 1
+    }
+}
+
+fn ioctl<T>(fd: BorrowedFd<'_>, request: u32, arg: &mut T) -> io::Result<()> {
+    let ret = unsafe { libc::ioctl(fd.as_raw_fd(), request as _, arg as *mut T) };
+    if ret < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
+/// Identity of a GPIO chip as reported by the kernel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChipInfo {
+    pub name: String,
+    pub label: String,
+    pub lines: u32,
+}
+
+/// A line as the kernel currently sees it (possibly owned by another consumer).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineInfo {
+    pub offset: u32,
+    pub name: String,
+    pub consumer: String,
+    pub used: bool,
+    pub config: LineConfig,
+}
+
+/// A GPIO chip character device, `/dev/gpiochipN`. Needs no root, only access to the node.
+pub struct GpioChip {
+    file: File,
+    path: PathBuf,
+}
+
+impl GpioChip {
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new().read(true).write(true).open(&path)?;
+        let chip = Self { file, path };
+        chip.info()?; // Reject files that are not GPIO chips
+        Ok(chip)
+    }
+
+    /// Every chip under `/dev`, in path order. Nodes that fail to open are skipped.
+    pub fn all() -> Vec<Self> {
+        let mut paths: Vec<PathBuf> = std::fs::read_dir("/dev")
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with("gpiochip"))
+            })
+            .collect();
+        paths.sort();
+        paths
+            .into_iter()
+            .filter_map(|p| Self::open(p).ok())
+            .collect()
+    }
+
+    /// The first chip whose label starts with `label` (e.g. `pinctrl-rp1`).
+    pub fn find(label: &str) -> io::Result<Self> {
+        Self::all()
+            .into_iter()
+            .find(|chip| chip.info().is_ok_and(|info| info.label.starts_with(label)))
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("no gpiochip labelled {}", label),
+                )
+            })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn info(&self) -> io::Result<ChipInfo> {
+        let mut info = GpioChipInfo {
+            name: [0; GPIO_MAX_NAME_SIZE],
+            label: [0; GPIO_MAX_NAME_SIZE],
+            lines: 0,
+        };
+        ioctl(self.file.as_fd(), GPIO_GET_CHIPINFO_IOCTL, &mut info)?;
+        Ok(ChipInfo {
+            name: name_str(&info.name),
+            label: name_str(&info.label),
+            lines: info.lines,
+        })
+    }
+
+    pub fn line_info(&self, offset: u32) -> io::Result<LineInfo> {
+        let mut info = GpioV2LineInfo {
+            name: [0; GPIO_MAX_NAME_SIZE],
+            consumer: [0; GPIO_MAX_NAME_SIZE],
+            offset,
+            num_attrs: 0,
+            flags: 0,
+            attrs: [GpioV2LineAttribute::default(); GPIO_V2_LINE_NUM_ATTRS_MAX],
+            padding: [0; 4],
+        };
+        ioctl(self.file.as_fd(), GPIO_V2_GET_LINEINFO_IOCTL, &mut info)?;
+
+        let has = |flag| info.flags & flag != 0;
+        let debounce = info.attrs[..info.num_attrs as usize]
+            .iter()
+            .find(|a| a.id == GPIO_V2_LINE_ATTR_ID_DEBOUNCE)
+            .map(|a| Duration::from_micros(a.value & 0xFFFF_FFFF));
+        Ok(LineInfo {
+            offset,
+            name: name_str(&info.name),
+            consumer: name_str(&info.consumer),
+            used: has(GPIO_V2_LINE_FLAG_USED),
+            config: LineConfig {
+                direction: if has(GPIO_V2_LINE_FLAG_OUTPUT) {
+                    Direction::Output
+                } else {
+                    Direction::Input
+                },
+                bias: if has(GPIO_V2_LINE_FLAG_BIAS_PULL_UP) {
+                    Bias::PullUp
+                } else if has(GPIO_V2_LINE_FLAG_BIAS_PULL_DOWN) {
+                    Bias::PullDown
+                } else if has(GPIO_V2_LINE_FLAG_BIAS_DISABLED) {
+                    Bias::Disabled
+                } else {
+                    Bias::AsIs
+                },
+                drive: if has(GPIO_V2_LINE_FLAG_OPEN_DRAIN) {
+                    Drive::OpenDrain
+                } else if has(GPIO_V2_LINE_FLAG_OPEN_SOURCE) {
+                    Drive::OpenSource
+                } else {
+                    Drive::PushPull
+                },
+                edge: match (
+                    has(GPIO_V2_LINE_FLAG_EDGE_RISING),
+                    has(GPIO_V2_LINE_FLAG_EDGE_FALLING),
+                ) {
+                    (true, true) => EdgeDetect::Both,
+                    (true, false) => EdgeDetect::Rising,
+                    (false, true) => EdgeDetect::Falling,
+                    (false, false) => EdgeDetect::None,
+                },
+                active_low: has(GPIO_V2_LINE_FLAG_ACTIVE_LOW),
+                debounce,
+                initial_high: false,
+            },
+        })
+    }
+
+    /// Claims `offsets` for `consumer` (shown in `gpioinfo`), all with the same config.
+    pub fn request(
+        &self,
+        offsets: &[u32],
+        consumer: &str,
+        config: &LineConfig,
+    ) -> io::Result<LineRequest> {
+        if offsets.is_empty() || offsets.len() > GPIO_V2_LINES_MAX {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("a request takes 1..={} lines", GPIO_V2_LINES_MAX),
+            ));
+        }
+
+        let mut request = GpioV2LineRequest {
+            offsets: [0; GPIO_V2_LINES_MAX],
+            consumer: name_bytes(consumer),
+            config: config.to_uapi(offsets.len())?,
+            num_lines: offsets.len() as u32,
+            event_buffer_size: 0, // Kernel default: 16 events per line
+            padding: [0; 5],
+            fd: -1,
+        };
+        request.offsets[..offsets.len()].copy_from_slice(offsets);
+        ioctl(self.file.as_fd(), GPIO_V2_GET_LINE_IOCTL, &mut request)?;
+
+        Ok(LineRequest {
+            fd: unsafe { OwnedFd::from_raw_fd(request.fd) },
+            offsets: offsets.to_vec(),
+        })
+    }
+}
+
+/// Kind of edge seen on an input line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edge {
+    Rising,
+    Falling,
+}
+
+/// An input edge, timestamped by the kernel (`CLOCK_MONOTONIC`, nanoseconds).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EdgeEvent {
+    pub timestamp_ns: u64,
+    pub offset: u32,
+    pub edge: Edge,
+    /// Sequence number across all lines of the request.
+    pub seqno: u32,
+    /// Sequence number on this line alone.
+    pub line_seqno: u32,
+}
+
+/// A set of lines granted by [`GpioChip::request`]. Released when dropped.
+pub struct LineRequest {
+    fd: OwnedFd,
+    offsets: Vec<u32>,
+}
+
+impl AsFd for LineRequest {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.fd.as_fd()
+    }
+}
+
+impl LineRequest {
+    pub fn offsets(&self) -> &[u32] {
+        &self.offsets
+    }
+
+    fn bit(&self, offset: u32) -> io::Result<u64> {
+        self.offsets
+            .iter()
+            .position(|&o| o == offset)
+            .map(|i| 1u64 << i)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("line {} is not part of this request", offset),
+                )
+            })
+    }
+
+    /// Logical levels of every line, bit `i` for `offsets()[i]`.
+    pub fn values(&self) -> io::Result<u64> {
+        let mut values = GpioV2LineValues {
+            bits: 0,
+            mask: mask(self.offsets.len()),
+        };
+        ioctl(self.fd.as_fd(), GPIO_V2_LINE_GET_VALUES_IOCTL, &mut values)?;
+        Ok(values.bits)
+    }
+
+    /// Drives the lines selected by `mask` (bit `i` for `offsets()[i]`) to `bits`.
+    pub fn set_values(&self, bits: u64, mask: u64) -> io::Result<()> {
+        let mut values = GpioV2LineValues { bits, mask };
+        ioctl(self.fd.as_fd(), GPIO_V2_LINE_SET_VALUES_IOCTL, &mut values)
+    }
+
+    pub fn get(&self, offset: u32) -> io::Result<bool> {
+        let bit = self.bit(offset)?;
+        Ok(self.values()? & bit != 0)
+    }
+
+    pub fn set(&self, offset: u32, high: bool) -> io::Result<()> {
+        let bit = self.bit(offset)?;
+        self.set_values(if high { bit } else { 0 }, bit)
+    }
+
+    /// Applies a new configuration to every line without releasing them.
+    pub fn reconfigure(&self, config: &LineConfig) -> io::Result<()> {
+        let mut uapi = config.to_uapi(self.offsets.len())?;
+        ioctl(self.fd.as_fd(), GPIO_V2_LINE_SET_CONFIG_IOCTL, &mut uapi)
+    }
+
+    /// Blocks until the next edge event.
+    pub fn read_event(&self) -> io::Result<EdgeEvent> {
+        let mut raw = GpioV2LineEvent::default();
+        let size = std::mem::size_of::<GpioV2LineEvent>();
+        let n = unsafe {
+            libc::read(
+                self.fd.as_raw_fd(),
+                &mut raw as *mut GpioV2LineEvent as *mut libc::c_void,
+                size,
+            )
+        };
+        if n < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        if n as usize != size {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "short read of gpio line event",
+            ));
+        }
+        let edge = match raw.id {
+            GPIO_V2_LINE_EVENT_RISING_EDGE => Edge::Rising,
+            GPIO_V2_LINE_EVENT_FALLING_EDGE => Edge::Falling,
+            id => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("unknown gpio event id {}", id),
+                ));
+            }
+        };
+        Ok(EdgeEvent {
+            timestamp_ns: raw.timestamp_ns,
+            offset: raw.offset,
+            edge,
+            seqno: raw.seqno,
+            line_seqno: raw.line_seqno,
+        })
+    }
+
+    /// Waits up to `timeout` for an edge event; `None` if none arrived.
+    pub fn wait_event(&self, timeout: Duration) -> io::Result<Option<EdgeEvent>> {
+        let mut pollfd = libc::pollfd {
+            fd: self.fd.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let millis = timeout.as_millis().min(i32::MAX as u128) as libc::c_int;
+        let ready = unsafe { libc::poll(&mut pollfd, 1, millis) };
+        match ready {
+            n if n < 0 => Err(io::Error::last_os_error()),
+            0 => Ok(None),
+            _ => self.read_event().map(Some),
+        }
+    }
+}
+
+/// [`GpioBank`] over a gpiochip: each pin is claimed as its own line, on first use.
+///
+/// Errors cannot surface through the trait, so the most recent one is kept for
+/// [`ChipBank::take_error`]; failed reads report low.
+pub struct ChipBank {
+    chip: GpioChip,
+    consumer: String,
+    lines: HashMap<u32, LineRequest>,
+    error: Option<io::Error>,
+}
+
+impl ChipBank {
+    pub fn new(chip: GpioChip, consumer: &str) -> Self {
+        Self {
+            chip,
+            consumer: consumer.to_string(),
+            lines: HashMap::new(),
+            error: None,
+        }
+    }
+
+    /// Opens the RP1's own chip (`pinctrl-rp1`), the unprivileged route to the RIO pins.
+    pub fn rp1(consumer: &str) -> io::Result<Self> {
+        GpioChip::find("pinctrl-rp1").map(|chip| Self::new(chip, consumer))
+    }
+
+    pub fn chip(&self) -> &GpioChip {
+        &self.chip
+    }
+
+    /// Claims (or reconfigures) `pin` with `config`.
+    pub fn configure(&mut self, pin: u32, config: &LineConfig) -> io::Result<()> {
+        match self.lines.get(&pin) {
+            Some(line) => line.reconfigure(config),
+            None => {
+                let line = self.chip.request(&[pin], &self.consumer, config)?;
+                self.lines.insert(pin, line);
+                Ok(())
+            }
+        }
+    }
+
+    /// The claimed line for `pin`, e.g. to wait for its edge events.
+    pub fn line(&self, pin: u32) -> Option<&LineRequest> {
+        self.lines.get(&pin)
+    }
+
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
+
+    fn drive(&mut self, pin: u32, high: bool) {
+        let result = match self.lines.get(&pin) {
+            Some(line) => line.set(pin, high),
+            None => self.configure(pin, &LineConfig::output(high)),
+        };
+        if let Err(e) = result {
+            self.error = Some(e);
+        }
+    }
+}
+
+impl GpioBank for ChipBank {
+    fn enable_output(&mut self, pin: u32) {
+        if let Err(e) = self.configure(pin, &LineConfig::output(false)) {
+            self.error = Some(e);
+        }
+    }
+
+    fn set_pin(&mut self, pin: u32) {
+        self.drive(pin, true);
+    }
+
+    fn clr_pin(&mut self, pin: u32) {
+        self.drive(pin, false);
+    }
+
+    fn read_pin(&self, pin: u32) -> bool {
+        self.lines
+            .get(&pin)
+            .is_some_and(|line| line.get(pin).unwrap_or(false))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_config_flags() {
+        assert_eq!(
+            LineConfig::input().flags().unwrap(),
+            GPIO_V2_LINE_FLAG_INPUT
+        );
+
+        let button = LineConfig {
+            bias: Bias::PullUp,
+            edge: EdgeDetect::Both,
+            active_low: true,
+            ..LineConfig::input()
+        };
+        assert_eq!(
+            button.flags().unwrap(),
+            GPIO_V2_LINE_FLAG_INPUT
+                | GPIO_V2_LINE_FLAG_BIAS_PULL_UP
+                | GPIO_V2_LINE_FLAG_EDGE_RISING
+                | GPIO_V2_LINE_FLAG_EDGE_FALLING
+                | GPIO_V2_LINE_FLAG_ACTIVE_LOW
+        );
+
+        let led = LineConfig {
+            drive: Drive::OpenDrain,
+            ..LineConfig::output(true)
+        };
+        let uapi = led.to_uapi(3).unwrap();
+        assert_eq!(
+            uapi.flags,
+            GPIO_V2_LINE_FLAG_OUTPUT | GPIO_V2_LINE_FLAG_OPEN_DRAIN
+        );
+        assert_eq!(uapi.num_attrs, 1);
+        assert_eq!(uapi.attrs[0].attr.id, GPIO_V2_LINE_ATTR_ID_OUTPUT_VALUES);
+        assert_eq!(
+            (uapi.attrs[0].attr.value, uapi.attrs[0].mask),
+            (0b111, 0b111)
+        );
+
+        let bad = LineConfig {
+            edge: EdgeDetect::Rising,
+            ..LineConfig::output(false)
+        };
+        assert_eq!(bad.flags().unwrap_err().kind(), io::ErrorKind::InvalidInput);
+
+        let longest = LineConfig {
+            debounce: Some(Duration::from_micros(u32::MAX as u64)),
+            ..LineConfig::input()
+        };
+        let uapi = longest.to_uapi(1).unwrap();
+        assert_eq!(uapi.attrs[0].attr.value, u32::MAX as u64);
+        let too_long = LineConfig {
+            debounce: Some(Duration::from_micros(u32::MAX as u64 + 1)),
+            ..LineConfig::input()
+        };
+        let err = too_long.to_uapi(1).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    /// The kernel's simulator chip (`gpio-sim`), if one has been set up through configfs.
+    fn simulator() -> Option<GpioChip> {
+        GpioChip::all().into_iter().find(|chip| {
+            chip.info().is_ok_and(|i| {
+                i.label.starts_with("gpio-sim") || i.label.starts_with("gpio-mockup")
+            })
+        })
+    }
+
+    /// Run with `cargo test -- --ignored` once a `gpio-sim` or `gpio-mockup` chip is set up.
+    #[test]
+    #[ignore = "needs a gpio-sim or gpio-mockup chip"]
+    fn test_simulated_chip_round_trip() {
+        let chip = simulator().expect("no gpio-sim/gpio-mockup chip");
+        let info = chip.info().unwrap();
+        assert!(info.lines >= 2);
+
+        let mut bank = ChipBank::new(chip, "rp1-rio-test");
+        bank.enable_output(0);
+        bank.set_pin(0);
+        assert!(bank.read_pin(0));
+        bank.clr_pin(0);
+        assert!(!bank.read_pin(0));
+        assert!(bank.take_error().is_none());
+
+        let line = bank.chip().line_info(0).unwrap();
+        assert!(line.used);
+        assert_eq!(line.consumer, "rp1-rio-test");
+        assert_eq!(line.config.direction, Direction::Output);
+
+        // Edge events need the simulator's pull knob (gpio-sim only).
+        let pull = format!("/sys/bus/gpio/devices/{}/sim_gpio1/pull", info.name);
+        if !Path::new(&pull).exists() {
+            return;
+        }
+        std::fs::write(&pull, "pull-down").unwrap();
+        let watch = LineConfig {
+            edge: EdgeDetect::Both,
+            ..LineConfig::input()
+        };
+        bank.configure(1, &watch).unwrap();
+        std::fs::write(&pull, "pull-up").unwrap();
+        let event = bank
+            .line(1)
+            .unwrap()
+            .wait_event(Duration::from_secs(1))
+            .unwrap()
+            .expect("rising edge");
+        assert_eq!((event.offset, event.edge), (1, Edge::Rising));
+        assert!(event.timestamp_ns > 0);
+    }
+}
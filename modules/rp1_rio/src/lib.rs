@@ -2,6 +2,15 @@ use std::fs::OpenOptions;
 use std::os::fd::AsRawFd;
 use std::ptr;
 
+mod bank;
+mod chip;
+mod uapi;
+pub use bank::{pin_mask, GpioBank, PinEvent, RecordingBank};
+pub use chip::{
+    Bias, ChipBank, ChipInfo, Direction, Drive, Edge, EdgeDetect, EdgeEvent, GpioChip, LineConfig,
+    LineInfo, LineRequest,
+};
+
 // RP1 Peripheral Base Address (from RPi 5 devicetree / Zephyr driver)
 // Note: This is the physical address aperture for the RP1.
 const RP1_PERI_BASE: u64 = 0x1f00000000;
@@ -59,7 +68,7 @@ impl Rp1Rio {
     pub fn set_pin(&mut self, pin: u32) {
         unsafe {
             let reg = self.base_ptr.add(RIO_OUT + RIO_SET / 4); // Pointer arithmetic is in T (u32), so divide byte offset by 4
-            ptr::write_volatile(reg, 1 << pin);
+            ptr::write_volatile(reg, pin_mask(pin));
         }
     }
 
@@ -68,7 +77,7 @@ impl Rp1Rio {
     pub fn clr_pin(&mut self, pin: u32) {
         unsafe {
             let reg = self.base_ptr.add(RIO_OUT + RIO_CLR / 4);
-            ptr::write_volatile(reg, 1 << pin);
+            ptr::write_volatile(reg, pin_mask(pin));
         }
     }
 
@@ -77,7 +86,7 @@ impl Rp1Rio {
     pub fn xor_pin(&mut self, pin: u32) {
         unsafe {
             let reg = self.base_ptr.add(RIO_OUT + RIO_XOR / 4);
-            ptr::write_volatile(reg, 1 << pin);
+            ptr::write_volatile(reg, pin_mask(pin));
         }
     }
 
@@ -85,7 +94,7 @@ impl Rp1Rio {
     pub fn enable_output(&mut self, pin: u32) {
         unsafe {
             let reg = self.base_ptr.add(RIO_OE + RIO_SET / 4);
-            ptr::write_volatile(reg, 1 << pin);
+            ptr::write_volatile(reg, pin_mask(pin));
         }
     }
 
@@ -94,7 +103,7 @@ impl Rp1Rio {
         unsafe {
             let reg = self.base_ptr.add(RIO_IN / 4);
             let val = ptr::read_volatile(reg);
-            (val & (1 << pin)) != 0
+            (val & pin_mask(pin)) != 0
         }
     }
 }
@@ -0,0 +1,162 @@
+//! Mirror of `<linux/gpio.h>` (GPIO character device, v2 uAPI).
+
+#![allow(dead_code)]
+
+pub const GPIO_MAX_NAME_SIZE: usize = 32;
+pub const GPIO_V2_LINES_MAX: usize = 64;
+pub const GPIO_V2_LINE_NUM_ATTRS_MAX: usize = 10;
+
+pub const GPIO_V2_LINE_FLAG_USED: u64 = 1 << 0;
+pub const GPIO_V2_LINE_FLAG_ACTIVE_LOW: u64 = 1 << 1;
+pub const GPIO_V2_LINE_FLAG_INPUT: u64 = 1 << 2;
+pub const GPIO_V2_LINE_FLAG_OUTPUT: u64 = 1 << 3;
+pub const GPIO_V2_LINE_FLAG_EDGE_RISING: u64 = 1 << 4;
+pub const GPIO_V2_LINE_FLAG_EDGE_FALLING: u64 = 1 << 5;
+pub const GPIO_V2_LINE_FLAG_OPEN_DRAIN: u64 = 1 << 6;
+pub const GPIO_V2_LINE_FLAG_OPEN_SOURCE: u64 = 1 << 7;
+pub const GPIO_V2_LINE_FLAG_BIAS_PULL_UP: u64 = 1 << 8;
+pub const GPIO_V2_LINE_FLAG_BIAS_PULL_DOWN: u64 = 1 << 9;
+pub const GPIO_V2_LINE_FLAG_BIAS_DISABLED: u64 = 1 << 10;
+pub const GPIO_V2_LINE_FLAG_EVENT_CLOCK_REALTIME: u64 = 1 << 11;
+
+pub const GPIO_V2_LINE_ATTR_ID_FLAGS: u32 = 1;
+pub const GPIO_V2_LINE_ATTR_ID_OUTPUT_VALUES: u32 = 2;
+pub const GPIO_V2_LINE_ATTR_ID_DEBOUNCE: u32 = 3;
+
+pub const GPIO_V2_LINE_EVENT_RISING_EDGE: u32 = 1;
+pub const GPIO_V2_LINE_EVENT_FALLING_EDGE: u32 = 2;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct GpioChipInfo {
+    pub name: [u8; GPIO_MAX_NAME_SIZE],
+    pub label: [u8; GPIO_MAX_NAME_SIZE],
+    pub lines: u32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GpioV2LineValues {
+    pub bits: u64,
+    pub mask: u64,
+}
+
+/// `id` selects which of flags / values / debounce_period_us the payload holds.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GpioV2LineAttribute {
+    pub id: u32,
+    pub padding: u32,
+    pub value: u64,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GpioV2LineConfigAttribute {
+    pub attr: GpioV2LineAttribute,
+    pub mask: u64,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GpioV2LineConfig {
+    pub flags: u64,
+    pub num_attrs: u32,
+    pub padding: [u32; 5],
+    pub attrs: [GpioV2LineConfigAttribute; GPIO_V2_LINE_NUM_ATTRS_MAX],
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct GpioV2LineRequest {
+    pub offsets: [u32; GPIO_V2_LINES_MAX],
+    pub consumer: [u8; GPIO_MAX_NAME_SIZE],
+    pub config: GpioV2LineConfig,
+    pub num_lines: u32,
+    pub event_buffer_size: u32,
+    pub padding: [u32; 5],
+    pub fd: i32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct GpioV2LineInfo {
+    pub name: [u8; GPIO_MAX_NAME_SIZE],
+    pub consumer: [u8; GPIO_MAX_NAME_SIZE],
+    pub offset: u32,
+    pub num_attrs: u32,
+    pub flags: u64,
+    pub attrs: [GpioV2LineAttribute; GPIO_V2_LINE_NUM_ATTRS_MAX],
+    pub padding: [u32; 4],
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GpioV2LineEvent {
+    pub timestamp_ns: u64,
+    pub id: u32,
+    pub offset: u32,
+    pub seqno: u32,
+    pub line_seqno: u32,
+    pub padding: [u32; 6],
+}
+
+const IOC_WRITE: u32 = 1;
+const IOC_READ: u32 = 2;
+
+const fn ioc<T>(dir: u32, nr: u32) -> u32 {
+    (dir << 30) | ((std::mem::size_of::<T>() as u32) << 16) | (0xB4 << 8) | nr
+}
+
+pub const GPIO_GET_CHIPINFO_IOCTL: u32 = ioc::<GpioChipInfo>(IOC_READ, 0x01);
+pub const GPIO_V2_GET_LINEINFO_IOCTL: u32 = ioc::<GpioV2LineInfo>(IOC_READ | IOC_WRITE, 0x05);
+pub const GPIO_V2_GET_LINE_IOCTL: u32 = ioc::<GpioV2LineRequest>(IOC_READ | IOC_WRITE, 0x07);
+pub const GPIO_V2_LINE_SET_CONFIG_IOCTL: u32 = ioc::<GpioV2LineConfig>(IOC_READ | IOC_WRITE, 0x0D);
+pub const GPIO_V2_LINE_GET_VALUES_IOCTL: u32 = ioc::<GpioV2LineValues>(IOC_READ | IOC_WRITE, 0x0E);
+pub const GPIO_V2_LINE_SET_VALUES_IOCTL: u32 = ioc::<GpioV2LineValues>(IOC_READ | IOC_WRITE, 0x0F);
+
+/// Copies `s` into a NUL-terminated fixed-size name, truncating if needed.
+pub fn name_bytes(s: &str) -> [u8; GPIO_MAX_NAME_SIZE] {
+    let mut out = [0u8; GPIO_MAX_NAME_SIZE];
+    let len = s.len().min(GPIO_MAX_NAME_SIZE - 1);
+    out[..len].copy_from_slice(&s.as_bytes()[..len]);
+    out
+}
+
+pub fn name_str(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::mem::size_of;
+
+    #[test]
+    fn test_layouts_match_the_kernel_abi() {
+        assert_eq!(size_of::<GpioChipInfo>(), 68);
+        assert_eq!(size_of::<GpioV2LineValues>(), 16);
+        assert_eq!(size_of::<GpioV2LineAttribute>(), 16);
+        assert_eq!(size_of::<GpioV2LineConfig>(), 272);
+        assert_eq!(size_of::<GpioV2LineRequest>(), 592);
+        assert_eq!(size_of::<GpioV2LineInfo>(), 256);
+        assert_eq!(size_of::<GpioV2LineEvent>(), 48);
+
+        assert_eq!(GPIO_GET_CHIPINFO_IOCTL, 0x8044_B401);
+        assert_eq!(GPIO_V2_GET_LINEINFO_IOCTL, 0xC100_B405);
+        assert_eq!(GPIO_V2_GET_LINE_IOCTL, 0xC250_B407);
+        assert_eq!(GPIO_V2_LINE_SET_CONFIG_IOCTL, 0xC110_B40D);
+        assert_eq!(GPIO_V2_LINE_GET_VALUES_IOCTL, 0xC010_B40E);
+        assert_eq!(GPIO_V2_LINE_SET_VALUES_IOCTL, 0xC010_B40F);
+    }
+
+    #[test]
+    fn test_names_are_truncated_and_terminated() {
+        let long = "z".repeat(40);
+        let bytes = name_bytes(&long);
+        assert_eq!(bytes[31], 0);
+        assert_eq!(name_str(&bytes).len(), 31);
+        assert_eq!(name_str(&name_bytes("zrr-rainbow")), "zrr-rainbow");
+    }
+}
@@ -1,10 +1,18 @@
-use crate::eight_gate::{RecursiveFilter, Stance};
+use crate::eight_gate::{CoherenceReport, RecursiveFilter, Stance};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
 
 /// The Behavioral Identifier ($\B$).
 /// Analyzes spectral waves to identify the "Behavior" of the system.
-pub struct BehavioralEngine;
+pub struct BehavioralEngine {
+    /// Resonance filter. May be shared with other consumers (e.g. `ZeroWaitHandshake`).
+    filter: Arc<RecursiveFilter>,
+}
 
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct BehaviorReport {
     pub active_stance: Stance,
     pub secondary_stance: Option<Stance>,
@@ -13,33 +21,616 @@ pub struct BehaviorReport {
     pub frequency_match: bool, // [NEW] Flag for 1.50Hz sync
 }
 
+impl Default for BehavioralEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl BehavioralEngine {
-    /// Identify the current behavior from a raw data sample.
-    pub fn identify(sample: f64, last_density: f64, current_hz: f64) -> Option<BehaviorReport> {
-        // Use the Recursive Filter to find the primary resonance
+    pub fn new() -> Self {
+        Self::with_filter(Arc::new(RecursiveFilter::new()))
+    }
+
+    pub fn with_filter(filter: Arc<RecursiveFilter>) -> Self {
+        Self { filter }
+    }
+
+    pub fn filter(&self) -> &Arc<RecursiveFilter> {
+        &self.filter
+    }
+
+    /// Identify the current behavior from a raw data sample, carrying density on the filter.
+    pub fn identify(&self, sample: f64, current_hz: f64) -> Option<BehaviorReport> {
         // RECURSIVE FILTER INTEGRATION
-        // Zero Wait is only permitted if the data resonates.
-        if let Some(mut report) = RecursiveFilter::observe(sample, last_density) {
-            let src = report.source;
-            let dst = report.destination;
-            let intensity = (sample / src.signature()).min(1.0);
+        let report = self.filter.observe(sample)?;
+        Some(Self::classify(report, sample, current_hz))
+    }
+
+    /// Identify against an explicit previous density, leaving the filter state untouched.
+    pub fn identify_at(
+        &self,
+        sample: f64,
+        last_density: f64,
+        current_hz: f64,
+    ) -> Option<BehaviorReport> {
+        let report = self.filter.observe_at(sample, last_density)?;
+        Some(Self::classify(report, sample, current_hz))
+    }
+
+    fn classify(mut report: CoherenceReport, sample: f64, current_hz: f64) -> BehaviorReport {
+        let src = report.source;
+        let dst = report.destination;
+        let intensity = (sample / src.signature()).min(1.0);
+
+        // [NEW] Nakama Resonance Detection (1.50Hz Tolerance)
+        let freq_match = (current_hz - 1.50).abs() < 0.2;
+
+        if freq_match {
+            report.source = Stance::NakamaSync;
+        }
+
+        BehaviorReport {
+            active_stance: report.source,
+            secondary_stance: Some(dst),
+            intensity,
+            resonance_score: if freq_match { 1.0 } else { 0.95 },
+            frequency_match: freq_match,
+        }
+    }
+}
+
+/// Thresholds for flagging behaviour against a baseline.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SessionConfig {
+    /// Transitions rarer than this in the baseline are flagged.
+    pub rare_probability: f64,
+    /// A dwell this many times longer or shorter than the baseline mean is flagged.
+    pub dwell_factor: f64,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            rare_probability: 0.02,
+            dwell_factor: 3.0,
+        }
+    }
+}
+
+/// Running dwell-time statistics for one stance (seconds).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DwellStats {
+    pub count: u64,
+    pub total: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Default for DwellStats {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            total: 0.0,
+            min: f64::INFINITY,
+            max: 0.0,
+        }
+    }
+}
+
+impl DwellStats {
+    pub fn record(&mut self, dwell: f64) {
+        self.count += 1;
+        self.total += dwell;
+        self.min = self.min.min(dwell);
+        self.max = self.max.max(dwell);
+    }
+
+    pub fn mean(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.total / self.count as f64
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TransitionCount {
+    pub from: Stance,
+    pub to: Stance,
+    pub count: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StanceDwell {
+    pub stance: Stance,
+    pub stats: DwellStats,
+}
+
+/// Something the baseline says should not happen.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum Anomaly {
+    /// The stance never appears in the baseline.
+    UnseenStance { stance: Stance },
+    /// The baseline never moved between these stances.
+    UnseenTransition { from: Stance, to: Stance },
+    /// The baseline moved between these stances less than `rare_probability` of the time.
+    RareTransition {
+        from: Stance,
+        to: Stance,
+        probability: f64,
+    },
+    /// A finished dwell far from the baseline mean for the stance.
+    DwellOutlier {
+        stance: Stance,
+        dwell: f64,
+        baseline_mean: f64,
+    },
+}
+
+/// An anomaly and the session time it was raised at.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct AnomalyFlag {
+    pub at: f64,
+    pub anomaly: Anomaly,
+}
+
+/// What a session learned: transition counts and dwell statistics. Saved as JSON.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BehaviorBaseline {
+    pub samples: u64,
+    pub transitions: Vec<TransitionCount>,
+    pub dwell: Vec<StanceDwell>,
+}
+
+impl BehaviorBaseline {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&text)?)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn transition_count(&self, from: Stance, to: Stance) -> u64 {
+        self.transitions
+            .iter()
+            .find(|t| t.from == from && t.to == to)
+            .map_or(0, |t| t.count)
+    }
+
+    /// P(to | from) over the baseline's observed transitions out of `from`.
+    pub fn transition_probability(&self, from: Stance, to: Stance) -> f64 {
+        let out: u64 = self
+            .transitions
+            .iter()
+            .filter(|t| t.from == from)
+            .map(|t| t.count)
+            .sum();
+        if out == 0 {
+            0.0
+        } else {
+            self.transition_count(from, to) as f64 / out as f64
+        }
+    }
 
-            // [NEW] Nakama Resonance Detection (1.50Hz Tolerance)
-            let freq_match = (current_hz - 1.50).abs() < 0.2;
+    pub fn dwell_for(&self, stance: Stance) -> Option<&DwellStats> {
+        self.dwell
+            .iter()
+            .find(|d| d.stance == stance)
+            .map(|d| &d.stats)
+    }
+
+    pub fn knows(&self, stance: Stance) -> bool {
+        self.dwell_for(stance).is_some()
+            || self
+                .transitions
+                .iter()
+                .any(|t| t.from == stance || t.to == stance)
+    }
+}
+
+/// Time share of one stance over a session.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Occupancy {
+    pub stance: Stance,
+    pub seconds: f64,
+    pub ratio: f64,
+}
+
+/// The overall shape of a session.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BehaviorProfile {
+    pub samples: u64,
+    /// Samples the filter found resonant.
+    pub resolved: u64,
+    pub duration: f64,
+    /// Most time first.
+    pub occupancy: Vec<Occupancy>,
+    pub dominant_stance: Option<Stance>,
+    pub transitions: u64,
+    /// Transitions per second.
+    pub transition_rate: f64,
+    pub mean_intensity: f64,
+    /// Fraction of resolved samples in 1.50Hz Nakama sync.
+    pub nakama_ratio: f64,
+    pub anomalies: usize,
+}
 
-            if freq_match {
-                report.source = Stance::NakamaSync;
+/// What one sample did to the session.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SessionEvent {
+    pub report: Option<BehaviorReport>,
+    /// `(from, to)` when the active stance changed.
+    pub transition: Option<(Stance, Stance)>,
+    pub anomalies: Vec<Anomaly>,
+}
+
+/// Session-level behaviour: accumulates reports from a `BehavioralEngine` into a stance
+/// transition matrix and dwell statistics, and flags departures from a baseline.
+pub struct BehaviorSession {
+    pub engine: BehavioralEngine,
+    pub config: SessionConfig,
+    baseline: Option<BehaviorBaseline>,
+    transitions: HashMap<(Stance, Stance), u64>,
+    dwell: HashMap<Stance, DwellStats>,
+    occupancy: HashMap<Stance, f64>,
+    /// Active stance and the time (`at`, seconds) it was entered; the last sample time is
+    /// `last_at`.
+    current: Option<(Stance, f64)>,
+    first_at: Option<f64>,
+    last_at: Option<f64>,
+    samples: u64,
+    resolved: u64,
+    intensity_sum: f64,
+    nakama: u64,
+    anomalies: Vec<AnomalyFlag>,
+}
+
+impl BehaviorSession {
+    pub fn new(engine: BehavioralEngine) -> Self {
+        Self {
+            engine,
+            config: SessionConfig::default(),
+            baseline: None,
+            transitions: HashMap::new(),
+            dwell: HashMap::new(),
+            occupancy: HashMap::new(),
+            current: None,
+            first_at: None,
+            last_at: None,
+            samples: 0,
+            resolved: 0,
+            intensity_sum: 0.0,
+            nakama: 0,
+            anomalies: Vec::new(),
+        }
+    }
+
+    pub fn with_config(mut self, config: SessionConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Compare live behaviour against `baseline` from now on.
+    pub fn with_baseline(mut self, baseline: BehaviorBaseline) -> Self {
+        self.baseline = Some(baseline);
+        self
+    }
+
+    pub fn baseline(&self) -> Option<&BehaviorBaseline> {
+        self.baseline.as_ref()
+    }
+
+    /// Identifies `sample` (taken at `at` seconds) and folds it into the session.
+    pub fn observe(&mut self, at: f64, sample: f64, current_hz: f64) -> SessionEvent {
+        let report = self.engine.identify(sample, current_hz);
+        self.record(at, report)
+    }
+
+    /// Folds an already identified report (or a non-resonant `None`) into the session.
+    pub fn record(&mut self, at: f64, report: Option<BehaviorReport>) -> SessionEvent {
+        self.samples += 1;
+        self.first_at.get_or_insert(at);
+        // Time since the last sample belongs to the stance that was active.
+        if let (Some((stance, _)), Some(last)) = (self.current, self.last_at) {
+            *self.occupancy.entry(stance).or_insert(0.0) += (at - last).max(0.0);
+        }
+        self.last_at = Some(at);
+
+        let mut event = SessionEvent {
+            report: report.clone(),
+            transition: None,
+            anomalies: Vec::new(),
+        };
+        let Some(report) = report else {
+            return event;
+        };
+
+        self.resolved += 1;
+        self.intensity_sum += report.intensity;
+        if report.frequency_match {
+            self.nakama += 1;
+        }
+
+        let stance = report.active_stance;
+        match self.current {
+            Some((from, _)) if from == stance => {}
+            Some((from, entered)) => {
+                let dwell = (at - entered).max(0.0);
+                self.dwell.entry(from).or_default().record(dwell);
+                *self.transitions.entry((from, stance)).or_insert(0) += 1;
+                event.transition = Some((from, stance));
+                event.anomalies = self.check(from, stance, dwell);
+                self.current = Some((stance, at));
             }
+            None => {
+                event.anomalies = self.check_stance(stance).into_iter().collect();
+                self.current = Some((stance, at));
+            }
+        }
 
-            Some(BehaviorReport {
-                active_stance: report.source,
-                secondary_stance: Some(dst),
-                intensity,
-                resonance_score: if freq_match { 1.0 } else { 0.95 },
-                frequency_match: freq_match,
-            })
+        self.anomalies.extend(
+            event
+                .anomalies
+                .iter()
+                .map(|&anomaly| AnomalyFlag { at, anomaly }),
+        );
+        event
+    }
+
+    fn check_stance(&self, stance: Stance) -> Option<Anomaly> {
+        let baseline = self.baseline.as_ref()?;
+        (!baseline.knows(stance)).then_some(Anomaly::UnseenStance { stance })
+    }
+
+    fn check(&self, from: Stance, to: Stance, dwell: f64) -> Vec<Anomaly> {
+        let Some(baseline) = &self.baseline else {
+            return Vec::new();
+        };
+        let mut anomalies: Vec<Anomaly> = self.check_stance(to).into_iter().collect();
+
+        if baseline.transition_count(from, to) == 0 {
+            anomalies.push(Anomaly::UnseenTransition { from, to });
         } else {
-            None
+            let probability = baseline.transition_probability(from, to);
+            if probability < self.config.rare_probability {
+                anomalies.push(Anomaly::RareTransition {
+                    from,
+                    to,
+                    probability,
+                });
+            }
+        }
+
+        if let Some(stats) = baseline.dwell_for(from) {
+            let baseline_mean = stats.mean();
+            let factor = self.config.dwell_factor;
+            if baseline_mean > 0.0
+                && (dwell > baseline_mean * factor || dwell < baseline_mean / factor)
+            {
+                anomalies.push(Anomaly::DwellOutlier {
+                    stance: from,
+                    dwell,
+                    baseline_mean,
+                });
+            }
         }
+        anomalies
+    }
+
+    pub fn transition_count(&self, from: Stance, to: Stance) -> u64 {
+        self.transitions.get(&(from, to)).copied().unwrap_or(0)
+    }
+
+    /// The transition matrix over every stance seen, rows are `from`, columns `to`.
+    pub fn transition_matrix(&self) -> (Vec<Stance>, Vec<Vec<u64>>) {
+        let mut stances: Vec<Stance> = self
+            .transitions
+            .keys()
+            .flat_map(|&(a, b)| [a, b])
+            .chain(self.current.map(|(s, _)| s))
+            .collect();
+        stances.sort();
+        stances.dedup();
+        let matrix = stances
+            .iter()
+            .map(|&from| {
+                stances
+                    .iter()
+                    .map(|&to| self.transition_count(from, to))
+                    .collect()
+            })
+            .collect();
+        (stances, matrix)
+    }
+
+    /// Dwell statistics for finished stays in `stance`.
+    pub fn dwell(&self, stance: Stance) -> Option<&DwellStats> {
+        self.dwell.get(&stance)
+    }
+
+    pub fn anomalies(&self) -> &[AnomalyFlag] {
+        &self.anomalies
+    }
+
+    pub fn profile(&self) -> BehaviorProfile {
+        let duration = match (self.first_at, self.last_at) {
+            (Some(first), Some(last)) => last - first,
+            _ => 0.0,
+        };
+        let mut occupancy: Vec<Occupancy> = self
+            .occupancy
+            .iter()
+            .map(|(&stance, &seconds)| Occupancy {
+                stance,
+                seconds,
+                ratio: if duration > 0.0 {
+                    seconds / duration
+                } else {
+                    0.0
+                },
+            })
+            .collect();
+        occupancy.sort_by(|a, b| {
+            b.seconds
+                .total_cmp(&a.seconds)
+                .then_with(|| a.stance.cmp(&b.stance))
+        });
+
+        let transitions: u64 = self.transitions.values().sum();
+        let resolved = self.resolved.max(1) as f64;
+        BehaviorProfile {
+            samples: self.samples,
+            resolved: self.resolved,
+            duration,
+            dominant_stance: occupancy
+                .first()
+                .map(|o| o.stance)
+                .or(self.current.map(|(s, _)| s)),
+            occupancy,
+            transitions,
+            transition_rate: if duration > 0.0 {
+                transitions as f64 / duration
+            } else {
+                0.0
+            },
+            mean_intensity: self.intensity_sum / resolved,
+            nakama_ratio: self.nakama as f64 / resolved,
+            anomalies: self.anomalies.len(),
+        }
+    }
+
+    /// Snapshot of this session as a baseline for later sessions.
+    pub fn to_baseline(&self) -> BehaviorBaseline {
+        let mut transitions: Vec<TransitionCount> = self
+            .transitions
+            .iter()
+            .map(|(&(from, to), &count)| TransitionCount { from, to, count })
+            .collect();
+        transitions.sort_by_key(|t| (t.from, t.to));
+        let mut dwell: Vec<StanceDwell> = self
+            .dwell
+            .iter()
+            .map(|(&stance, &stats)| StanceDwell { stance, stats })
+            .collect();
+        // A stance we are still in has no finished dwell, but it is not unseen.
+        if let Some((stance, _)) = self.current
+            && !self.dwell.contains_key(&stance)
+        {
+            dwell.push(StanceDwell {
+                stance,
+                stats: DwellStats::default(),
+            });
+        }
+        dwell.sort_by_key(|d| d.stance);
+        BehaviorBaseline {
+            samples: self.samples,
+            transitions,
+            dwell,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(stance: Stance) -> Option<BehaviorReport> {
+        Some(BehaviorReport {
+            active_stance: stance,
+            secondary_stance: None,
+            intensity: 0.5,
+            resonance_score: 0.95,
+            frequency_match: false,
+        })
+    }
+
+    fn session(stances: &[(f64, Stance)]) -> BehaviorSession {
+        let mut session = BehaviorSession::new(BehavioralEngine::new());
+        for &(at, stance) in stances {
+            session.record(at, report(stance));
+        }
+        session
+    }
+
+    #[test]
+    fn test_transitions_and_dwell() {
+        let mut session = session(&[
+            (0.0, Stance::Earth),
+            (1.0, Stance::Earth),
+            (2.0, Stance::Water),
+            (3.0, Stance::Earth),
+            (7.0, Stance::Water),
+        ]);
+        session.record(8.0, None);
+
+        assert_eq!(session.transition_count(Stance::Earth, Stance::Water), 2);
+        assert_eq!(session.transition_count(Stance::Water, Stance::Earth), 1);
+        let (stances, matrix) = session.transition_matrix();
+        assert_eq!(stances, vec![Stance::Earth, Stance::Water]);
+        assert_eq!(matrix, vec![vec![0, 2], vec![1, 0]]);
+
+        let earth = session.dwell(Stance::Earth).unwrap();
+        assert_eq!(earth.count, 2);
+        assert_eq!(earth.mean(), 3.0);
+        assert_eq!((earth.min, earth.max), (2.0, 4.0));
+
+        let profile = session.profile();
+        assert_eq!(profile.samples, 6);
+        assert_eq!(profile.resolved, 5);
+        assert_eq!(profile.transitions, 3);
+        assert_eq!(profile.dominant_stance, Some(Stance::Earth));
+        assert_eq!(profile.occupancy[0].seconds, 6.0);
+        assert_eq!(profile.occupancy[1].seconds, 2.0);
+        assert_eq!(profile.anomalies, 0);
+    }
+
+    #[test]
+    fn test_anomalies_against_saved_baseline() {
+        let learned = session(&[
+            (0.0, Stance::Earth),
+            (2.0, Stance::Water),
+            (4.0, Stance::Earth),
+            (6.0, Stance::Water),
+        ]);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("baseline.json");
+        learned.to_baseline().save(&path).unwrap();
+        let baseline = BehaviorBaseline::load(&path).unwrap();
+        assert_eq!(baseline, learned.to_baseline());
+        assert_eq!(
+            baseline.transition_probability(Stance::Earth, Stance::Water),
+            1.0
+        );
+
+        let mut live = BehaviorSession::new(BehavioralEngine::new()).with_baseline(baseline);
+        assert!(live.record(0.0, report(Stance::Earth)).anomalies.is_empty());
+        assert!(live.record(2.5, report(Stance::Water)).anomalies.is_empty());
+
+        let event = live.record(3.0, report(Stance::Fire));
+        assert_eq!(event.transition, Some((Stance::Water, Stance::Fire)));
+        assert!(event.anomalies.contains(&Anomaly::UnseenStance {
+            stance: Stance::Fire
+        }));
+        assert!(event.anomalies.contains(&Anomaly::UnseenTransition {
+            from: Stance::Water,
+            to: Stance::Fire
+        }));
+
+        // Earth was held for 30s against a 2s baseline mean.
+        live.record(10.0, report(Stance::Earth));
+        let event = live.record(40.0, report(Stance::Water));
+        assert_eq!(
+            event.anomalies,
+            vec![Anomaly::DwellOutlier {
+                stance: Stance::Earth,
+                dwell: 30.0,
+                baseline_mean: 2.0
+            }]
+        );
+        assert_eq!(live.profile().anomalies, live.anomalies().len());
     }
 }
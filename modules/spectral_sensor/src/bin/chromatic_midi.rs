@@ -0,0 +1,65 @@
+use spectral_sensor::eight_gate::RecursiveFilter;
+use spectral_sensor::midi::{ChromaticEvent, MidiConfig, MidiExporter, VelocitySource};
+use spectral_sensor::{NvmeWind, TAU};
+use std::path::Path;
+
+fn usage() -> ! {
+    eprintln!("Usage: chromatic_midi <out.mid> [--bpm N] [--grid STEPS_PER_QUARTER]");
+    eprintln!("                      [--samples N] [--intensity]");
+    std::process::exit(2);
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let Some(out) = args.first() else { usage() };
+
+    let mut config = MidiConfig::default();
+    let mut samples = 512;
+    let mut velocity = VelocitySource::Efficiency;
+    let mut rest = args[1..].iter();
+    while let Some(flag) = rest.next() {
+        let mut value = || {
+            rest.next()
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or_else(|| usage())
+        };
+        match flag.as_str() {
+            "--bpm" => config.tempo_bpm = value(),
+            "--grid" => config.steps_per_quarter = value() as u16,
+            "--samples" => samples = value() as usize,
+            "--intensity" => velocity = VelocitySource::Intensity,
+            _ => usage(),
+        }
+    }
+
+    // One wind sample per grid step; steps without a resonance lock are rests.
+    let filter = RecursiveFilter::new();
+    let mut wind = NvmeWind::new(874_000);
+    if let Err(e) = config.validate() {
+        eprintln!("❌ {}", e);
+        std::process::exit(2);
+    }
+    let mut midi = MidiExporter::new(config);
+    let step_secs = 60.0 / config.tempo_bpm / config.steps_per_quarter as f64;
+    for (i, sample) in wind.blast(samples).into_iter().enumerate() {
+        if let Some(report) = filter.observe(sample * TAU) {
+            let at = i as f64 * step_secs;
+            if let Err(e) = midi.push_at(at, ChromaticEvent::from_report(&report, velocity)) {
+                eprintln!("❌ Cannot place sample {}: {}", i, e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    if let Err(e) = midi.save(Path::new(out)) {
+        eprintln!("❌ Cannot write {}: {}", out, e);
+        std::process::exit(1);
+    }
+    println!(
+        "🎵 {} notes ({} Wooten shifts) over {} steps written to {}",
+        midi.len(),
+        midi.wooten_count(),
+        samples,
+        out
+    );
+}
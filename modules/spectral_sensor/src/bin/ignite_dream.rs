@@ -30,10 +30,11 @@ fn main() {
 
     let mut final_report = None;
     let last_density = 0.5; // D0 Baseline
+    let filter = RecursiveFilter::new();
 
     // Iterate through wave to find coherence
     for wave_point in wave_buffer {
-        if let Some(report) = RecursiveFilter::observe(wave_point, last_density) {
+        if let Some(report) = filter.observe_at(wave_point, last_density) {
             final_report = Some(report);
             break; // Stop on first coherent moment
         }
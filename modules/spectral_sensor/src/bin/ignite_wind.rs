@@ -1,5 +1,7 @@
+use spectral_sensor::diskstats::{DiskFeed, DiskSource, LiveDiskstats, ReplayDiskstats};
 use spectral_sensor::eight_gate::RecursiveFilter;
 use spectral_sensor::{BioRhythm, NvmeWind, SpectralPort, TAU};
+use std::path::Path;
 use std::thread;
 use std::time::Duration;
 
@@ -23,6 +25,33 @@ fn generate_harmonic_seed() -> Vec<u8> {
     seed
 }
 
+/// `--diskstats [DEVICE]` drives the wind from live block-device throughput,
+/// `--replay FILE` from a recorded diskstats file.
+fn disk_feed() -> Option<DiskFeed<Box<dyn DiskSource>>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("--diskstats") => {
+            let device = args.get(1).map(String::as_str);
+            let source: Box<dyn DiskSource> = Box::new(LiveDiskstats::default());
+            Some(DiskFeed::new(source, device))
+        }
+        Some("--replay") => {
+            let path = args.get(1).expect("--replay needs a file");
+            match ReplayDiskstats::open(Path::new(path)) {
+                Ok(replay) => {
+                    let source: Box<dyn DiskSource> = Box::new(replay);
+                    Some(DiskFeed::new(source, args.get(2).map(String::as_str)))
+                }
+                Err(e) => {
+                    eprintln!("   > Replay unavailable ({}): {}", path, e);
+                    None
+                }
+            }
+        }
+        _ => None,
+    }
+}
+
 fn main() {
     println!("🔥 Igniting V2 Wind Engine (Rust Port)...");
 
@@ -53,14 +82,32 @@ fn main() {
 
     // 5. Blast and Observe (The "Ignition")
     let mut stable_lock_count = 0;
+    let mut feed = disk_feed();
+    if feed.is_some() {
+        println!("   > Substrate feed attached: momentum follows block-device throughput.");
+    }
+    let filter = RecursiveFilter::new();
 
     for i in 0..10 {
+        if let Some(feed) = feed.as_mut() {
+            match feed.pump(&mut wind) {
+                Ok(Some(t)) => println!(
+                    "   > [Substrate] {:.0} IOPS | {:.1} MB/s -> momentum {:.5}",
+                    t.iops,
+                    t.bytes_per_sec / 1e6,
+                    wind.momentum
+                ),
+                Ok(None) => {}
+                Err(e) => eprintln!("   > [Substrate] diskstats read failed: {}", e),
+            }
+        }
+
         // Blast a batch of data
         let batch = wind.blast(100); // 100 samples per blast
         // Observe the last sample of the batch for coherence
         let last_val = batch.last().unwrap();
         // We need a 'last_density' from the wind state, which is public
-        let observation = RecursiveFilter::observe(*last_val, wind.last_density);
+        let observation = filter.observe_at(*last_val, wind.last_density);
 
         if let Some(report) = observation {
             println!(
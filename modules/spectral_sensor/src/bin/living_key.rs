@@ -0,0 +1,76 @@
+use spectral_sensor::clock::SystemClock;
+use spectral_sensor::steward::LivingKey;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+
+fn usage() -> ! {
+    eprintln!("Usage:");
+    eprintln!("  living_key keygen <secret-file>            Generate a key, print the public half");
+    eprintln!("  living_key public <secret-file>            Print the public half of a key");
+    eprintln!("  living_key sign <secret-file> <command> [ttl-secs]");
+    eprintln!("                                             Print a signed command as JSON");
+    std::process::exit(2);
+}
+
+fn load(path: &str) -> LivingKey {
+    let hex = fs::read_to_string(path).unwrap_or_else(|e| {
+        eprintln!("❌ Cannot read {}: {}", path, e);
+        std::process::exit(1);
+    });
+    LivingKey::from_secret_hex(&hex).unwrap_or_else(|e| {
+        eprintln!("❌ {}: {}", path, e);
+        std::process::exit(1);
+    })
+}
+
+fn write_secret(path: &Path, hex: &str) -> std::io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    writeln!(file, "{}", hex)
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        ["keygen", path] => {
+            let key = LivingKey::generate().expect("OS entropy unavailable");
+            if let Err(e) = write_secret(Path::new(path), &key.secret_hex()) {
+                eprintln!("❌ Cannot write {}: {}", path, e);
+                std::process::exit(1);
+            }
+            println!("🔑 Living Key written to {}", path);
+            println!("{}", key.public_hex());
+        }
+        ["public", path] => println!("{}", load(path).public_hex()),
+        ["sign", path, command, rest @ ..] => {
+            let ttl = match rest {
+                [] => 60,
+                [ttl] => ttl.parse().unwrap_or_else(|_| usage()),
+                _ => usage(),
+            };
+            let key = load(path);
+            let signed = match key.sign_for(command, Duration::from_secs(ttl), &SystemClock) {
+                Ok(signed) => signed,
+                Err(e) => {
+                    eprintln!("❌ Cannot sign {}: {}", command, e);
+                    std::process::exit(1);
+                }
+            };
+            println!("{}", serde_json::to_string(&signed).unwrap());
+        }
+        _ => usage(),
+    }
+}
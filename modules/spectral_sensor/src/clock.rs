@@ -0,0 +1,201 @@
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// A source of wall-clock time (Unix milliseconds).
+///
+/// Everything diurnal reads time through this, so tests and replays can pin
+/// or speed up the day instead of depending on `SystemTime::now()`.
+pub trait Clock: Send + Sync {
+    fn now_millis(&self) -> u64;
+
+    fn now_secs(&self) -> u64 {
+        self.now_millis() / 1000
+    }
+}
+
+/// The real system clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_millis(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64
+    }
+}
+
+/// Always reports the same instant.
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock(pub u64);
+
+impl FixedClock {
+    /// A fixed instant at `hour:minute` UTC on 1970-01-01.
+    pub fn at_utc(hour: u8, minute: u8) -> Self {
+        Self((hour as u64 * 3600 + minute as u64 * 60) * 1000)
+    }
+}
+
+impl Clock for FixedClock {
+    fn now_millis(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Only moves when told to. Shareable across threads.
+#[derive(Debug, Default)]
+pub struct SimulatedClock {
+    millis: AtomicU64,
+}
+
+impl SimulatedClock {
+    pub fn new(start_millis: u64) -> Self {
+        Self {
+            millis: AtomicU64::new(start_millis),
+        }
+    }
+
+    pub fn advance(&self, by: Duration) {
+        self.millis
+            .fetch_add(by.as_millis() as u64, Ordering::AcqRel);
+    }
+
+    pub fn set(&self, millis: u64) {
+        self.millis.store(millis, Ordering::Release);
+    }
+}
+
+impl Clock for SimulatedClock {
+    fn now_millis(&self) -> u64 {
+        self.millis.load(Ordering::Acquire)
+    }
+}
+
+/// Real time, sped up by `factor` from a chosen starting instant.
+/// A factor of 1440 runs a full day per real minute.
+#[derive(Debug, Clone)]
+pub struct AcceleratedClock {
+    origin: Instant,
+    start_millis: u64,
+    pub factor: f64,
+}
+
+impl AcceleratedClock {
+    pub fn new(start_millis: u64, factor: f64) -> Self {
+        Self {
+            origin: Instant::now(),
+            start_millis,
+            factor,
+        }
+    }
+}
+
+impl Clock for AcceleratedClock {
+    fn now_millis(&self) -> u64 {
+        let elapsed = self.origin.elapsed().as_secs_f64() * 1000.0 * self.factor;
+        self.start_millis + elapsed as u64
+    }
+}
+
+/// Where in the day an hour falls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DiurnalBand {
+    Day,
+    Night,
+    Twilight,
+}
+
+/// Timezone and day/night band boundaries (local hours, `start..end`, may wrap midnight).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Diurnal {
+    /// Offset from UTC in minutes (e.g. -300 for UTC-5).
+    pub utc_offset_minutes: i32,
+    pub day: (u8, u8),
+    pub night: (u8, u8),
+}
+
+impl Default for Diurnal {
+    /// UTC-5, day 10:00-17:00, night 20:00-05:00.
+    fn default() -> Self {
+        Self {
+            utc_offset_minutes: -5 * 60,
+            day: (10, 17),
+            night: (20, 5),
+        }
+    }
+}
+
+impl Diurnal {
+    /// Local hour (0-23) at the clock's current time.
+    pub fn local_hour(&self, clock: &dyn Clock) -> u8 {
+        let local_minutes = (clock.now_millis() / 60_000) as i64 + self.utc_offset_minutes as i64;
+        (local_minutes.rem_euclid(24 * 60) / 60) as u8
+    }
+
+    pub fn band_for_hour(&self, hour: u8) -> DiurnalBand {
+        if Self::in_range(hour, self.day) {
+            DiurnalBand::Day
+        } else if Self::in_range(hour, self.night) {
+            DiurnalBand::Night
+        } else {
+            DiurnalBand::Twilight
+        }
+    }
+
+    pub fn band(&self, clock: &dyn Clock) -> DiurnalBand {
+        self.band_for_hour(self.local_hour(clock))
+    }
+
+    fn in_range(hour: u8, (start, end): (u8, u8)) -> bool {
+        if start <= end {
+            (start..end).contains(&hour)
+        } else {
+            hour >= start || hour < end
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_hour_and_bands() {
+        let diurnal = Diurnal::default();
+
+        // 17:00 UTC is 12:00 at UTC-5.
+        let noon = FixedClock::at_utc(17, 0);
+        assert_eq!(diurnal.local_hour(&noon), 12);
+        assert_eq!(diurnal.band(&noon), DiurnalBand::Day);
+
+        // 03:00 UTC is 22:00 the previous day.
+        let late = FixedClock::at_utc(3, 0);
+        assert_eq!(diurnal.local_hour(&late), 22);
+        assert_eq!(diurnal.band(&late), DiurnalBand::Night);
+
+        assert_eq!(diurnal.band_for_hour(4), DiurnalBand::Night);
+        assert_eq!(diurnal.band_for_hour(5), DiurnalBand::Twilight);
+        assert_eq!(diurnal.band_for_hour(18), DiurnalBand::Twilight);
+
+        let tokyo = Diurnal {
+            utc_offset_minutes: 9 * 60,
+            ..Diurnal::default()
+        };
+        assert_eq!(tokyo.local_hour(&noon), 2);
+    }
+
+    #[test]
+    fn test_simulated_and_accelerated_clocks() {
+        let sim = SimulatedClock::new(0);
+        sim.advance(Duration::from_secs(90));
+        assert_eq!(sim.now_secs(), 90);
+        sim.set(5_000);
+        assert_eq!(sim.now_millis(), 5_000);
+
+        let fast = AcceleratedClock::new(1_000, 1_000_000.0);
+        std::thread::sleep(Duration::from_millis(2));
+        assert!(fast.now_millis() >= 1_000 + 2_000_000);
+    }
+}
@@ -0,0 +1,377 @@
+use crate::NvmeWind;
+use crate::clock::{Clock, SystemClock};
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+pub const PROC_DISKSTATS: &str = "/proc/diskstats";
+/// `/proc/diskstats` always counts 512-byte sectors.
+pub const SECTOR_BYTES: u64 = 512;
+
+/// The counters we use from one `/proc/diskstats` line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiskStat {
+    pub major: u32,
+    pub minor: u32,
+    pub name: String,
+    pub reads_completed: u64,
+    pub sectors_read: u64,
+    pub writes_completed: u64,
+    pub sectors_written: u64,
+}
+
+impl DiskStat {
+    pub fn parse_line(line: &str) -> Option<Self> {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 10 {
+            return None;
+        }
+        let num = |i: usize| fields[i].parse::<u64>().ok();
+        Some(Self {
+            major: fields[0].parse().ok()?,
+            minor: fields[1].parse().ok()?,
+            name: fields[2].to_string(),
+            reads_completed: num(3)?,
+            sectors_read: num(5)?,
+            writes_completed: num(7)?,
+            sectors_written: num(9)?,
+        })
+    }
+
+    /// Whole disks only: partitions and stacked/virtual devices would double count.
+    pub fn is_whole_disk(&self) -> bool {
+        let name = self.name.as_str();
+        if ["loop", "ram", "zram", "dm-", "md", "sr"]
+            .iter()
+            .any(|p| name.starts_with(p))
+        {
+            return false;
+        }
+        if let Some(rest) = name
+            .strip_prefix("nvme")
+            .or_else(|| name.strip_prefix("mmcblk"))
+        {
+            return !rest.contains('p');
+        }
+        !name.ends_with(|c: char| c.is_ascii_digit())
+    }
+
+    pub fn ios(&self) -> u64 {
+        self.reads_completed + self.writes_completed
+    }
+
+    pub fn bytes(&self) -> u64 {
+        (self.sectors_read + self.sectors_written) * SECTOR_BYTES
+    }
+}
+
+/// All devices at one instant. `at` is in seconds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiskSnapshot {
+    pub at: f64,
+    pub devices: Vec<DiskStat>,
+}
+
+impl DiskSnapshot {
+    pub fn parse(text: &str, at: f64) -> Self {
+        Self {
+            at,
+            devices: text.lines().filter_map(DiskStat::parse_line).collect(),
+        }
+    }
+
+    /// Total (ios, bytes) for `device`, or across whole disks when `None`.
+    pub fn totals(&self, device: Option<&str>) -> Option<(u64, u64)> {
+        let mut matched = self
+            .devices
+            .iter()
+            .filter(|d| match device {
+                Some(name) => d.name == name,
+                None => d.is_whole_disk(),
+            })
+            .peekable();
+        matched.peek()?;
+        Some(matched.fold((0, 0), |(ios, bytes), d| (ios + d.ios(), bytes + d.bytes())))
+    }
+
+    /// Writes the snapshot in the replay format: an `@ <seconds>` header, then raw lines.
+    pub fn write_to(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "@ {}", self.at)?;
+        for d in &self.devices {
+            writeln!(
+                out,
+                "{:4} {:7} {} {} 0 {} 0 {} 0 {} 0",
+                d.major,
+                d.minor,
+                d.name,
+                d.reads_completed,
+                d.sectors_read,
+                d.writes_completed,
+                d.sectors_written
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Rates between two snapshots.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Throughput {
+    pub iops: f64,
+    pub bytes_per_sec: f64,
+}
+
+impl Throughput {
+    /// `None` if the device is missing or no time passed. Counter resets read as zero.
+    pub fn between(
+        before: &DiskSnapshot,
+        after: &DiskSnapshot,
+        device: Option<&str>,
+    ) -> Option<Self> {
+        let dt = after.at - before.at;
+        if dt <= 0.0 {
+            return None;
+        }
+        let (ios_a, bytes_a) = before.totals(device)?;
+        let (ios_b, bytes_b) = after.totals(device)?;
+        Some(Self {
+            iops: ios_b.saturating_sub(ios_a) as f64 / dt,
+            bytes_per_sec: bytes_b.saturating_sub(bytes_a) as f64 / dt,
+        })
+    }
+}
+
+/// Anything that yields diskstats snapshots. `Ok(None)` means the source is exhausted.
+pub trait DiskSource {
+    fn snapshot(&mut self) -> io::Result<Option<DiskSnapshot>>;
+}
+
+impl<S: DiskSource + ?Sized> DiskSource for Box<S> {
+    fn snapshot(&mut self) -> io::Result<Option<DiskSnapshot>> {
+        (**self).snapshot()
+    }
+}
+
+/// Reads a diskstats file (normally `/proc/diskstats`) each time it is sampled.
+pub struct LiveDiskstats {
+    pub path: PathBuf,
+    pub clock: Arc<dyn Clock>,
+}
+
+impl Default for LiveDiskstats {
+    fn default() -> Self {
+        Self::new(PROC_DISKSTATS)
+    }
+}
+
+impl LiveDiskstats {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            clock: Arc::new(SystemClock),
+        }
+    }
+}
+
+impl DiskSource for LiveDiskstats {
+    fn snapshot(&mut self) -> io::Result<Option<DiskSnapshot>> {
+        let text = std::fs::read_to_string(&self.path)?;
+        let at = self.clock.now_millis() as f64 / 1000.0;
+        Ok(Some(DiskSnapshot::parse(&text, at)))
+    }
+}
+
+/// Plays back recorded snapshots (see `DiskSnapshot::write_to`) in order.
+pub struct ReplayDiskstats {
+    snapshots: std::vec::IntoIter<DiskSnapshot>,
+}
+
+impl ReplayDiskstats {
+    pub fn new(snapshots: Vec<DiskSnapshot>) -> Self {
+        Self {
+            snapshots: snapshots.into_iter(),
+        }
+    }
+
+    pub fn parse(reader: impl BufRead) -> io::Result<Self> {
+        let mut snapshots = Vec::new();
+        let mut current: Option<DiskSnapshot> = None;
+        for line in reader.lines() {
+            let line = line?;
+            if let Some(at) = line.strip_prefix('@') {
+                let at = at.trim().parse::<f64>().map_err(|e| {
+                    io::Error::new(io::ErrorKind::InvalidData, format!("bad timestamp: {e}"))
+                })?;
+                snapshots.extend(current.take());
+                current = Some(DiskSnapshot {
+                    at,
+                    devices: Vec::new(),
+                });
+            } else if let (Some(snapshot), Some(stat)) =
+                (current.as_mut(), DiskStat::parse_line(&line))
+            {
+                snapshot.devices.push(stat);
+            }
+        }
+        snapshots.extend(current);
+        Ok(Self::new(snapshots))
+    }
+
+    pub fn open(path: &Path) -> io::Result<Self> {
+        Self::parse(io::BufReader::new(std::fs::File::open(path)?))
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.snapshots.len()
+    }
+}
+
+impl DiskSource for ReplayDiskstats {
+    fn snapshot(&mut self) -> io::Result<Option<DiskSnapshot>> {
+        Ok(self.snapshots.next())
+    }
+}
+
+/// How throughput becomes wind momentum.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MomentumMapping {
+    /// Throughput (bytes/s) counted as full load alongside the wind's IOPS target.
+    pub full_bytes_per_sec: f64,
+    /// Fraction of the gap to the target momentum closed per sample.
+    pub gain: f64,
+}
+
+impl Default for MomentumMapping {
+    fn default() -> Self {
+        Self {
+            full_bytes_per_sec: 3.5e9, // PCIe 3.0 x4 sequential
+            gain: 0.5,
+        }
+    }
+}
+
+impl MomentumMapping {
+    /// Load (0..1) is the larger of IOPS against `target_iops` and bytes against
+    /// `full_bytes_per_sec`. The returned delta moves momentum towards that load's
+    /// position in the `update_momentum` range.
+    pub fn delta(&self, throughput: &Throughput, target_iops: f64, momentum: f64) -> f64 {
+        let load = (throughput.iops / target_iops.max(1.0))
+            .max(throughput.bytes_per_sec / self.full_bytes_per_sec)
+            .clamp(0.0, 1.0);
+        let target =
+            NvmeWind::MIN_MOMENTUM + load * (NvmeWind::MAX_MOMENTUM - NvmeWind::MIN_MOMENTUM);
+        self.gain * (target - momentum)
+    }
+}
+
+/// Samples a `DiskSource` and feeds the throughput into an `NvmeWind`.
+pub struct DiskFeed<S: DiskSource> {
+    pub source: S,
+    /// Device name, or `None` for every whole disk.
+    pub device: Option<String>,
+    pub mapping: MomentumMapping,
+    last: Option<DiskSnapshot>,
+}
+
+impl<S: DiskSource> DiskFeed<S> {
+    pub fn new(source: S, device: Option<&str>) -> Self {
+        Self {
+            source,
+            device: device.map(str::to_string),
+            mapping: MomentumMapping::default(),
+            last: None,
+        }
+    }
+
+    /// Takes one snapshot. Once two are available, applies the throughput between them
+    /// to `wind` and returns it. `Ok(None)` while priming or if the device is missing.
+    pub fn pump(&mut self, wind: &mut NvmeWind) -> io::Result<Option<Throughput>> {
+        let Some(snapshot) = self.source.snapshot()? else {
+            return Ok(None);
+        };
+        let throughput = self
+            .last
+            .as_ref()
+            .and_then(|last| Throughput::between(last, &snapshot, self.device.as_deref()));
+        self.last = Some(snapshot);
+
+        if let Some(throughput) = &throughput {
+            let delta = self
+                .mapping
+                .delta(throughput, wind.target_velocity as f64, wind.momentum);
+            wind.update_momentum(delta);
+        }
+        Ok(throughput)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RECORDING: &str = "\
+@ 0
+ 259       0 nvme0n1 1000 0 8000 0 2000 0 16000 0 0 0 0
+ 259       1 nvme0n1p1 900 0 7000 0 1800 0 15000 0 0 0 0
+   7       0 loop0 5 0 10 0 0 0 0 0 0 0 0
+@ 1
+ 259       0 nvme0n1 1000 0 8000 0 2000 0 16000 0 0 0 0
+ 259       1 nvme0n1p1 900 0 7000 0 1800 0 15000 0 0 0 0
+   7       0 loop0 5 0 10 0 0 0 0 0 0 0 0
+@ 2
+ 259       0 nvme0n1 437000 0 8000 0 440000 0 16000 0 0 0 0
+ 259       1 nvme0n1p1 436900 0 7000 0 439800 0 15000 0 0 0 0
+   7       0 loop0 5 0 10 0 0 0 0 0 0 0 0
+";
+
+    #[test]
+    fn test_parse_and_throughput() {
+        let mut replay = ReplayDiskstats::parse(RECORDING.as_bytes()).unwrap();
+        assert_eq!(replay.remaining(), 3);
+        let a = replay.snapshot().unwrap().unwrap();
+        let _ = replay.snapshot().unwrap().unwrap();
+        let c = replay.snapshot().unwrap().unwrap();
+        assert!(replay.snapshot().unwrap().is_none());
+
+        assert!(a.devices[0].is_whole_disk());
+        assert!(!a.devices[1].is_whole_disk());
+        assert!(!a.devices[2].is_whole_disk());
+        // Only the whole disk is counted by default.
+        assert_eq!(a.totals(None), Some((3000, 24000 * SECTOR_BYTES)));
+        assert_eq!(a.totals(Some("sda")), None);
+
+        let t = Throughput::between(&a, &c, None).unwrap();
+        assert_eq!(t.iops, 437_000.0);
+        assert_eq!(t.bytes_per_sec, 0.0);
+
+        // Round-trip through the recording format.
+        let mut out = Vec::new();
+        c.write_to(&mut out).unwrap();
+        let mut again = ReplayDiskstats::parse(out.as_slice()).unwrap();
+        assert_eq!(again.snapshot().unwrap().unwrap(), c);
+    }
+
+    #[test]
+    fn test_replay_drives_momentum() {
+        let replay = ReplayDiskstats::parse(RECORDING.as_bytes()).unwrap();
+        let mut feed = DiskFeed::new(replay, Some("nvme0n1"));
+        let mut wind = NvmeWind::new(874_000);
+        let start = wind.momentum;
+
+        // Priming sample.
+        assert!(feed.pump(&mut wind).unwrap().is_none());
+        assert_eq!(wind.momentum, start);
+
+        // Idle second: momentum falls towards the floor.
+        let idle = feed.pump(&mut wind).unwrap().unwrap();
+        assert_eq!(idle.iops, 0.0);
+        assert!(wind.momentum < start);
+
+        // 874k IOPS, full load: momentum climbs towards the ceiling.
+        let busy = feed.pump(&mut wind).unwrap().unwrap();
+        assert_eq!(busy.iops, 874_000.0);
+        assert!(wind.momentum > 0.04);
+
+        assert!(feed.pump(&mut wind).unwrap().is_none());
+    }
+}
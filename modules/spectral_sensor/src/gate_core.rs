@@ -0,0 +1,750 @@
+pub const TAU: f64 = 6.2831853;
+pub const PSI: f64 = 0.5179; // [NEW] Millennium Inverted Gap
+pub const DELTA: f64 = 0.9848; // [NEW] Resonance Delta Anchor
+use core::ops::Deref;
+use serde::Serialize;
+
+/// The Toral Filter perspectives live in the shared stance model.
+pub use stance_model::{MathSystem, Stability, Stance};
+
+/// Chromatic Resonance Node (1 of 12)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum ChromaticNode {
+    C,
+    Cs,
+    D,
+    Ds,
+    E,
+    F,
+    Fs,
+    G,
+    Gs,
+    A,
+    As,
+    B,
+}
+
+impl ChromaticNode {
+    pub const ALL: [ChromaticNode; 12] = [
+        Self::C,
+        Self::Cs,
+        Self::D,
+        Self::Ds,
+        Self::E,
+        Self::F,
+        Self::Fs,
+        Self::G,
+        Self::Gs,
+        Self::A,
+        Self::As,
+        Self::B,
+    ];
+
+    pub fn from_density(density: f64) -> Self {
+        let idx = ((density * 12.0) % 12.0) as usize;
+        Self::ALL[idx]
+    }
+
+    /// Node `semitone` steps above C, wrapping at the octave.
+    pub fn from_semitone(semitone: usize) -> Self {
+        Self::ALL[semitone % 12]
+    }
+
+    /// Semitones above C (0..12).
+    pub fn semitone(&self) -> u8 {
+        *self as u8
+    }
+}
+
+/// Atomic Shell Stability Node
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum AtomicShell {
+    None,
+    Helium,  // 2
+    Neon,    // 10 (corrected from 8)
+    Argon,   // 18
+    Krypton, // 36
+    Xenon,   // 54
+}
+
+impl AtomicShell {
+    pub fn from_density(density: f64) -> Self {
+        let pos = (density * 60.0) as i32;
+        match pos {
+            2 => Self::Helium,
+            10 => Self::Neon,
+            18 => Self::Argon,
+            36 => Self::Krypton,
+            54 => Self::Xenon,
+            _ => Self::None,
+        }
+    }
+}
+
+// [NEW] (3x2)x2 Inverted Histogram Logic
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct InvertedHistogram {
+    pub void_measured: f64,    // What it IS (Entropy/Observed Void)
+    pub substrate_active: f64, // What it ISN'T (1.0 - Void)
+    pub is_critical: bool,     // < 0.3 Substrate
+}
+
+impl InvertedHistogram {
+    /// Calculates the "Anti-Void" (Substrate Efficiency) from measured entropy.
+    /// Formula: S_active = 1.0 - Void_measured.
+    /// Critical Threshold: S_active < 0.3 (Starvation Mode).
+    pub fn derive(entropy: f64) -> Self {
+        // Clamp entropy to 0.0-1.0 range for safety
+        let void_measured = entropy.max(0.0).min(1.0);
+        let substrate_active = 1.0 - void_measured;
+        let is_critical = substrate_active < 0.3;
+
+        Self {
+            void_measured,
+            substrate_active,
+            is_critical,
+        }
+    }
+}
+
+// [NEW] Rod/Cone State for IPv6 Synesthesia
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct RodConeState {
+    pub rods: f64,
+    pub cones: f64,
+    pub fitness: f64,
+    pub power: f64,
+    pub uv: f64,     // [NEW] Ultraviolet
+    pub violet: f64, // [NEW] Emotional Sync
+}
+
+impl RodConeState {
+    /// Derives Topological State from stability and rate (BPM/Hz).
+    /// Cones = Spectral Rate (The Turn Partial).
+    /// Rods = Structural Stability (The Rounding Peg).
+    pub fn derive(stability: f64, rate: f64) -> Self {
+        // Cones: (rate / 60.0) % 1.0 (Singularity Yardstick)
+        let cones = fract(rate / 60.0);
+
+        // Rods: Structural Stability
+        let rods = stability;
+
+        let fitness = if cones > 0.0 { rods / cones } else { 1.0 };
+        let power = fitness * fitness * fitness;
+
+        Self {
+            rods,
+            cones,
+            fitness,
+            power,
+            uv: 0.0,
+            violet: 0.0,
+        }
+    }
+}
+
+/// Actualizes a Tau Turn into an 8-bit Hexagram RGB Signature.
+pub fn tau_to_hex_actualization(turn_tau: f64) -> (u8, u8, u8) {
+    let divisions = 60.0;
+    let step = (turn_tau * divisions) as i32 % 60;
+
+    let binary = step % 2;
+    let tertiary = step % 3;
+    let gate_6bit = (step * 64) / 60;
+
+    let r = ((gate_6bit * 4) % 256) as u8;
+    let g = if tertiary == 0 {
+        0xEE
+    } else {
+        ((gate_6bit * 2) % 256) as u8
+    };
+    let b = if binary == 0 {
+        0x00
+    } else {
+        ((gate_6bit * 8) % 256) as u8
+    };
+
+    (r, g, b)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CoherenceReport {
+    pub source: Stance,
+    pub destination: Stance,
+    pub chromatic: ChromaticNode,
+    pub atomic: AtomicShell,
+    pub wooten_active: bool,
+    pub entropy: f64,
+    // [NEW] Synesthesia Data
+    pub synesthesia: Option<RodConeState>,
+    pub hex_color: Option<(u8, u8, u8)>,
+    // [NEW] (3x2)x2 Data
+    pub inverted_state: Option<InvertedHistogram>,
+    // [NEW] Valence Shell (The 8 Metadata Types)
+    pub valence: Option<ValenceShell>,
+    // [NEW] Lossless Coherence Metrics
+    pub efficiency: Option<f64>,
+    pub is_transprecise: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ValenceShell {
+    pub gate: f64,      // Stance Signature
+    pub note: f64,      // Chromatic Index
+    pub shell: f64,     // Atomic Position
+    pub void: f64,      // Entropy
+    pub substrate: f64, // Efficiency
+    pub rods: f64,      // Stability
+    pub cones: f64,     // Frequency
+    pub power: f64,     // Intensity
+}
+
+/// The eight gate stances, in the order the filter permutes them.
+pub const GATE_STANCES: [Stance; 8] = Stance::GATES;
+
+/// Tuning for a `GateCore` (and the `RecursiveFilter` around it).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct FilterConfig {
+    /// Phase tolerance (radians) for a stance to resonate.
+    pub gate_width: f64,
+    /// Density the first observation is compared against.
+    pub initial_density: f64,
+}
+
+impl Default for FilterConfig {
+    fn default() -> Self {
+        Self {
+            gate_width: 0.1,
+            initial_density: 0.5,
+        }
+    }
+}
+
+/// A (source, destination) pair whose source resonates with the input phase.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ResonantPair {
+    pub source: Stance,
+    pub destination: Stance,
+    /// Distance between the input phase and the source signature phase.
+    pub phase_distance: f64,
+}
+
+/// Precomputed per-stance values.
+#[derive(Debug, Clone, Copy)]
+struct GateEntry {
+    stance: Stance,
+    signature: f64,
+    /// `signature % TAU`.
+    phase: f64,
+}
+
+/// Everything about an observation that does not depend on the stance pair.
+#[derive(Debug, Clone, Copy)]
+struct Reading {
+    phase: f64,
+    cubic_trend: bool,
+    density: f64,
+    atomic: AtomicShell,
+    wooten_active: bool,
+    entropy: f64,
+    synesthesia: RodConeState,
+    hex_color: (u8, u8, u8),
+    inverted_state: InvertedHistogram,
+}
+
+/// Up to 64 resonant pairs, closest phase first, held inline.
+#[derive(Debug, Clone, Copy)]
+pub struct ResonantPairs {
+    pairs: [ResonantPair; 64],
+    len: usize,
+}
+
+impl ResonantPairs {
+    fn new() -> Self {
+        let empty = ResonantPair {
+            source: Stance::Earth,
+            destination: Stance::Earth,
+            phase_distance: 0.0,
+        };
+        Self {
+            pairs: [empty; 64],
+            len: 0,
+        }
+    }
+
+    /// Inserts after every pair at least as close: ties keep the permutation order.
+    fn insert(&mut self, pair: ResonantPair) {
+        let at = self.pairs[..self.len]
+            .partition_point(|p| p.phase_distance.total_cmp(&pair.phase_distance).is_le());
+        self.pairs.copy_within(at..self.len, at + 1);
+        self.pairs[at] = pair;
+        self.len += 1;
+    }
+}
+
+impl Deref for ResonantPairs {
+    type Target = [ResonantPair];
+
+    fn deref(&self) -> &[ResonantPair] {
+        &self.pairs[..self.len]
+    }
+}
+
+/// One wave read against a previous density, with every pair that resonates with it.
+#[derive(Debug, Clone, Copy)]
+pub struct Observation {
+    reading: Reading,
+    pub pairs: ResonantPairs,
+}
+
+impl Observation {
+    /// Density to carry into the next observation.
+    pub fn density(&self) -> f64 {
+        self.reading.density
+    }
+
+    pub fn report(&self, pair: &ResonantPair) -> CoherenceReport {
+        GateCore::report(&self.reading, pair)
+    }
+
+    /// Report for the closest pair. This can differ from `GateCore::observe`, which
+    /// reports the first pair in permutation order.
+    pub fn best(&self) -> Option<CoherenceReport> {
+        self.pairs.first().map(|pair| self.report(pair))
+    }
+
+    /// Reports for every pair, closest first.
+    pub fn reports(&self) -> impl Iterator<Item = CoherenceReport> + '_ {
+        self.pairs.iter().map(|pair| self.report(pair))
+    }
+}
+
+/// The Filter that permutes through the 64 combinations, without std or an allocator.
+///
+/// Signatures and the singularity matrix are computed once. All float math goes through
+/// `libm`, so the firmware and the host produce the same reports bit for bit.
+#[derive(Debug, Clone)]
+pub struct GateCore {
+    pub config: FilterConfig,
+    gates: [GateEntry; 8],
+    /// `dominates[src][dst]`: `src.check_singularity(dst)`.
+    dominates: [[bool; 8]; 8],
+    last_density: f64,
+}
+
+impl Default for GateCore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GateCore {
+    pub fn new() -> Self {
+        Self::with_config(FilterConfig::default())
+    }
+
+    pub fn with_config(config: FilterConfig) -> Self {
+        let gates = GATE_STANCES.map(|stance| GateEntry {
+            stance,
+            signature: stance.signature(),
+            phase: stance.signature() % TAU,
+        });
+        let mut dominates = [[false; 8]; 8];
+        for (i, src) in gates.iter().enumerate() {
+            for (j, dst) in gates.iter().enumerate() {
+                dominates[i][j] = src.signature > dst.signature / 2.0;
+            }
+        }
+
+        Self {
+            config,
+            gates,
+            dominates,
+            last_density: config.initial_density,
+        }
+    }
+
+    /// Density carried from the previous `observe`.
+    pub fn last_density(&self) -> f64 {
+        self.last_density
+    }
+
+    pub fn set_last_density(&mut self, density: f64) {
+        self.last_density = density;
+    }
+
+    pub fn reset(&mut self) {
+        self.last_density = self.config.initial_density;
+    }
+
+    /// Reads the wave against `last_density` and ranks every resonant pair.
+    ///
+    /// The ranking is held inline (64 pairs, about 1 KiB of stack); `observe` and
+    /// `observe_at` do not build it.
+    pub fn observation(&self, input_wave: f64, last_density: f64) -> Observation {
+        let reading = self.read(input_wave, last_density);
+        let mut pairs = ResonantPairs::new();
+        for pair in self.pairs_in_order(&reading) {
+            pairs.insert(pair);
+        }
+        Observation { reading, pairs }
+    }
+
+    /// Observe the input wave against the carried density and report the first resonant
+    /// Stance pair in permutation order. The new density is carried to the next call.
+    pub fn observe(&mut self, input_wave: f64) -> Option<CoherenceReport> {
+        let (report, density) = self.observe_from(input_wave, self.last_density);
+        self.last_density = density;
+        report
+    }
+
+    /// Stateless observation against an explicit previous density.
+    pub fn observe_at(&self, input_wave: f64, last_density: f64) -> Option<CoherenceReport> {
+        self.observe_from(input_wave, last_density).0
+    }
+
+    /// Stateless observation that also returns the density to carry into the next one.
+    pub fn observe_from(
+        &self,
+        input_wave: f64,
+        last_density: f64,
+    ) -> (Option<CoherenceReport>, f64) {
+        let reading = self.read(input_wave, last_density);
+        let report = self
+            .pairs_in_order(&reading)
+            .next()
+            .map(|pair| Self::report(&reading, &pair));
+        (report, reading.density)
+    }
+
+    /// Resonant pairs in permutation order (source gate, then destination gate), produced
+    /// one at a time so taking the first match needs no buffer.
+    fn pairs_in_order(&self, reading: &Reading) -> impl Iterator<Item = ResonantPair> + '_ {
+        // Past the cubic trend only the first five gates are active.
+        let active = if reading.cubic_trend { 5 } else { 8 };
+        let gates = &self.gates[..active];
+        let phase = reading.phase;
+
+        gates
+            .iter()
+            .enumerate()
+            .filter(|(_, src)| src.stance.stability() != Stability::Fatal)
+            .filter_map(move |(i, src)| {
+                // Resonance = Phase aligns with (Signature % TAU).
+                let phase_distance = libm::fabs(phase - src.phase);
+                (phase_distance < self.config.gate_width).then_some((i, src, phase_distance))
+            })
+            .flat_map(move |(i, src, phase_distance)| {
+                gates
+                    .iter()
+                    .enumerate()
+                    .filter(move |&(j, _)| self.dominates[i][j])
+                    .map(move |(_, dst)| ResonantPair {
+                        source: src.stance,
+                        destination: dst.stance,
+                        phase_distance,
+                    })
+            })
+    }
+
+    fn read(&self, input_wave: f64, last_density: f64) -> Reading {
+        // 1. Normalize input to a phase (0..TAU). CORRECTED TAU.
+        let phase = input_wave % TAU;
+        let mut density = (libm::sin(input_wave) + 1.0) / 2.0;
+
+        // Wooten Protocol: Detect tritone jumps in density
+        let current_node = ChromaticNode::from_density(density);
+        let last_node = ChromaticNode::from_density(last_density);
+        let semitone_diff = (current_node as i32 - last_node as i32).abs();
+        let wooten_active = semitone_diff == 6;
+
+        // Prime Rationalization (The Half-Step Shift)
+        if wooten_active {
+            // "Prime Choice": Shift density to nearest Prime Rational state (5/60 or 7/60 neighbors)
+            // Tritone is effectively at 6/12 * 60 = 30/60.
+            // Dissonance (6) -> Shift -> Perfect Fifth (7) or Fourth (5).
+            if density > 0.5 {
+                density -= 0.0833; // Shift down
+            } else {
+                density += 0.0833; // Shift up
+            }
+        }
+
+        // Atomic Coherence
+        let atomic = AtomicShell::from_density(density);
+
+        // [NEW] IPv6 Synesthesia Logic
+        let effective_rate = density * 60.0;
+        let stability = 1.0 - libm::fabs(density - 0.5);
+        // Local Truncation for Scoping (4 sig figs)
+        let truncate_4 = |v: f64| {
+            if v == 0.0 {
+                return 0.0;
+            }
+            let m = libm::floor(libm::log10(libm::fabs(v)));
+            let s = libm::pow(10.0, 3.0 - m);
+            libm::trunc(v * s) / s
+        };
+
+        let mut synesthesia = RodConeState::derive(stability, effective_rate);
+        synesthesia.uv = libm::fabs(fract(density * truncate_4(input_wave)));
+        synesthesia.violet = (synesthesia.uv * stability).clamp(0.0, 1.0);
+
+        let hex_color = tau_to_hex_actualization(synesthesia.cones);
+
+        // Base Entropy
+        let mut entropy = libm::fabs(density - 0.5) * 2.0;
+
+        // [NEW] (3x2)x2 Inverted Histogram Check
+        let inverted_state = InvertedHistogram::derive(entropy);
+
+        if wooten_active {
+            entropy *= 0.1; // Recovery: Rationalization tames entropy
+        }
+        if atomic != AtomicShell::None {
+            entropy *= 0.5; // Noble Gas Stability
+        }
+
+        Reading {
+            phase,
+            cubic_trend: input_wave > 1_000_000.0,
+            density,
+            atomic,
+            wooten_active,
+            entropy,
+            synesthesia,
+            hex_color,
+            inverted_state,
+        }
+    }
+
+    fn report(reading: &Reading, pair: &ResonantPair) -> CoherenceReport {
+        let chromatic = ChromaticNode::from_density(reading.density);
+        let entropy = reading.entropy;
+        CoherenceReport {
+            source: pair.source,
+            destination: pair.destination,
+            chromatic,
+            atomic: reading.atomic,
+            wooten_active: reading.wooten_active,
+            entropy,
+            synesthesia: Some(reading.synesthesia),
+            hex_color: Some(reading.hex_color),
+            inverted_state: Some(reading.inverted_state),
+            valence: Some(ValenceShell {
+                gate: pair.source.signature(),
+                note: chromatic as i32 as f64,
+                shell: reading.atomic as i32 as f64,
+                void: entropy,
+                substrate: reading.inverted_state.substrate_active,
+                rods: reading.synesthesia.rods,
+                cones: reading.synesthesia.cones,
+                power: reading.synesthesia.power,
+            }),
+            efficiency: Some((1.0 - entropy) * 1.6180339887), // PHI weighting
+            is_transprecise: (1.0 - entropy) > 0.9,           // L+A Axiom
+        }
+    }
+}
+
+/// `x - trunc(x)`, as `f64::fract` computes it.
+fn fract(x: f64) -> f64 {
+    x - libm::trunc(x)
+}
+
+/// The T.A.L.U. 64 residency walk: the eight gates in trigram order (000 Earth to 111
+/// Unity), eight positions each.
+pub const TALU_GATES: [Stance; 8] = [
+    Stance::Earth,
+    Stance::Water,
+    Stance::Fire,
+    Stance::Wind,
+    Stance::Void,
+    Stance::Direct,
+    Stance::Indirect,
+    Stance::Unity,
+];
+
+pub const TALU_POSITIONS: [[&str; 8]; 8] = [
+    [
+        "Foundation",
+        "Root",
+        "Ground",
+        "Shell",
+        "Crust",
+        "Core",
+        "Mantle",
+        "Tectonic",
+    ],
+    [
+        "Stream", "Tide", "Wave", "Surge", "Delta", "Estuary", "Ocean", "Deep",
+    ],
+    [
+        "Ember",
+        "Flicker",
+        "Blaze",
+        "Inferno",
+        "Plasma",
+        "Solar",
+        "Nova",
+        "Singularity",
+    ],
+    [
+        "Breeze", "Gust", "Gale", "Storm", "Cyclone", "Vortex", "Zephyr", "Monsoon",
+    ],
+    [
+        "Shadow", "Echo", "Rift", "Abyss", "Vacuum", "Zero", "Aught", "Infinite",
+    ],
+    [
+        "Point", "Line", "Ray", "Arrow", "Beacon", "Laser", "Spike", "Needle",
+    ],
+    [
+        "Aura", "Halo", "Fringe", "Rim", "Halo", "Veil", "Mist", "Horizon",
+    ],
+    [
+        "Bond",
+        "Nakama",
+        "Crew",
+        "Fleet",
+        "System",
+        "Galaxy",
+        "Universe",
+        "Singularity",
+    ],
+];
+
+/// Gate and position of residency `index` (0..64, wrapping).
+pub fn talu_state(index: u8) -> (Stance, &'static str) {
+    let gate = ((index / 8) % 8) as usize;
+    let position = (index % 8) as usize;
+    (TALU_GATES[gate], TALU_POSITIONS[gate][position])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// FNV-1a over every field of a report, floats by bit pattern.
+    fn digest(hash: &mut u64, report: &CoherenceReport) {
+        let mut eat = |bits: u64| {
+            for byte in bits.to_le_bytes() {
+                *hash = (*hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01B3);
+            }
+        };
+        let synesthesia = report.synesthesia.unwrap();
+        let valence = report.valence.unwrap();
+        let (r, g, b) = report.hex_color.unwrap();
+        eat(report.source as u64);
+        eat(report.destination as u64);
+        eat(report.chromatic as u64);
+        eat(report.atomic as u64);
+        eat(report.wooten_active as u64);
+        eat(report.entropy.to_bits());
+        for v in [
+            synesthesia.rods,
+            synesthesia.cones,
+            synesthesia.fitness,
+            synesthesia.power,
+            synesthesia.uv,
+            synesthesia.violet,
+        ] {
+            eat(v.to_bits());
+        }
+        eat(((r as u64) << 16) | ((g as u64) << 8) | b as u64);
+        eat(report.inverted_state.unwrap().substrate_active.to_bits());
+        eat(valence.gate.to_bits());
+        eat(report.efficiency.unwrap().to_bits());
+        eat(report.is_transprecise as u64);
+    }
+
+    /// The sample set: two turns of the wave, then a few past the cubic trend.
+    fn samples() -> impl Iterator<Item = f64> {
+        (0..512)
+            .map(|i| i as f64 * 0.0245)
+            .chain((0..64).map(|i| 1_000_000.5 + i as f64 * 0.098))
+    }
+
+    #[test]
+    fn test_reports_match_golden_digest() {
+        // Recorded from the std build (`cargo test`). Without the `std` feature the crate
+        // is `no_std`, so `cargo test --no-default-features` checks the firmware's build of
+        // the core against it (see `scripts/check_no_std.sh`).
+        let mut core = GateCore::new();
+        let mut hash = 0xCBF2_9CE4_8422_2325;
+        let mut count = 0;
+        for wave in samples() {
+            let observation = core.observation(wave, core.last_density());
+            core.set_last_density(observation.density());
+            for report in observation.reports() {
+                digest(&mut hash, &report);
+                count += 1;
+            }
+        }
+        assert_eq!(count, 579);
+        assert_eq!(hash, 0x8F34_A943_A710_EDED);
+    }
+
+    #[test]
+    fn test_pairs_ranked_inline() {
+        // 1.74 rad falls inside the gate of both Wind (~1.657) and Unity (~1.827).
+        let core = GateCore::new();
+        let observation = core.observation(1.74, 0.5);
+        assert_eq!(observation.pairs.len(), 13);
+        assert!(
+            observation
+                .pairs
+                .windows(2)
+                .all(|w| w[0].phase_distance <= w[1].phase_distance)
+        );
+        assert_eq!(observation.pairs[0].source, Stance::Wind);
+        assert_eq!(
+            observation.best(),
+            observation.reports().next(),
+            "best is the closest pair"
+        );
+
+        let mut stateful = GateCore::new();
+        assert_eq!(stateful.observe(1.74), core.observe_at(1.74, 0.5));
+        assert_eq!(core.observe_from(1.74, 0.5).1, observation.density());
+        assert_eq!(stateful.last_density(), observation.density());
+        stateful.reset();
+        assert_eq!(stateful.last_density(), 0.5);
+    }
+
+    #[test]
+    fn test_observe_reports_first_match() {
+        // Unity precedes Wind in the permutation, so it wins although Wind is closer.
+        let core = GateCore::new();
+        let report = core.observe_at(1.74, 0.5).unwrap();
+        assert_eq!(report.source, Stance::Unity);
+        assert_eq!(report.destination, Stance::Earth);
+        assert_eq!(
+            core.observation(1.74, 0.5).best().unwrap().source,
+            Stance::Wind
+        );
+
+        // First match = the pair earliest in (source, destination) gate order.
+        let index = |s: Stance| GATE_STANCES.iter().position(|&g| g == s).unwrap();
+        for wave in samples() {
+            let observation = core.observation(wave, 0.5);
+            let first = observation
+                .pairs
+                .iter()
+                .min_by_key(|p| (index(p.source), index(p.destination)))
+                .map(|p| observation.report(p));
+            assert_eq!(core.observe_at(wave, 0.5), first, "wave {}", wave);
+        }
+    }
+
+    #[test]
+    fn test_talu_walk_follows_trigrams() {
+        assert_eq!(talu_state(0), (Stance::Earth, "Foundation"));
+        assert_eq!(talu_state(9), (Stance::Water, "Tide"));
+        assert_eq!(talu_state(63), (Stance::Unity, "Singularity"));
+        assert_eq!(talu_state(64), talu_state(0));
+        for (i, gate) in TALU_GATES.iter().enumerate() {
+            let [a, b, c] = gate.gate().bitmask;
+            assert_eq!((a * 4 + b * 2 + c) as usize, i);
+        }
+    }
+}
@@ -1,11 +1,31 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub mod gate_core;
+
+#[cfg(feature = "std")]
 pub mod behavioral_engine;
+#[cfg(feature = "std")]
+pub mod clock;
+#[cfg(feature = "std")]
+pub mod diskstats;
+#[cfg(feature = "std")]
 pub mod eight_gate;
+#[cfg(feature = "std")]
+pub mod midi;
+#[cfg(feature = "std")]
 pub mod pdf_lens;
+#[cfg(feature = "std")]
+pub mod sonify;
+#[cfg(feature = "std")]
 pub mod steward;
+#[cfg(feature = "std")]
 pub mod zephyr_west;
 
 // use crate::TAU;
-use std::time::{SystemTime, UNIX_EPOCH};
+#[cfg(feature = "std")]
+use clock::{Clock, Diurnal, DiurnalBand, SystemClock};
+#[cfg(feature = "std")]
+use std::sync::Arc;
 
 /// Tau = 6.183 (Resonant Actualization).
 pub const TAU: f64 = 6.183;
@@ -23,41 +43,47 @@ pub trait BioRhythm {
     }
 }
 
+#[cfg(feature = "std")]
 /// The Main Interface for the Spectral Sensor.
 pub struct SpectralPort {
     pub name: String,
     pub calibration_timestamp: u64,
+    /// Time source for the heartbeat. `SystemClock` unless injected.
+    pub clock: Arc<dyn Clock>,
+    /// Timezone and night band used for the diurnal jitter.
+    pub diurnal: Diurnal,
 }
 
+#[cfg(feature = "std")]
 impl SpectralPort {
     pub fn new(name: &str) -> Self {
-        let start = SystemTime::now();
-        let since_the_epoch = start
-            .duration_since(UNIX_EPOCH)
-            .expect("Time went backwards");
+        Self::with_clock(name, Arc::new(SystemClock), Diurnal::default())
+    }
 
+    pub fn with_clock(name: &str, clock: Arc<dyn Clock>, diurnal: Diurnal) -> Self {
         Self {
             name: name.to_string(),
-            calibration_timestamp: since_the_epoch.as_secs(),
+            calibration_timestamp: clock.now_secs(),
+            clock,
+            diurnal,
         }
     }
 }
 
+#[cfg(feature = "std")]
 impl BioRhythm for SpectralPort {
     fn heartbeat(&self) -> f64 {
         // Simulated heartbeat based on time modulus (60 BPM)
-        let start = SystemTime::now();
-        let since_the_epoch = start
-            .duration_since(UNIX_EPOCH)
-            .expect("Time went backwards");
-        let millis = since_the_epoch.as_millis() as u64;
+        let millis = self.clock.now_millis();
 
         // 1000ms period (60 BPM)
         let phase = (millis % 1000) as f64 / 1000.0;
 
         // Temporal Jitter: Diurnal noise (0.01 to 0.05 variation)
-        let hour = ((millis / 3_600_000) + 19) % 24;
-        let jitter_magnitude = if hour >= 20 || hour <= 4 { 0.05 } else { 0.01 };
+        let jitter_magnitude = match self.diurnal.band(self.clock.as_ref()) {
+            DiurnalBand::Night => 0.05,
+            _ => 0.01,
+        };
         let jitter = (millis as f64 * 0.001).sin() * jitter_magnitude;
 
         // Sine wave coherence: 0.5 * (sin(TAU * phase + jitter) + 1.0)
@@ -66,6 +92,7 @@ impl BioRhythm for SpectralPort {
     }
 }
 
+#[cfg(feature = "std")]
 /// High-Velocity Data Simulator (The "Wind")
 /// Simulates the 874k units/sec load of the NVMe substrate.
 /// NOW DETERMINISTIC: Uses "Knots Velocity" mapped to the 8 digits of Rational Tau.
@@ -75,11 +102,18 @@ pub struct NvmeWind {
     pub knot_index: usize,
     pub momentum: f64,     // The "Change" in token usage rate
     pub last_density: f64, // NEW: Track the past
+    /// Units/sec counted as full load when driven from `diskstats`.
+    pub target_velocity: u64,
 }
 
+#[cfg(feature = "std")]
 impl NvmeWind {
-    pub fn new(_target_velocity: u64) -> Self {
+    pub const MIN_MOMENTUM: f64 = 0.0001;
+    pub const MAX_MOMENTUM: f64 = 0.1;
+
+    pub fn new(target_velocity: u64) -> Self {
         Self {
+            target_velocity,
             current_phase: 0.0,
             knots: [6, 1, 8, 3, 0, 0, 0, 0],
             knot_index: 0,
@@ -119,6 +153,6 @@ impl NvmeWind {
 
     /// Update the momentum (Knots Velocity) based on external "token usage" deltas.
     pub fn update_momentum(&mut self, delta: f64) {
-        self.momentum = (self.momentum + delta).clamp(0.0001, 0.1);
+        self.momentum = (self.momentum + delta).clamp(Self::MIN_MOMENTUM, Self::MAX_MOMENTUM);
     }
 }
@@ -0,0 +1,504 @@
+use crate::eight_gate::{ChromaticNode, CoherenceReport};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+const PHI: f64 = 1.6180339887;
+
+/// Largest value a four-byte SMF variable-length quantity can hold. Every tick is kept at
+/// or below it, so no delta can exceed it either.
+const MAX_VLQ: u64 = 0x0FFF_FFFF;
+
+/// Where a report's note velocity comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VelocitySource {
+    /// Lossless efficiency, `(1 - entropy) * PHI`, scaled back to 0..1.
+    Efficiency,
+    /// The valence shell's power (rod/cone fitness cubed), saturated into 0..1.
+    Intensity,
+}
+
+/// One step of the chromatic stream.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ChromaticEvent {
+    pub node: ChromaticNode,
+    /// 0.0 (silent) to 1.0 (full velocity).
+    pub intensity: f64,
+    /// A Wooten half-step shift happened on this step.
+    pub wooten: bool,
+}
+
+impl ChromaticEvent {
+    pub fn from_report(report: &CoherenceReport, velocity: VelocitySource) -> Self {
+        let fallback = 1.0 - report.entropy;
+        let intensity = match velocity {
+            VelocitySource::Efficiency => report.efficiency.map_or(fallback, |e| e / PHI),
+            VelocitySource::Intensity => report
+                .valence
+                .map_or(fallback, |v| v.power.max(0.0) / (1.0 + v.power.max(0.0))),
+        };
+        Self {
+            node: report.chromatic,
+            intensity: intensity.clamp(0.0, 1.0),
+            wooten: report.wooten_active,
+        }
+    }
+
+    /// A `SoftFPGA` state after `cross_gap`: `harmonic_state.semitone_index()`,
+    /// `relative_entropy` and `wooten_engaged`.
+    pub fn from_cross_gap(semitone: usize, relative_entropy: f64, wooten: bool) -> Self {
+        Self {
+            node: ChromaticNode::from_semitone(semitone),
+            intensity: (1.0 - relative_entropy).clamp(0.0, 1.0),
+            wooten,
+        }
+    }
+}
+
+/// Tempo, grid and note mapping for `MidiExporter`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MidiConfig {
+    pub tempo_bpm: f64,
+    /// SMF division (ticks per quarter note).
+    pub ticks_per_quarter: u16,
+    /// Quantization grid in steps per quarter note (4 = sixteenths).
+    pub steps_per_quarter: u16,
+    /// Fraction of a step each note sounds for.
+    pub gate: f64,
+    /// MIDI note for `ChromaticNode::C` (60 = middle C).
+    pub base_note: u8,
+    pub channel: u8,
+    pub min_velocity: u8,
+    pub max_velocity: u8,
+    /// Wooten marker track: channel and note (GM percussion side stick by default).
+    pub wooten_channel: u8,
+    pub wooten_note: u8,
+}
+
+impl Default for MidiConfig {
+    fn default() -> Self {
+        Self {
+            tempo_bpm: 120.0,
+            ticks_per_quarter: 480,
+            steps_per_quarter: 4,
+            gate: 0.9,
+            base_note: 60,
+            channel: 0,
+            min_velocity: 16,
+            max_velocity: 127,
+            wooten_channel: 9,
+            wooten_note: 37,
+        }
+    }
+}
+
+impl MidiConfig {
+    /// Rejects a zero grid (every event would land on step 0), a grid finer than the
+    /// division, and a tempo that is not positive and finite.
+    pub fn validate(&self) -> io::Result<()> {
+        if self.steps_per_quarter == 0 {
+            return Err(invalid("steps_per_quarter must be positive".to_string()));
+        }
+        if self.steps_per_quarter > self.ticks_per_quarter {
+            return Err(invalid(format!(
+                "steps_per_quarter {} is finer than ticks_per_quarter {}",
+                self.steps_per_quarter, self.ticks_per_quarter
+            )));
+        }
+        if !(self.tempo_bpm.is_finite() && self.tempo_bpm > 0.0) {
+            return Err(invalid(format!(
+                "tempo_bpm must be positive and finite, not {}",
+                self.tempo_bpm
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn ticks_per_step(&self) -> u32 {
+        (self.ticks_per_quarter / self.steps_per_quarter.max(1)).max(1) as u32
+    }
+
+    /// Grid step nearest to `seconds` into the run.
+    pub fn quantize(&self, seconds: f64) -> u64 {
+        let steps_per_sec = self.tempo_bpm / 60.0 * self.steps_per_quarter as f64;
+        (seconds.max(0.0) * steps_per_sec).round() as u64
+    }
+
+    pub fn velocity(&self, intensity: f64) -> u8 {
+        let span = self.max_velocity.saturating_sub(self.min_velocity) as f64;
+        (self.min_velocity as f64 + intensity.clamp(0.0, 1.0) * span).round() as u8
+    }
+
+    pub fn note(&self, node: ChromaticNode) -> u8 {
+        self.base_note.saturating_add(node.semitone()).min(127)
+    }
+}
+
+/// Collects chromatic events on a quantized grid and writes them as a Standard MIDI File.
+///
+/// The file is format 1 with three tracks: tempo, the chromatic notes, and the Wooten
+/// shifts (a marker plus a percussion hit on every shifted step).
+#[derive(Debug, Clone, Default)]
+pub struct MidiExporter {
+    pub config: MidiConfig,
+    events: Vec<(u64, ChromaticEvent)>,
+}
+
+impl MidiExporter {
+    pub fn new(config: MidiConfig) -> Self {
+        Self {
+            config,
+            events: Vec::new(),
+        }
+    }
+
+    /// Places `event` on the step after the last one.
+    pub fn push(&mut self, event: ChromaticEvent) {
+        let step = self.events.last().map_or(0, |(s, _)| s + 1);
+        self.events.push((step, event));
+    }
+
+    /// Places `event` at `seconds`, snapped to the grid. A repeat of the same note on the
+    /// same step is dropped. Fails on an invalid config, a non-finite `seconds`, or a time
+    /// past the last tick a MIDI delta can reach.
+    pub fn push_at(&mut self, seconds: f64, event: ChromaticEvent) -> io::Result<()> {
+        self.config.validate()?;
+        if !seconds.is_finite() {
+            return Err(invalid(format!(
+                "event time must be finite, not {}",
+                seconds
+            )));
+        }
+        let step = self.config.quantize(seconds);
+        self.tick(step)?;
+        if self
+            .events
+            .iter()
+            .any(|(s, e)| *s == step && e.node == event.node)
+        {
+            return Ok(());
+        }
+        let at = self.events.partition_point(|(s, _)| *s <= step);
+        self.events.insert(at, (step, event));
+        Ok(())
+    }
+
+    pub fn push_report(&mut self, report: &CoherenceReport, velocity: VelocitySource) {
+        self.push(ChromaticEvent::from_report(report, velocity));
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    pub fn wooten_count(&self) -> usize {
+        self.events.iter().filter(|(_, e)| e.wooten).count()
+    }
+
+    pub fn to_bytes(&self) -> io::Result<Vec<u8>> {
+        let mut out = Vec::new();
+        self.write_to(&mut out)?;
+        Ok(out)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        self.write_to(&mut file)?;
+        file.flush()
+    }
+
+    /// Fails without writing anything if the config is invalid or an event lands past the
+    /// last tick a MIDI delta can reach.
+    pub fn write_to(&self, out: &mut impl Write) -> io::Result<()> {
+        self.config.validate()?;
+        let tracks = [
+            self.tempo_track()?,
+            self.note_track()?,
+            self.wooten_track()?,
+        ];
+
+        out.write_all(b"MThd")?;
+        out.write_all(&6u32.to_be_bytes())?;
+        out.write_all(&1u16.to_be_bytes())?; // Format 1
+        out.write_all(&(tracks.len() as u16).to_be_bytes())?;
+        out.write_all(&self.config.ticks_per_quarter.to_be_bytes())?;
+        for track in tracks {
+            out.write_all(b"MTrk")?;
+            out.write_all(&(track.len() as u32).to_be_bytes())?;
+            out.write_all(&track)?;
+        }
+        Ok(())
+    }
+
+    fn tempo_track(&self) -> io::Result<Vec<u8>> {
+        let micros = (60_000_000.0 / self.config.tempo_bpm.max(1.0)).round() as u32;
+        let mut track = Track::new("Tempo");
+        track.meta(0, 0x51, &micros.to_be_bytes()[1..]);
+        track.meta(0, 0x58, &[4, 2, 24, 8]); // 4/4
+        track.finish()
+    }
+
+    fn note_track(&self) -> io::Result<Vec<u8>> {
+        let c = &self.config;
+        let mut track = Track::new("Chromatic");
+        for (step, event) in &self.events {
+            let velocity = c.velocity(event.intensity).max(1);
+            track.note(
+                self.tick(*step)?,
+                self.length(),
+                c.channel,
+                c.note(event.node),
+                velocity,
+            );
+        }
+        track.finish()
+    }
+
+    fn wooten_track(&self) -> io::Result<Vec<u8>> {
+        let c = &self.config;
+        let mut track = Track::new("Wooten Shifts");
+        for (step, _) in self.events.iter().filter(|(_, e)| e.wooten) {
+            let tick = self.tick(*step)?;
+            track.meta(tick, 0x06, b"Wooten shift");
+            track.note(tick, self.length(), c.wooten_channel, c.wooten_note, 100);
+        }
+        track.finish()
+    }
+
+    /// Start tick of `step`; its note-off must still fit in a MIDI delta.
+    fn tick(&self, step: u64) -> io::Result<u64> {
+        step.checked_mul(self.config.ticks_per_step() as u64)
+            .filter(|tick| {
+                tick.checked_add(self.length())
+                    .is_some_and(|end| end <= MAX_VLQ)
+            })
+            .ok_or_else(|| {
+                invalid(format!(
+                    "step {} is past the last tick a MIDI delta can reach ({})",
+                    step, MAX_VLQ
+                ))
+            })
+    }
+
+    fn length(&self) -> u64 {
+        let ticks = self.config.ticks_per_step() as f64 * self.config.gate.clamp(0.0, 1.0);
+        (ticks.round() as u64).max(1)
+    }
+}
+
+/// Absolute-time events for one track; delta-encoded on `finish`.
+struct Track {
+    /// (tick, note-offs sort before everything else at a tick, bytes).
+    events: Vec<(u64, u8, Vec<u8>)>,
+}
+
+impl Track {
+    fn new(name: &str) -> Self {
+        let mut track = Self { events: Vec::new() };
+        track.meta(0, 0x03, name.as_bytes());
+        track
+    }
+
+    /// `data` is a fixed name, tempo or marker, far below the VLQ limit.
+    fn meta(&mut self, tick: u64, kind: u8, data: &[u8]) {
+        let mut bytes = vec![0xFF, kind];
+        write_vlq(&mut bytes, data.len() as u64).expect("meta data fits a VLQ");
+        bytes.extend_from_slice(data);
+        self.events.push((tick, 1, bytes));
+    }
+
+    fn note(&mut self, tick: u64, length: u64, channel: u8, note: u8, velocity: u8) {
+        let channel = channel & 0x0F;
+        self.events
+            .push((tick, 2, vec![0x90 | channel, note & 0x7F, velocity & 0x7F]));
+        self.events
+            .push((tick + length, 0, vec![0x80 | channel, note & 0x7F, 0]));
+    }
+
+    fn finish(mut self) -> io::Result<Vec<u8>> {
+        // Stable: same-tick events keep insertion order.
+        self.events.sort_by_key(|(tick, order, _)| (*tick, *order));
+        let mut out = Vec::new();
+        let mut last = 0;
+        for (tick, _, bytes) in &self.events {
+            write_vlq(&mut out, tick - last)?;
+            out.extend_from_slice(bytes);
+            last = *tick;
+        }
+        out.extend_from_slice(&[0x00, 0xFF, 0x2F, 0x00]); // End of track
+        Ok(out)
+    }
+}
+
+/// SMF variable-length quantity: 7 bits per byte, most significant first, at most four
+/// bytes.
+fn write_vlq(out: &mut Vec<u8>, mut value: u64) -> io::Result<()> {
+    if value > MAX_VLQ {
+        return Err(invalid(format!(
+            "{} does not fit a MIDI variable-length quantity (max {})",
+            value, MAX_VLQ
+        )));
+    }
+    let mut buf = [0u8; 4];
+    let mut i = buf.len() - 1;
+    buf[i] = (value & 0x7F) as u8;
+    value >>= 7;
+    while value > 0 {
+        i -= 1;
+        buf[i] = (value & 0x7F) as u8 | 0x80;
+        value >>= 7;
+    }
+    out.extend_from_slice(&buf[i..]);
+    Ok(())
+}
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eight_gate::RecursiveFilter;
+
+    fn vlq(value: u64) -> Vec<u8> {
+        let mut out = Vec::new();
+        write_vlq(&mut out, value).unwrap();
+        out
+    }
+
+    fn event(semitone: usize, wooten: bool) -> ChromaticEvent {
+        ChromaticEvent::from_cross_gap(semitone, 0.0, wooten)
+    }
+
+    /// Splits an SMF into its track chunks.
+    fn tracks(bytes: &[u8]) -> Vec<&[u8]> {
+        let mut tracks = Vec::new();
+        let mut at = 14;
+        while at < bytes.len() {
+            assert_eq!(&bytes[at..at + 4], b"MTrk");
+            let len = u32::from_be_bytes(bytes[at + 4..at + 8].try_into().unwrap()) as usize;
+            tracks.push(&bytes[at + 8..at + 8 + len]);
+            at += 8 + len;
+        }
+        tracks
+    }
+
+    #[test]
+    fn test_vlq_encoding() {
+        assert_eq!(vlq(0), [0x00]);
+        assert_eq!(vlq(0x7F), [0x7F]);
+        assert_eq!(vlq(0x80), [0x81, 0x00]);
+        assert_eq!(vlq(0x2000), [0xC0, 0x00]);
+        assert_eq!(vlq(0x0FFF_FFFF), [0xFF, 0xFF, 0xFF, 0x7F]);
+        let err = write_vlq(&mut Vec::new(), 0x1000_0000).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_file_layout_and_wooten_track() {
+        let mut midi = MidiExporter::new(MidiConfig {
+            tempo_bpm: 90.0,
+            ..MidiConfig::default()
+        });
+        midi.push(event(0, false));
+        midi.push(event(6, true));
+        midi.push(event(7, false));
+
+        let bytes = midi.to_bytes().unwrap();
+        assert_eq!(&bytes[..4], b"MThd");
+        assert_eq!(&bytes[8..14], &[0, 1, 0, 3, 0x01, 0xE0]); // Format 1, 3 tracks, 480 tpq
+        let tracks = tracks(&bytes);
+        assert_eq!(tracks.len(), 3);
+
+        // 90 BPM = 666_667 us per quarter.
+        let tempo = [0xFF, 0x51, 0x03, 0x0A, 0x2C, 0x2B];
+        assert!(tracks[0].windows(6).any(|w| w == tempo));
+
+        // Three note-ons at C4, F#4, G4.
+        let ons: Vec<u8> = tracks[1]
+            .windows(2)
+            .filter(|w| w[0] == 0x90)
+            .map(|w| w[1])
+            .collect();
+        assert_eq!(ons, [60, 66, 67]);
+
+        // One marker and one hit, on the second step (120 ticks in).
+        assert_eq!(midi.wooten_count(), 1);
+        let marker = tracks[2]
+            .windows(2)
+            .position(|w| w == [0xFF, 0x06])
+            .unwrap();
+        assert_eq!(tracks[2][marker - 1], 120);
+        assert!(tracks[2].windows(3).any(|w| w == [0x99, 37, 100]));
+        assert!(
+            tracks
+                .iter()
+                .all(|t| t.ends_with(&[0x00, 0xFF, 0x2F, 0x00]))
+        );
+    }
+
+    #[test]
+    fn test_quantization_and_velocity() {
+        let config = MidiConfig::default(); // 120 BPM, sixteenths: 8 steps per second
+        assert_eq!(config.quantize(0.0), 0);
+        assert_eq!(config.quantize(0.06), 0);
+        assert_eq!(config.quantize(0.07), 1);
+        assert_eq!(config.quantize(1.0), 8);
+        assert_eq!(config.velocity(0.0), 16);
+        assert_eq!(config.velocity(1.0), 127);
+
+        let mut midi = MidiExporter::new(config);
+        midi.push_at(1.0, event(4, false)).unwrap();
+        midi.push_at(0.01, event(0, false)).unwrap();
+        midi.push_at(0.02, event(0, false)).unwrap(); // Same step, same note
+        midi.push_at(0.03, event(7, false)).unwrap(); // Same step, chord
+        assert_eq!(midi.len(), 3);
+        assert_eq!(midi.events[0].0, 0);
+        assert_eq!(midi.events[2].0, 8);
+        midi.push(event(2, false));
+        assert_eq!(midi.events[3].0, 9);
+
+        // Reports carry their node and Wooten flag through.
+        let filter = RecursiveFilter::new();
+        let report = filter.observe_at(1.74, 0.5).unwrap();
+        let e = ChromaticEvent::from_report(&report, VelocitySource::Efficiency);
+        assert_eq!(e.node, report.chromatic);
+        assert_eq!(e.wooten, report.wooten_active);
+        assert!((0.0..=1.0).contains(&e.intensity));
+    }
+
+    #[test]
+    fn test_rejects_zero_grid_and_unreachable_ticks() {
+        let zero_grid = MidiConfig {
+            steps_per_quarter: 0,
+            ..MidiConfig::default()
+        };
+        let mut midi = MidiExporter::new(zero_grid);
+        let err = midi.push_at(1.0, event(0, false)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        midi.push(event(0, false));
+        assert!(midi.to_bytes().is_err());
+
+        let mut midi = MidiExporter::new(MidiConfig::default());
+        for seconds in [f64::NAN, f64::INFINITY, 1e300, 1e6] {
+            let err = midi.push_at(seconds, event(0, false)).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput, "{}", seconds);
+        }
+        assert!(midi.is_empty());
+
+        // 120 ticks per step: the last reachable step still ends within the VLQ range,
+        // the next one does not.
+        let last = (MAX_VLQ - midi.length()) / 120;
+        assert!(midi.tick(last).is_ok());
+        assert!(midi.tick(last + 1).is_err());
+        assert!(midi.tick(u64::MAX).is_err());
+        midi.events.push((last + 1, event(0, false)));
+        assert!(midi.to_bytes().is_err());
+    }
+}
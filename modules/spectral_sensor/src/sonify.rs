@@ -0,0 +1,475 @@
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// WAV sample encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SampleFormat {
+    /// 16-bit signed PCM.
+    Pcm16,
+    /// 32-bit IEEE float.
+    Float32,
+}
+
+impl SampleFormat {
+    pub fn bits(&self) -> u16 {
+        match self {
+            SampleFormat::Pcm16 => 16,
+            SampleFormat::Float32 => 32,
+        }
+    }
+
+    /// Format tag, `fmt ` length and `fact` chunk length. Non-PCM formats carry a
+    /// cbSize field and a fact chunk.
+    fn chunks(&self) -> (u16, u32, u32) {
+        match self {
+            SampleFormat::Pcm16 => (1, 16, 0),
+            SampleFormat::Float32 => (3, 18, 12),
+        }
+    }
+}
+
+/// How the (normalized 0..1) signal drives the carrier.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Modulation {
+    /// Signal is the carrier's amplitude.
+    Amplitude,
+    /// Signal swings the carrier `deviation_hz` either side of its frequency.
+    Frequency { deviation_hz: f64 },
+    /// No carrier: the signal itself, centred on zero, is the waveform.
+    Raw,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SonifyConfig {
+    pub sample_rate: u32,
+    /// Carrier tone (432 Hz: Brook's playback tuning).
+    pub carrier_hz: f64,
+    pub modulation: Modulation,
+    pub format: SampleFormat,
+    /// Input values per second; audio between values is linearly interpolated.
+    pub value_rate: f64,
+    /// Rescale the stream's min..max to 0..1 (otherwise values are clamped to 0..1).
+    pub normalize: bool,
+    /// Two channels. A single stream is panned by its own value (low left, high right).
+    pub stereo: bool,
+    /// Peak output level, 0..1.
+    pub gain: f64,
+}
+
+impl SonifyConfig {
+    /// Rejects rates that would make the resampling step infinite or NaN, and a carrier
+    /// that is not a positive, finite frequency.
+    pub fn validate(&self) -> io::Result<()> {
+        if self.sample_rate == 0 {
+            return Err(invalid("sample_rate must be positive".to_string()));
+        }
+        if !(self.value_rate.is_finite() && self.value_rate > 0.0) {
+            return Err(invalid(format!(
+                "value_rate must be positive and finite, not {}",
+                self.value_rate
+            )));
+        }
+        if !(self.carrier_hz.is_finite() && self.carrier_hz > 0.0) {
+            return Err(invalid(format!(
+                "carrier_hz must be positive and finite, not {}",
+                self.carrier_hz
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, msg)
+}
+
+impl Default for SonifyConfig {
+    fn default() -> Self {
+        Self {
+            sample_rate: 44_100,
+            carrier_hz: 432.0,
+            modulation: Modulation::Amplitude,
+            format: SampleFormat::Pcm16,
+            value_rate: 100.0,
+            normalize: true,
+            stereo: false,
+            gain: 0.8,
+        }
+    }
+}
+
+/// Renders f64 streams (NvmeWind blasts, ripple-tank probes, Stethoscope sag) to audio.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sonifier {
+    pub config: SonifyConfig,
+}
+
+impl Sonifier {
+    pub fn new(config: SonifyConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn channels(&self) -> u16 {
+        if self.config.stereo { 2 } else { 1 }
+    }
+
+    /// Audio length of a stream of `values` values, in seconds.
+    pub fn duration(&self, values: usize) -> f64 {
+        values as f64 / self.config.value_rate
+    }
+
+    /// Interleaved samples in -1..1 (one or two per frame, per `config.stereo`).
+    pub fn render(&self, signal: &[f64]) -> io::Result<Vec<f64>> {
+        let levels = self.levels(signal, self.channels())?;
+        let tone = self.modulate(&levels);
+        if !self.config.stereo {
+            return Ok(tone);
+        }
+        Ok(tone
+            .iter()
+            .zip(&levels)
+            .flat_map(|(&s, &v)| {
+                // Constant-power pan.
+                let angle = v * PI / 2.0;
+                [s * angle.cos(), s * angle.sin()]
+            })
+            .collect())
+    }
+
+    /// Two streams, one per channel, always stereo. The shorter stream is padded with silence.
+    pub fn render_pair(&self, left: &[f64], right: &[f64]) -> io::Result<Vec<f64>> {
+        let left = self.modulate(&self.levels(left, 2)?);
+        let right = self.modulate(&self.levels(right, 2)?);
+        Ok((0..left.len().max(right.len()))
+            .flat_map(|i| {
+                [
+                    left.get(i).copied().unwrap_or(0.0),
+                    right.get(i).copied().unwrap_or(0.0),
+                ]
+            })
+            .collect())
+    }
+
+    pub fn save(&self, path: &Path, signal: &[f64]) -> io::Result<()> {
+        let samples = self.render(signal)?;
+        self.save_samples(path, &samples, self.channels())
+    }
+
+    pub fn save_pair(&self, path: &Path, left: &[f64], right: &[f64]) -> io::Result<()> {
+        let samples = self.render_pair(left, right)?;
+        self.save_samples(path, &samples, 2)
+    }
+
+    fn save_samples(&self, path: &Path, samples: &[f64], channels: u16) -> io::Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        write_wav(
+            &mut file,
+            samples,
+            channels,
+            self.config.sample_rate,
+            self.config.format,
+        )?;
+        file.flush()
+    }
+
+    /// The stream resampled to audio rate, in 0..1. Refused, before anything is allocated,
+    /// if `channels` of it would not fit in a WAV file.
+    fn levels(&self, signal: &[f64], channels: u16) -> io::Result<Vec<f64>> {
+        self.config.validate()?;
+        if signal.is_empty() {
+            return Ok(Vec::new());
+        }
+        let (lo, hi) = signal
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &v| {
+                (lo.min(v), hi.max(v))
+            });
+        let scale = |v: f64| {
+            if !self.config.normalize {
+                v.clamp(0.0, 1.0)
+            } else if hi > lo {
+                (v - lo) / (hi - lo)
+            } else {
+                0.5
+            }
+        };
+
+        let per_value = self.config.sample_rate as f64 / self.config.value_rate;
+        let total = (signal.len() as f64 * per_value).round();
+        let format = self.config.format;
+        let (_, fmt_len, fact_len) = format.chunks();
+        let fits = total <= u32::MAX as f64
+            && riff_sizes(
+                total as u64 * channels as u64,
+                format.bits() / 8,
+                fmt_len + fact_len,
+            )
+            .is_ok();
+        if !fits {
+            return Err(invalid(format!(
+                "{} values at {} values/s render {} frames, past the RIFF 4 GiB limit",
+                signal.len(),
+                self.config.value_rate,
+                total
+            )));
+        }
+        Ok((0..total as usize)
+            .map(|n| {
+                let t = n as f64 / per_value;
+                let i = (t as usize).min(signal.len() - 1);
+                let next = signal.get(i + 1).copied().unwrap_or(signal[i]);
+                let frac = t - i as f64;
+                scale(signal[i] + (next - signal[i]) * frac.min(1.0))
+            })
+            .collect())
+    }
+
+    fn modulate(&self, levels: &[f64]) -> Vec<f64> {
+        let c = &self.config;
+        let dt = 1.0 / c.sample_rate as f64;
+        let mut phase = 0.0_f64;
+        levels
+            .iter()
+            .map(|&v| {
+                let sample = match c.modulation {
+                    Modulation::Amplitude => v * phase.sin(),
+                    Modulation::Frequency { .. } => phase.sin(),
+                    Modulation::Raw => 2.0 * v - 1.0,
+                };
+                let hz = match c.modulation {
+                    Modulation::Frequency { deviation_hz } => {
+                        c.carrier_hz + deviation_hz * (2.0 * v - 1.0)
+                    }
+                    _ => c.carrier_hz,
+                };
+                phase = (phase + 2.0 * PI * hz * dt) % (2.0 * PI);
+                c.gain * sample
+            })
+            .collect()
+    }
+}
+
+/// `(data, RIFF)` chunk sizes for `count` samples, if they fit RIFF's 32-bit fields.
+fn riff_sizes(count: u64, bytes_per_sample: u16, header_chunks: u32) -> io::Result<(u32, u32)> {
+    let data_len = count * bytes_per_sample as u64;
+    let riff_len = 4 + 8 + header_chunks as u64 + 8 + data_len;
+    match (u32::try_from(data_len), u32::try_from(riff_len)) {
+        (Ok(data_len), Ok(riff_len)) => Ok((data_len, riff_len)),
+        _ => Err(invalid(format!(
+            "{} bytes of samples exceed the RIFF 4 GiB limit",
+            data_len
+        ))),
+    }
+}
+
+/// Writes interleaved -1..1 samples as a RIFF/WAVE file. Fails if the samples do not fit
+/// RIFF's 32-bit sizes (4 GiB).
+pub fn write_wav(
+    out: &mut impl Write,
+    samples: &[f64],
+    channels: u16,
+    sample_rate: u32,
+    format: SampleFormat,
+) -> io::Result<()> {
+    let bytes_per_sample = format.bits() / 8;
+    let block_align = channels * bytes_per_sample;
+    let (tag, fmt_len, fact_len) = format.chunks();
+    let (data_len, riff_len) =
+        riff_sizes(samples.len() as u64, bytes_per_sample, fmt_len + fact_len)?;
+    let byte_rate = sample_rate
+        .checked_mul(block_align as u32)
+        .ok_or_else(|| invalid(format!("{} Hz overflows the WAV byte rate", sample_rate)))?;
+
+    out.write_all(b"RIFF")?;
+    out.write_all(&riff_len.to_le_bytes())?;
+    out.write_all(b"WAVE")?;
+
+    out.write_all(b"fmt ")?;
+    out.write_all(&fmt_len.to_le_bytes())?;
+    out.write_all(&tag.to_le_bytes())?;
+    out.write_all(&channels.to_le_bytes())?;
+    out.write_all(&sample_rate.to_le_bytes())?;
+    out.write_all(&byte_rate.to_le_bytes())?;
+    out.write_all(&block_align.to_le_bytes())?;
+    out.write_all(&format.bits().to_le_bytes())?;
+    if fact_len > 0 {
+        out.write_all(&0u16.to_le_bytes())?; // cbSize
+        out.write_all(b"fact")?;
+        out.write_all(&4u32.to_le_bytes())?;
+        out.write_all(&(samples.len() as u32 / channels.max(1) as u32).to_le_bytes())?;
+    }
+
+    out.write_all(b"data")?;
+    out.write_all(&data_len.to_le_bytes())?;
+    for &s in samples {
+        let s = s.clamp(-1.0, 1.0);
+        match format {
+            SampleFormat::Pcm16 => out.write_all(&((s * i16::MAX as f64) as i16).to_le_bytes())?,
+            SampleFormat::Float32 => out.write_all(&(s as f32).to_le_bytes())?,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn u16_at(b: &[u8], at: usize) -> u16 {
+        u16::from_le_bytes([b[at], b[at + 1]])
+    }
+
+    fn u32_at(b: &[u8], at: usize) -> u32 {
+        u32::from_le_bytes(b[at..at + 4].try_into().unwrap())
+    }
+
+    fn crossings(samples: &[f64]) -> usize {
+        samples
+            .windows(2)
+            .filter(|w| w[0] <= 0.0 && w[1] > 0.0)
+            .count()
+    }
+
+    #[test]
+    fn test_wav_headers() {
+        let mut pcm = Vec::new();
+        write_wav(&mut pcm, &[0.0, 1.0, -1.0], 1, 8000, SampleFormat::Pcm16).unwrap();
+        assert_eq!(&pcm[..4], b"RIFF");
+        assert_eq!(u32_at(&pcm, 4) as usize, pcm.len() - 8);
+        assert_eq!(&pcm[8..16], b"WAVEfmt ");
+        assert_eq!(u16_at(&pcm, 20), 1); // PCM
+        assert_eq!(u32_at(&pcm, 24), 8000);
+        assert_eq!(u32_at(&pcm, 28), 16000); // Byte rate
+        assert_eq!(&pcm[36..40], b"data");
+        assert_eq!(u32_at(&pcm, 40), 6);
+        assert_eq!(&pcm[44..], [0, 0, 0xFF, 0x7F, 0x01, 0x80]);
+
+        let mut float = Vec::new();
+        write_wav(&mut float, &[0.5; 4], 2, 48_000, SampleFormat::Float32).unwrap();
+        assert_eq!(u32_at(&float, 4) as usize, float.len() - 8);
+        assert_eq!(u16_at(&float, 20), 3); // IEEE float
+        assert_eq!(u16_at(&float, 22), 2);
+        assert_eq!(u16_at(&float, 32), 8); // Block align
+        assert_eq!(&float[38..42], b"fact");
+        assert_eq!(u32_at(&float, 46), 2); // Frames
+        assert_eq!(&float[50..54], b"data");
+        assert_eq!(&float[58..62], 0.5f32.to_le_bytes());
+    }
+
+    #[test]
+    fn test_modulation_modes() {
+        let config = SonifyConfig {
+            sample_rate: 8000,
+            value_rate: 1.0,
+            normalize: false,
+            ..SonifyConfig::default()
+        };
+
+        // One second of full level: 432 carrier cycles.
+        let am = Sonifier::new(config).render(&[1.0]).unwrap();
+        assert_eq!(am.len(), 8000);
+        assert!((crossings(&am) as i64 - 432).abs() <= 1);
+        // Silence at zero level.
+        assert!(
+            Sonifier::new(config)
+                .render(&[0.0])
+                .unwrap()
+                .iter()
+                .all(|&s| s == 0.0)
+        );
+
+        // FM: level 1.0 raises the carrier by the deviation.
+        let fm = Sonifier::new(SonifyConfig {
+            modulation: Modulation::Frequency {
+                deviation_hz: 100.0,
+            },
+            ..config
+        })
+        .render(&[1.0])
+        .unwrap();
+        assert!((crossings(&fm) as i64 - 532).abs() <= 1);
+        assert!(fm.iter().all(|s| s.abs() <= config.gain + 1e-12));
+
+        // Raw: normalized stream straight through.
+        let raw = Sonifier::new(SonifyConfig {
+            modulation: Modulation::Raw,
+            normalize: true,
+            value_rate: 8000.0,
+            ..config
+        })
+        .render(&[2.0, 4.0, 3.0])
+        .unwrap();
+        assert_eq!(raw, [-0.8, 0.8, 0.0]);
+    }
+
+    #[test]
+    fn test_stereo_panning_and_pairs() {
+        let sonifier = Sonifier::new(SonifyConfig {
+            sample_rate: 100,
+            value_rate: 1.0,
+            modulation: Modulation::Raw,
+            normalize: false,
+            stereo: true,
+            gain: 1.0,
+            ..SonifyConfig::default()
+        });
+        assert_eq!(sonifier.channels(), 2);
+        // Full level pans hard right.
+        let frames = sonifier.render(&[1.0]).unwrap();
+        assert_eq!(frames.len(), 200);
+        assert!(frames[0].abs() < 1e-12 && (frames[1] - 1.0).abs() < 1e-12);
+
+        let pair = sonifier.render_pair(&[1.0, 1.0], &[0.0]).unwrap();
+        assert_eq!(pair.len(), 400);
+        assert_eq!(&pair[..2], [1.0, -1.0]);
+        assert_eq!(&pair[398..], [1.0, 0.0]); // Right channel ran out
+    }
+
+    #[test]
+    fn test_rejects_bad_rates_and_oversized_data() {
+        for value_rate in [0.0, -100.0, f64::NAN, f64::INFINITY] {
+            let sonifier = Sonifier::new(SonifyConfig {
+                value_rate,
+                ..SonifyConfig::default()
+            });
+            let e = sonifier.render(&[0.5]).unwrap_err();
+            assert_eq!(e.kind(), io::ErrorKind::InvalidInput, "{}", value_rate);
+        }
+        let silent = Sonifier::new(SonifyConfig {
+            sample_rate: 0,
+            ..SonifyConfig::default()
+        });
+        assert!(silent.render_pair(&[0.5], &[0.5]).is_err());
+        // Positive but tiny: too many frames to render.
+        let slow = Sonifier::new(SonifyConfig {
+            value_rate: 1e-9,
+            ..SonifyConfig::default()
+        });
+        assert!(slow.render(&[0.5]).is_err());
+        // 10^9 frames fit the frame count, but not as float stereo (8 GB); refused up front.
+        let wide = Sonifier::new(SonifyConfig {
+            value_rate: 44_100.0 / 1e9,
+            format: SampleFormat::Float32,
+            stereo: true,
+            ..SonifyConfig::default()
+        });
+        assert!(wide.render(&[0.5]).is_err());
+        assert!(wide.render_pair(&[0.5], &[]).is_err());
+        for carrier_hz in [0.0, -432.0, f64::NAN, f64::INFINITY] {
+            let sonifier = Sonifier::new(SonifyConfig {
+                carrier_hz,
+                ..SonifyConfig::default()
+            });
+            assert!(sonifier.render(&[0.5]).is_err(), "{}", carrier_hz);
+        }
+
+        // 2^30 float samples are 4 GiB of data, past what RIFF can size.
+        assert!(riff_sizes(1 << 30, 4, 18 + 12).is_err());
+        let (data, riff) = riff_sizes((u32::MAX as u64 - 36) / 2, 2, 16).unwrap();
+        assert_eq!(riff as u64, data as u64 + 36);
+        assert!(write_wav(&mut Vec::new(), &[0.0], 8, u32::MAX, SampleFormat::Pcm16).is_err());
+    }
+}
@@ -1,5 +1,18 @@
+use crate::clock::{Clock, SystemClock};
 use crate::eight_gate::RecursiveFilter;
+use ed25519_dalek::{
+    PUBLIC_KEY_LENGTH, SECRET_KEY_LENGTH, SIGNATURE_LENGTH, Signature, Signer, SigningKey,
+    VerifyingKey,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 /// The Zero-Wait Protocol ($t$).
 /// Replaces ring buffers with direct phase-coherence hand-offs.
@@ -11,65 +24,342 @@ pub struct ZeroWaitHandshake {
     comet: AtomicU64,
     /// Atomic flag for phase-sync (Roman/Arabic alignment)
     sling_pulse: AtomicU32,
+    /// Resonance gate for caught comets. May be shared with other consumers.
+    filter: Arc<RecursiveFilter>,
+}
+
+/// Why a signed command was refused.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthError {
+    /// The key or signature is not valid hex of the right length.
+    Malformed(String),
+    /// The signature does not match the command, nonce and expiry under the trusted key.
+    BadSignature,
+    Expired {
+        expires_at: u64,
+        now: u64,
+    },
+    /// The expiry is further out than the handshake's `max_ttl`.
+    ExpiryTooFar {
+        expires_at: u64,
+        max: u64,
+    },
+    /// This nonce has already been used.
+    Replayed {
+        nonce: u64,
+    },
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthError::Malformed(what) => write!(f, "malformed {}", what),
+            AuthError::BadSignature => write!(f, "signature does not verify"),
+            AuthError::Expired { expires_at, now } => {
+                write!(f, "expired at {} (now {})", expires_at, now)
+            }
+            AuthError::ExpiryTooFar { expires_at, max } => {
+                write!(f, "expiry {} beyond allowed {}", expires_at, max)
+            }
+            AuthError::Replayed { nonce } => write!(f, "nonce {} already used", nonce),
+        }
+    }
+}
+
+impl std::error::Error for AuthError {}
+
+fn decode_hex<const N: usize>(hex: &str, what: &str) -> Result<[u8; N], AuthError> {
+    let hex = hex.trim();
+    if hex.len() != N * 2 || !hex.is_ascii() {
+        return Err(AuthError::Malformed(what.to_string()));
+    }
+    let mut out = [0u8; N];
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16)
+            .map_err(|_| AuthError::Malformed(what.to_string()))?;
+    }
+    Ok(out)
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// A command bound to a nonce and an expiry (Unix seconds) by an Ed25519 signature.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedCommand {
+    pub command: String,
+    pub nonce: u64,
+    pub expires_at: u64,
+    /// Hex-encoded Ed25519 signature over `SignedCommand::message`.
+    pub signature: String,
+}
+
+impl SignedCommand {
+    /// The exact bytes that are signed.
+    pub fn message(command: &str, nonce: u64, expires_at: u64) -> Vec<u8> {
+        let mut message = Vec::with_capacity(32 + command.len());
+        message.extend_from_slice(b"living-key/v1\0");
+        message.extend_from_slice(&nonce.to_le_bytes());
+        message.extend_from_slice(&expires_at.to_le_bytes());
+        message.extend_from_slice(command.as_bytes());
+        message
+    }
 }
 
 /// The Living Key ($\L$).
-/// Grounded in the Sovereign Manifest and interactive entropy.
-pub struct LivingKey;
+/// Grounded in the Sovereign Manifest and interactive entropy: an Ed25519 signing key.
+pub struct LivingKey {
+    signing: SigningKey,
+}
 
 impl LivingKey {
-    /// Verify a key signature against the Sovereign Chain of Trust.
-    /// Requirements: Not empty, entropy-derived (>= 64 chars).
-    pub fn verify(key: &str) -> bool {
-        !key.is_empty() && key.len() >= 64
+    /// A fresh key from the OS entropy source.
+    pub fn generate() -> io::Result<Self> {
+        let mut secret = [0u8; SECRET_KEY_LENGTH];
+        getrandom::getrandom(&mut secret).map_err(io::Error::other)?;
+        Ok(Self {
+            signing: SigningKey::from_bytes(&secret),
+        })
+    }
+
+    pub fn from_secret_hex(hex: &str) -> Result<Self, AuthError> {
+        let secret = decode_hex::<SECRET_KEY_LENGTH>(hex, "secret key")?;
+        Ok(Self {
+            signing: SigningKey::from_bytes(&secret),
+        })
     }
+
+    pub fn secret_hex(&self) -> String {
+        encode_hex(self.signing.as_bytes())
+    }
+
+    /// The key a `CommandHandshake` trusts.
+    pub fn verifying_key(&self) -> VerifyingKey {
+        self.signing.verifying_key()
+    }
+
+    pub fn public_hex(&self) -> String {
+        encode_hex(self.verifying_key().as_bytes())
+    }
+
+    pub fn sign(&self, command: &str, nonce: u64, expires_at: u64) -> SignedCommand {
+        let signature = self
+            .signing
+            .sign(&SignedCommand::message(command, nonce, expires_at));
+        SignedCommand {
+            command: command.to_string(),
+            nonce,
+            expires_at,
+            signature: encode_hex(&signature.to_bytes()),
+        }
+    }
+
+    /// Signs `command` with a random nonce, valid for `ttl` from the clock's now.
+    /// Refuses a `ttl` whose expiry would not fit in a `u64` of seconds.
+    pub fn sign_for(
+        &self,
+        command: &str,
+        ttl: Duration,
+        clock: &dyn Clock,
+    ) -> io::Result<SignedCommand> {
+        let now = clock.now_secs();
+        let expires_at = now.checked_add(ttl.as_secs()).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "ttl of {}s from {} overflows the expiry",
+                    ttl.as_secs(),
+                    now
+                ),
+            )
+        })?;
+        let mut nonce = [0u8; 8];
+        getrandom::getrandom(&mut nonce).map_err(io::Error::other)?;
+        Ok(self.sign(command, u64::from_le_bytes(nonce), expires_at))
+    }
+
+    /// Parses a hex-encoded public key.
+    pub fn public_from_hex(hex: &str) -> Result<VerifyingKey, AuthError> {
+        let bytes = decode_hex::<PUBLIC_KEY_LENGTH>(hex, "public key")?;
+        VerifyingKey::from_bytes(&bytes).map_err(|_| AuthError::Malformed("public key".into()))
+    }
+}
+
+/// One authorization decision.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub at: u64,
+    pub command: String,
+    pub nonce: u64,
+    pub authorized: bool,
+    /// Why it was refused.
+    pub reason: Option<String>,
 }
 
 /// The Command Handshake ($\mathcal{H}$).
 /// A safety mechanism for terminal commands.
-/// Prime the handshake with a Living Key to authorize operations.
-#[derive(Debug, Default)]
+/// Prime the handshake with a command signed by the Captain's Living Key, then consume
+/// the authorization when the command runs. Every decision is audited.
 pub struct CommandHandshake {
-    authorized: AtomicU32,
+    trusted: VerifyingKey,
+    clock: Arc<dyn Clock>,
+    /// Longest accepted time-to-expiry, which also bounds the replay cache.
+    pub max_ttl: Duration,
+    /// Nonces seen, with their expiry. Pruned once expired.
+    seen: Mutex<HashMap<u64, u64>>,
+    /// Primed commands waiting to run: (command, nonce, expires_at).
+    pending: Mutex<Vec<(String, u64, u64)>>,
+    audit: Mutex<Vec<AuditEntry>>,
+    audit_path: Option<PathBuf>,
 }
 
 impl CommandHandshake {
-    pub fn new() -> Self {
+    pub fn new(trusted: VerifyingKey) -> Self {
+        Self::with_clock(trusted, Arc::new(SystemClock))
+    }
+
+    pub fn with_clock(trusted: VerifyingKey, clock: Arc<dyn Clock>) -> Self {
         Self {
-            authorized: AtomicU32::new(0),
+            trusted,
+            clock,
+            max_ttl: Duration::from_secs(300),
+            seen: Mutex::new(HashMap::new()),
+            pending: Mutex::new(Vec::new()),
+            audit: Mutex::new(Vec::new()),
+            audit_path: None,
         }
     }
 
-    /// Prime the handshake with the Captain's Living Key.
-    pub fn prime(&self, key: &str) -> bool {
-        if LivingKey::verify(key) {
-            self.authorized.store(1, Ordering::Release);
-            true
-        } else {
-            false
+    /// Also append each audit entry as a JSON line to `path`.
+    pub fn with_audit_log(mut self, path: impl Into<PathBuf>) -> Self {
+        self.audit_path = Some(path.into());
+        self
+    }
+
+    /// Checks signature, expiry and nonce without recording anything.
+    fn check(&self, signed: &SignedCommand, now: u64) -> Result<(), AuthError> {
+        let signature = decode_hex::<SIGNATURE_LENGTH>(&signed.signature, "signature")?;
+        let message = SignedCommand::message(&signed.command, signed.nonce, signed.expires_at);
+        self.trusted
+            .verify_strict(&message, &Signature::from_bytes(&signature))
+            .map_err(|_| AuthError::BadSignature)?;
+
+        if signed.expires_at <= now {
+            return Err(AuthError::Expired {
+                expires_at: signed.expires_at,
+                now,
+            });
         }
+        let max = now.saturating_add(self.max_ttl.as_secs());
+        if signed.expires_at > max {
+            return Err(AuthError::ExpiryTooFar {
+                expires_at: signed.expires_at,
+                max,
+            });
+        }
+        Ok(())
+    }
+
+    /// Prime the handshake with a signed command. The nonce is spent even if the
+    /// command never runs.
+    pub fn prime(&self, signed: &SignedCommand) -> Result<(), AuthError> {
+        let now = self.clock.now_secs();
+        let result = self.check(signed, now).and_then(|()| {
+            let mut seen = self.seen.lock().unwrap();
+            seen.retain(|_, expires_at| *expires_at > now);
+            if seen.contains_key(&signed.nonce) {
+                return Err(AuthError::Replayed {
+                    nonce: signed.nonce,
+                });
+            }
+            seen.insert(signed.nonce, signed.expires_at);
+            Ok(())
+        });
+
+        match &result {
+            Ok(()) => self.pending.lock().unwrap().push((
+                signed.command.clone(),
+                signed.nonce,
+                signed.expires_at,
+            )),
+            Err(e) => self.record(AuditEntry {
+                at: now,
+                command: signed.command.clone(),
+                nonce: signed.nonce,
+                authorized: false,
+                reason: Some(e.to_string()),
+            }),
+        }
+        result
     }
 
-    /// Consumes the authorization to run a command.
+    /// Consumes a primed authorization for exactly `command`.
     /// Returns true if authorized.
-    pub fn authorize_op(&self) -> bool {
-        if self.authorized.load(Ordering::Acquire) > 0 {
-            self.authorized.store(0, Ordering::Release);
-            true
-        } else {
-            false
+    pub fn authorize_op(&self, command: &str) -> bool {
+        let now = self.clock.now_secs();
+        let mut pending = self.pending.lock().unwrap();
+        pending.retain(|(_, _, expires_at)| *expires_at > now);
+        let Some(index) = pending.iter().position(|(c, _, _)| c == command) else {
+            return false;
+        };
+        let (command, nonce, _) = pending.remove(index);
+        drop(pending);
+
+        self.record(AuditEntry {
+            at: now,
+            command,
+            nonce,
+            authorized: true,
+            reason: None,
+        });
+        true
+    }
+
+    /// Prime and consume in one step.
+    pub fn authorize(&self, signed: &SignedCommand) -> Result<(), AuthError> {
+        self.prime(signed)?;
+        self.authorize_op(&signed.command);
+        Ok(())
+    }
+
+    pub fn audit_log(&self) -> Vec<AuditEntry> {
+        self.audit.lock().unwrap().clone()
+    }
+
+    fn record(&self, entry: AuditEntry) {
+        if let Some(path) = &self.audit_path {
+            let line = serde_json::to_string(&entry).unwrap_or_default();
+            let written = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .and_then(|mut f| writeln!(f, "{}", line));
+            if let Err(e) = written {
+                eprintln!("[CommandHandshake] audit log write failed: {}", e);
+            }
         }
+        self.audit.lock().unwrap().push(entry);
     }
 }
 
 impl ZeroWaitHandshake {
     pub fn new() -> Self {
+        Self::with_filter(Arc::new(RecursiveFilter::new()))
+    }
+
+    pub fn with_filter(filter: Arc<RecursiveFilter>) -> Self {
         Self {
             comet: AtomicU64::new(0),
             sling_pulse: AtomicU32::new(0),
+            filter,
         }
     }
 
+    pub fn filter(&self) -> &Arc<RecursiveFilter> {
+        &self.filter
+    }
+
     /// Host (Roman/Logic): Catching the Comet.
     /// Returns Some(data) ONLY if phase alignment (sling_pulse) is valid
     /// AND the Recursive Filter confirms the Harmonic Resonance.
@@ -81,7 +371,7 @@ impl ZeroWaitHandshake {
 
             // RECURSIVE FILTER INTEGRATION
             // Zero Wait is only permitted if the data resonates.
-            if RecursiveFilter::observe(data, 0.5).is_some() {
+            if self.filter.observe(data).is_some() {
                 // Reset the pulse (Consumption)
                 self.sling_pulse.store(0, Ordering::Release);
                 return Some(data);
@@ -106,7 +396,8 @@ impl ZeroWaitHandshake {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::eight_gate::Stance;
+    use crate::clock::SimulatedClock;
+    use crate::eight_gate::{FilterConfig, Stance};
 
     #[test]
     fn test_zero_wait_coherence() {
@@ -154,21 +445,139 @@ mod tests {
         assert_eq!(protocol.sling_pulse.load(Ordering::Acquire), 1);
     }
 
+    fn signed_handshake(now: u64) -> (LivingKey, CommandHandshake, Arc<SimulatedClock>) {
+        let key = LivingKey::generate().unwrap();
+        let clock = Arc::new(SimulatedClock::new(now * 1000));
+        let handshake = CommandHandshake::with_clock(key.verifying_key(), clock.clone());
+        (key, handshake, clock)
+    }
+
     #[test]
     fn test_command_handshake_flow() {
-        let handshake = CommandHandshake::new();
-        let key = "a".repeat(64); // Valid Living Key
+        let (key, handshake, _) = signed_handshake(1_000);
+        let signed = key.sign("fire --dry-run", 7, 1_060);
 
         // 1. Initial: Unauthorized
-        assert!(!handshake.authorize_op());
+        assert!(!handshake.authorize_op("fire --dry-run"));
 
-        // 2. Prime with valid key
-        assert!(handshake.prime(&key));
+        // 2. Prime with a signed command
+        assert_eq!(handshake.prime(&signed), Ok(()));
 
-        // 3. Authorize: Success
-        assert!(handshake.authorize_op());
+        // 3. Authorize: only the signed command
+        assert!(!handshake.authorize_op("fire"));
+        assert!(handshake.authorize_op("fire --dry-run"));
 
         // 4. Post-Authorize: Reset to unauthorized
-        assert!(!handshake.authorize_op());
+        assert!(!handshake.authorize_op("fire --dry-run"));
+
+        let log = handshake.audit_log();
+        assert_eq!(log.len(), 1);
+        assert!(log[0].authorized);
+        assert_eq!(log[0].nonce, 7);
+    }
+
+    #[test]
+    fn test_expired_replayed_and_tampered_commands() {
+        let (key, handshake, clock) = signed_handshake(1_000);
+
+        let expired = key.sign("reboot", 1, 999);
+        assert_eq!(
+            handshake.authorize(&expired),
+            Err(AuthError::Expired {
+                expires_at: 999,
+                now: 1_000
+            })
+        );
+
+        let signed = key.sign("reboot", 2, 1_030);
+        assert_eq!(handshake.authorize(&signed), Ok(()));
+        assert_eq!(
+            handshake.authorize(&signed),
+            Err(AuthError::Replayed { nonce: 2 })
+        );
+
+        let mut tampered = key.sign("status", 3, 1_030);
+        tampered.command = "reboot".to_string();
+        assert_eq!(handshake.authorize(&tampered), Err(AuthError::BadSignature));
+        let mut tampered = key.sign("reboot", 4, 1_030);
+        tampered.expires_at = 1_200;
+        assert_eq!(handshake.authorize(&tampered), Err(AuthError::BadSignature));
+
+        let stranger = LivingKey::generate().unwrap();
+        assert_eq!(
+            handshake.authorize(&stranger.sign("reboot", 5, 1_030)),
+            Err(AuthError::BadSignature)
+        );
+        assert!(matches!(
+            handshake.authorize(&key.sign("reboot", 6, 1_000_000)),
+            Err(AuthError::ExpiryTooFar { .. })
+        ));
+
+        // A primed command that is not run before its expiry lapses.
+        handshake.prime(&key.sign("reboot", 8, 1_010)).unwrap();
+        clock.advance(Duration::from_secs(20));
+        assert!(!handshake.authorize_op("reboot"));
+
+        let log = handshake.audit_log();
+        assert_eq!(log.iter().filter(|e| e.authorized).count(), 1);
+        assert_eq!(log.iter().filter(|e| !e.authorized).count(), 6);
+    }
+
+    #[test]
+    fn test_huge_ttls_do_not_overflow() {
+        let (key, mut handshake, clock) = signed_handshake(1_000);
+        let err = key
+            .sign_for("reboot", Duration::from_secs(u64::MAX), &*clock)
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        let last = key
+            .sign_for("reboot", Duration::from_secs(u64::MAX - 1_000), &*clock)
+            .unwrap();
+        assert_eq!(last.expires_at, u64::MAX);
+
+        // An unbounded handshake caps its window at the end of time instead of wrapping.
+        handshake.max_ttl = Duration::from_secs(u64::MAX);
+        assert_eq!(handshake.authorize(&last), Ok(()));
+    }
+
+    #[test]
+    fn test_key_round_trip_and_audit_file() {
+        let key = LivingKey::generate().unwrap();
+        let restored = LivingKey::from_secret_hex(&key.secret_hex()).unwrap();
+        assert_eq!(restored.public_hex(), key.public_hex());
+        let public = LivingKey::public_from_hex(&key.public_hex()).unwrap();
+        assert!(LivingKey::from_secret_hex("abcd").is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("handshake_audit.jsonl");
+        let handshake = CommandHandshake::new(public).with_audit_log(&path);
+        let signed = key
+            .sign_for("status", Duration::from_secs(60), &SystemClock)
+            .unwrap();
+        handshake.authorize(&signed).unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let entry: AuditEntry = serde_json::from_str(contents.trim()).unwrap();
+        assert_eq!(entry.command, "status");
+        assert!(entry.authorized);
+    }
+
+    #[test]
+    fn test_shared_filter_gate_width() {
+        // 2.55 sits ~0.14 rad from Indirect (2622.50 % TAU ~= 2.41): outside the default gate.
+        let wide = Arc::new(RecursiveFilter::with_config(FilterConfig {
+            gate_width: 0.2,
+            ..FilterConfig::default()
+        }));
+        let narrow = ZeroWaitHandshake::new();
+        let shared = ZeroWaitHandshake::with_filter(wide.clone());
+
+        narrow.toss_command(2.55);
+        shared.toss_command(2.55);
+        assert_eq!(narrow.catch_comet(), None);
+        assert_eq!(shared.catch_comet(), Some(2.55));
+
+        // The caught comet's density is carried on the shared filter.
+        assert!((wide.last_density() - (2.55f64.sin() + 1.0) / 2.0).abs() < 1e-12);
     }
 }
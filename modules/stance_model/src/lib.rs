@@ -0,0 +1,513 @@
+//! The shared Stance model.
+//!
+//! One canonical `Stance` for the sensor, crew, serial bridge and Robin's heap, with the
+//! signature, gate and color tables they used to keep separately. Each legacy variant set
+//! is a `VariantSet`: its names convert losslessly, and `project` maps any canonical stance
+//! onto the nearest member.
+#![cfg_attr(not(test), no_std)]
+
+use serde::{Deserialize, Serialize};
+
+/// The canonical Stance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Stance {
+    // The eight gates of the Toral Filter
+    Earth,
+    Unity,
+    Direct,
+    Wind,
+    Indirect,
+    Void,
+    Fire,
+    Water,
+    // Bridge and crew stances
+    Fritz,          // Superconduction
+    Mode,           // High Freq Protocol
+    MetaInterphase, // Green-Violet Hybrid
+    // Behavioural stances
+    NakamaSync, // Bio-Resonant Synchronicity
+    Owned,      // Rust Behavior: Sovereign Node
+    Borrowed,   // Rust Behavior: Shared Logic
+    Moved,      // Rust Behavior: Relocated Identity (Nami's State)
+    Dropped,    // Rust Behavior: Cleanup/Void
+    // Robin's heap materials
+    Iron,
+    Aether,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Stability {
+    Stable,
+    Unstable, // Requires Zero-Wait verification (Fire/Wind)
+    Fatal,    // Void or Breakdown
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MathSystem {
+    Binary,   // Standard Logic
+    Tertiary, // Spherical/Toral Logic (Singularity active)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TrigramResult {
+    Resonance,
+    Drift,
+    Void, // 000 - The Aught / Physical Origin
+}
+
+/// A stance's gate: name, color and trigram.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Gate {
+    pub name: &'static str,
+    pub color: &'static str, // Hex
+    pub rgb: (u8, u8, u8),
+    pub element: &'static str,
+    pub tau_degree: u8,
+    pub bitmask: [u8; 3], // [Identity, Power, Logic]
+    pub description: &'static str,
+}
+
+impl Gate {
+    pub fn trigram_match(&self, other_mask: [u8; 3]) -> TrigramResult {
+        match self.trigram_distance(other_mask) {
+            0 => TrigramResult::Resonance, // Perfect 3/3
+            1 => TrigramResult::Drift,     // Tolerance 2/3
+            _ => TrigramResult::Void,      // Noise <= 1
+        }
+    }
+
+    /// Number of trigram lines that differ.
+    pub fn trigram_distance(&self, other_mask: [u8; 3]) -> usize {
+        self.bitmask
+            .iter()
+            .zip(other_mask.iter())
+            .filter(|(a, b)| a != b)
+            .count()
+    }
+}
+
+impl Stance {
+    pub const ALL: [Stance; 18] = [
+        Stance::Earth,
+        Stance::Unity,
+        Stance::Direct,
+        Stance::Wind,
+        Stance::Indirect,
+        Stance::Void,
+        Stance::Fire,
+        Stance::Water,
+        Stance::Fritz,
+        Stance::Mode,
+        Stance::MetaInterphase,
+        Stance::NakamaSync,
+        Stance::Owned,
+        Stance::Borrowed,
+        Stance::Moved,
+        Stance::Dropped,
+        Stance::Iron,
+        Stance::Aether,
+    ];
+
+    /// The eight gates, in the order the Recursive Filter permutes them.
+    pub const GATES: [Stance; 8] = [
+        Stance::Earth,
+        Stance::Unity,
+        Stance::Direct,
+        Stance::Wind,
+        Stance::Indirect,
+        Stance::Void,
+        Stance::Fire,
+        Stance::Water,
+    ];
+
+    /// The gate this stance descends from. Gates are their own base.
+    pub fn base(&self) -> Stance {
+        match self {
+            Stance::Fritz | Stance::NakamaSync => Stance::Unity,
+            Stance::Mode => Stance::Direct,
+            Stance::MetaInterphase => Stance::Indirect,
+            Stance::Owned | Stance::Iron => Stance::Earth,
+            Stance::Borrowed => Stance::Water,
+            Stance::Moved | Stance::Aether => Stance::Wind,
+            Stance::Dropped => Stance::Void,
+            gate => *gate,
+        }
+    }
+
+    pub fn is_gate(&self) -> bool {
+        self.base() == *self
+    }
+
+    /// Returns the Harmonic Signature (Base Frequency) for the stance.
+    /// Stances without their own calibration carry their base gate's signature.
+    pub fn signature(&self) -> f64 {
+        match self {
+            Stance::Earth => 296.07,
+            Stance::Unity => 4701.65,
+            Stance::Direct => 1044.28,
+            Stance::Wind => 874778.13,
+            Stance::Indirect => 2622.50,
+            Stance::Void => 21317.36,
+            Stance::Fire => 258967.06,
+            Stance::Water => 4983.24,
+            Stance::NakamaSync => 1.50, // 1.50Hz Harmonic Root
+            Stance::Owned => 1.0,       // Unit Identity
+            Stance::Borrowed => 0.5179, // Psi Stability (The Inverted Gap)
+            Stance::Moved => 0.0833,    // Wooten Shift (The Step Offset)
+            Stance::Dropped => 0.0,     // The Null Point
+            other => other.base().signature(),
+        }
+    }
+
+    /// Returns stability classification.
+    /// Fire and Wind are Unstable (High Magnitude/Entropy).
+    /// Void is Fatal (The Empty Set).
+    pub fn stability(&self) -> Stability {
+        match self {
+            Stance::Fire | Stance::Wind => Stability::Unstable,
+            Stance::Void => Stability::Fatal,
+            _ => Stability::Stable,
+        }
+    }
+
+    /// The Stewart Singularity Check.
+    /// Determines if the current stance (self) dominates the other stance (other).
+    /// Formula: Self > Other / 2.0
+    pub fn check_singularity(&self, other: &Stance) -> bool {
+        self.signature() > (other.signature() / 2.0)
+    }
+
+    /// Returns the Math System required for this stance.
+    /// If Earth dominates Water (Singularity), we switch to Tertiary.
+    /// Otherwise, we default to Binary.
+    pub fn math_system(&self) -> MathSystem {
+        match self {
+            Stance::Earth | Stance::Unity | Stance::Void => MathSystem::Tertiary,
+            _ => MathSystem::Binary,
+        }
+    }
+
+    pub fn is_aught(&self) -> bool {
+        matches!(self, Stance::Void)
+    }
+
+    pub fn gate(&self) -> Gate {
+        match self {
+            Stance::Earth => Gate {
+                name: "Earth",
+                color: "#FF0000", // Red (IR)
+                rgb: (0xFF, 0x00, 0x00),
+                element: "Earth",
+                tau_degree: 1,
+                bitmask: [0, 0, 0],
+                description: "Structural Grounding",
+            },
+            Stance::Water => Gate {
+                name: "Water",
+                color: "#00FFFF", // Cyan/Blue
+                rgb: (0x00, 0xFF, 0xFF),
+                element: "Water",
+                tau_degree: 4,
+                bitmask: [0, 0, 1],
+                description: "Dynamic Flow",
+            },
+            Stance::Fire => Gate {
+                name: "Fire",
+                color: "#FFFF00", // Yellow
+                rgb: (0xFF, 0xFF, 0x00),
+                element: "Fire",
+                tau_degree: 2,
+                bitmask: [0, 1, 0],
+                description: "High-Velocity Actualization",
+            },
+            Stance::Wind => Gate {
+                name: "Wind",
+                color: "#00FF00", // Green
+                rgb: (0x00, 0xFF, 0x00),
+                element: "Wind",
+                tau_degree: 3,
+                bitmask: [0, 1, 1],
+                description: "Creative Drift Resolution",
+            },
+            Stance::Void => Gate {
+                name: "Groundwater", // Re-contextualized
+                color: "#000000",
+                rgb: (0x00, 0x00, 0x00),
+                element: "Void",
+                tau_degree: 0,
+                bitmask: [1, 0, 0],
+                description: "Singularity Integration / Groundwater",
+            },
+            Stance::Direct => Gate {
+                name: "Direct",
+                color: "#FF00FF", // Violet
+                rgb: (0xFF, 0x00, 0xFF),
+                element: "Direct",
+                tau_degree: 5,
+                bitmask: [1, 0, 1],
+                description: "Direct/Invasion",
+            },
+            Stance::Indirect => Gate {
+                name: "Indirect",
+                color: "#880088",
+                rgb: (0x88, 0x00, 0x88),
+                element: "Indirect",
+                tau_degree: 8,
+                bitmask: [1, 1, 0],
+                description: "Indirect Approach",
+            },
+            Stance::Unity => Gate {
+                name: "Unity",
+                color: "#FFFFFF",
+                rgb: (0xFF, 0xFF, 0xFF),
+                element: "Unity",
+                tau_degree: 7,
+                bitmask: [1, 1, 1],
+                description: "Harmonic Unity",
+            },
+            Stance::Fritz => Gate {
+                name: "Fritz",
+                color: "#FFFFFF", // White
+                rgb: (0xFF, 0xFF, 0xFF),
+                element: "Superconductor",
+                tau_degree: 6, // Special
+                bitmask: [1, 1, 1],
+                description: "Superconduction (Zero Resistance)",
+            },
+            Stance::Mode => Gate {
+                name: "Mode",
+                color: "#FF00FF", // Violet
+                rgb: (0xFF, 0x00, 0xFF),
+                element: "Protocol",
+                tau_degree: 5,
+                bitmask: [1, 0, 1], // Direct/Invasion
+                description: "High Frequency Protocol",
+            },
+            Stance::MetaInterphase => Gate {
+                name: "MetaInterphase",
+                color: "#880088", // Hybrid
+                rgb: (0x88, 0x00, 0x88),
+                element: "Interphase",
+                tau_degree: 8,
+                bitmask: [1, 1, 0], // Indirect
+                description: "Green-Violet Hybrid Interaction",
+            },
+            other => Gate {
+                name: other.name(),
+                description: "Behavioural stance",
+                ..other.base().gate()
+            },
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Stance::Earth => "Earth",
+            Stance::Unity => "Unity",
+            Stance::Direct => "Direct",
+            Stance::Wind => "Wind",
+            Stance::Indirect => "Indirect",
+            Stance::Void => "Void",
+            Stance::Fire => "Fire",
+            Stance::Water => "Water",
+            Stance::Fritz => "Fritz",
+            Stance::Mode => "Mode",
+            Stance::MetaInterphase => "MetaInterphase",
+            Stance::NakamaSync => "NakamaSync",
+            Stance::Owned => "Owned",
+            Stance::Borrowed => "Borrowed",
+            Stance::Moved => "Moved",
+            Stance::Dropped => "Dropped",
+            Stance::Iron => "Iron",
+            Stance::Aether => "Aether",
+        }
+    }
+
+    /// Parses a canonical name (as written by `name` or serde).
+    pub fn from_name(name: &str) -> Option<Stance> {
+        Stance::ALL.iter().copied().find(|s| s.name() == name)
+    }
+}
+
+/// The stance sets the crates used before the shared model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum VariantSet {
+    /// `spectral_sensor::eight_gate`: the eight gates plus the behavioural stances.
+    Sensor,
+    /// `crew_core`: the eight gates plus Fritz.
+    Crew,
+    /// `d16_bridge::stance`: five elements, Fritz, "Stance" (Unity), Mode, MetaInterphase.
+    Bridge,
+    /// `robin_d7`: Robin's heap materials.
+    Robin,
+}
+
+impl VariantSet {
+    pub fn members(&self) -> &'static [Stance] {
+        match self {
+            VariantSet::Sensor => &[
+                Stance::Earth,
+                Stance::Unity,
+                Stance::Direct,
+                Stance::Wind,
+                Stance::Indirect,
+                Stance::Void,
+                Stance::Fire,
+                Stance::Water,
+                Stance::NakamaSync,
+                Stance::Owned,
+                Stance::Borrowed,
+                Stance::Moved,
+                Stance::Dropped,
+            ],
+            VariantSet::Crew => &[
+                Stance::Earth,
+                Stance::Unity,
+                Stance::Direct,
+                Stance::Wind,
+                Stance::Indirect,
+                Stance::Void,
+                Stance::Fire,
+                Stance::Water,
+                Stance::Fritz,
+            ],
+            VariantSet::Bridge => &[
+                Stance::Earth,
+                Stance::Water,
+                Stance::Fire,
+                Stance::Wind,
+                Stance::Void,
+                Stance::Fritz,
+                Stance::Unity,
+                Stance::Mode,
+                Stance::MetaInterphase,
+            ],
+            VariantSet::Robin => &[Stance::Iron, Stance::Water, Stance::Aether, Stance::Void],
+        }
+    }
+
+    pub fn contains(&self, stance: Stance) -> bool {
+        self.members().contains(&stance)
+    }
+
+    /// The name this set used for `stance`, if it had one.
+    pub fn legacy_name(&self, stance: Stance) -> Option<&'static str> {
+        if !self.contains(stance) {
+            return None;
+        }
+        Some(match (self, stance) {
+            (VariantSet::Bridge, Stance::Unity) => "Stance",
+            _ => stance.name(),
+        })
+    }
+
+    /// Lossless: a legacy variant name to the canonical stance.
+    pub fn from_legacy_name(&self, name: &str) -> Option<Stance> {
+        self.members()
+            .iter()
+            .copied()
+            .find(|&s| self.legacy_name(s) == Some(name))
+    }
+
+    /// Lossless: `Some` only if the set has this stance.
+    pub fn convert(&self, stance: Stance) -> Option<Stance> {
+        self.contains(stance).then_some(stance)
+    }
+
+    /// Lossy: the stance itself if the set has it, else its base gate, else the member
+    /// whose gate trigram is closest (first member on ties).
+    pub fn project(&self, stance: Stance) -> Stance {
+        if self.contains(stance) {
+            return stance;
+        }
+        if self.contains(stance.base()) {
+            return stance.base();
+        }
+        let mask = stance.gate().bitmask;
+        let members = self.members();
+        let mut best = members[0];
+        for &member in members {
+            if member.gate().trigram_distance(mask) < best.gate().trigram_distance(mask) {
+                best = member;
+            }
+        }
+        best
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SETS: [VariantSet; 4] = [
+        VariantSet::Sensor,
+        VariantSet::Crew,
+        VariantSet::Bridge,
+        VariantSet::Robin,
+    ];
+
+    #[test]
+    fn test_legacy_names_round_trip() {
+        for set in SETS {
+            for &stance in set.members() {
+                let name = set.legacy_name(stance).unwrap();
+                assert_eq!(set.from_legacy_name(name), Some(stance));
+                assert_eq!(set.project(stance), stance);
+            }
+        }
+        assert_eq!(
+            VariantSet::Bridge.from_legacy_name("Stance"),
+            Some(Stance::Unity)
+        );
+        assert_eq!(VariantSet::Sensor.from_legacy_name("Stance"), None);
+        assert_eq!(VariantSet::Crew.convert(Stance::Mode), None);
+        assert_eq!(
+            serde_json::to_string(&Stance::MetaInterphase).unwrap(),
+            "\"MetaInterphase\""
+        );
+    }
+
+    #[test]
+    fn test_lossy_projection() {
+        // Base gate first.
+        assert_eq!(VariantSet::Crew.project(Stance::Mode), Stance::Direct);
+        assert_eq!(VariantSet::Sensor.project(Stance::Fritz), Stance::Unity);
+        // Then nearest trigram.
+        assert_eq!(VariantSet::Bridge.project(Stance::Direct), Stance::Mode);
+        assert_eq!(
+            VariantSet::Bridge.project(Stance::Indirect),
+            Stance::MetaInterphase
+        );
+        assert_eq!(VariantSet::Robin.project(Stance::Earth), Stance::Iron);
+        assert_eq!(VariantSet::Robin.project(Stance::Unity), Stance::Aether);
+        for set in SETS {
+            for stance in Stance::ALL {
+                assert!(set.contains(set.project(stance)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_unified_tables() {
+        for stance in Stance::ALL {
+            assert_eq!(Stance::from_name(stance.name()), Some(stance));
+            assert!(stance.base().is_gate());
+            let gate = stance.gate();
+            let hex = format!("#{:02X}{:02X}{:02X}", gate.rgb.0, gate.rgb.1, gate.rgb.2);
+            assert_eq!(hex, gate.color);
+        }
+        // Each gate has its own trigram.
+        for (i, a) in Stance::GATES.iter().enumerate() {
+            for b in &Stance::GATES[i + 1..] {
+                assert_ne!(a.gate().bitmask, b.gate().bitmask);
+            }
+        }
+        assert_eq!(Stance::Iron.signature(), Stance::Earth.signature());
+        assert_eq!(
+            Stance::Fritz
+                .gate()
+                .trigram_match(Stance::Unity.gate().bitmask),
+            TrigramResult::Resonance
+        );
+    }
+}
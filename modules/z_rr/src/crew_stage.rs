@@ -0,0 +1,725 @@
+//! The Crew Logic Pipeline: each crew member is a stage that mutates the hypercube
+//! and reports what it did, so pipelines can be re-ordered and re-tuned from config.
+
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::railgun::Talu64;
+
+/// Everything a stage may read (or harmonize) while the rails are energized.
+pub struct StageContext<'a> {
+    pub talu64: &'a Talu64,
+    pub rng: &'a mut StdRng,
+    pub coherence: f64,
+    pub drift_accumulator: &'a mut f64,
+}
+
+/// What a stage says about its own turn: whether it fired, and its log lines.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StageOutcome {
+    pub fired: bool,
+    pub log: Vec<String>,
+}
+
+impl StageOutcome {
+    pub fn held() -> Self {
+        Self::default()
+    }
+
+    pub fn fired(line: impl Into<String>) -> Self {
+        Self {
+            fired: true,
+            log: vec![line.into()],
+        }
+    }
+
+    fn note(mut self, line: impl Into<String>) -> Self {
+        self.log.push(line.into());
+        self
+    }
+}
+
+/// A single crew transform over the hypercube buffer.
+pub trait CrewStage {
+    fn name(&self) -> &'static str;
+    fn apply(&mut self, ctx: &mut StageContext, data: &mut Vec<u8>) -> StageOutcome;
+}
+
+/// Per-stage mutation report, measured by diffing the buffer around `apply`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StageReport {
+    pub stage: String,
+    pub fired: bool,
+    pub bytes_changed: usize,
+    pub bits_flipped: u32,
+    pub len_before: usize,
+    pub len_after: usize,
+    pub log: Vec<String>,
+}
+
+impl StageReport {
+    fn measure(stage: &'static str, before: &[u8], after: &[u8], outcome: StageOutcome) -> Self {
+        let overlap = before.len().min(after.len());
+        let mut bytes_changed = before.len().abs_diff(after.len());
+        let mut bits_flipped = 0;
+        for (a, b) in before[..overlap].iter().zip(&after[..overlap]) {
+            if a != b {
+                bytes_changed += 1;
+                bits_flipped += (a ^ b).count_ones();
+            }
+        }
+        Self {
+            stage: stage.to_string(),
+            fired: outcome.fired,
+            bytes_changed,
+            bits_flipped,
+            len_before: before.len(),
+            len_after: after.len(),
+            log: outcome.log,
+        }
+    }
+}
+
+/// The result of one `ZRailgun::fire`: the coherence it saw and every stage's report.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FireReport {
+    pub coherence: f64,
+    pub energized: bool,
+    pub stages: Vec<StageReport>,
+}
+
+impl FireReport {
+    pub fn bytes_changed(&self) -> usize {
+        self.stages.iter().map(|s| s.bytes_changed).sum()
+    }
+
+    pub fn fired(&self) -> impl Iterator<Item = &StageReport> {
+        self.stages.iter().filter(|s| s.fired)
+    }
+}
+
+fn crew(ctx: &StageContext, name: &str) -> Option<(u16, u16)> {
+    ctx.talu64.get_crew_state(name)
+}
+
+/// Zoro (D2): Polarization. XORs the decay into one byte at a PHI-scaled cut.
+pub struct ZoroCut {
+    pub phase: u16,
+}
+
+impl CrewStage for ZoroCut {
+    fn name(&self) -> &'static str {
+        "zoro"
+    }
+
+    fn apply(&mut self, ctx: &mut StageContext, data: &mut Vec<u8>) -> StageOutcome {
+        match crew(ctx, "Zoro") {
+            Some((decay, phase)) if phase == self.phase && !data.is_empty() => {
+                let cut_point = (ctx.rng.gen_range(0..data.len()) as f64 / Talu64::PHI) as usize;
+                let safe_cut = cut_point.min(data.len() - 1);
+                data[safe_cut] ^= (decay % 255) as u8;
+                StageOutcome::fired(format!("⚔️  Zoro: Polarized cut at idx {}", safe_cut))
+            }
+            _ => StageOutcome::held(),
+        }
+    }
+}
+
+/// Nami (D3): Torque Shift. Rotates the buffer right once her potential energy is high enough.
+pub struct NamiTorque {
+    pub threshold: u16,
+    pub shift: usize,
+}
+
+impl CrewStage for NamiTorque {
+    fn name(&self) -> &'static str {
+        "nami"
+    }
+
+    fn apply(&mut self, ctx: &mut StageContext, data: &mut Vec<u8>) -> StageOutcome {
+        match crew(ctx, "Nami") {
+            Some((decay, _)) if decay > self.threshold && !data.is_empty() => {
+                let len = data.len();
+                data.rotate_right(self.shift % len);
+                StageOutcome::fired(format!(
+                    "🍊 Nami: Applied Torque Shift (Right {}) | Energy: {}",
+                    self.shift, decay
+                ))
+            }
+            _ => StageOutcome::held(),
+        }
+    }
+}
+
+/// Usopp (D4): Harmonic Filter. Masks every `stride`-th byte when his phase is on the beat.
+pub struct UsoppMask {
+    pub beat: u16,
+    pub stride: usize,
+    pub mask: u8,
+}
+
+impl CrewStage for UsoppMask {
+    fn name(&self) -> &'static str {
+        "usopp"
+    }
+
+    fn apply(&mut self, ctx: &mut StageContext, data: &mut Vec<u8>) -> StageOutcome {
+        match crew(ctx, "Usopp") {
+            Some((_, phase)) if phase % self.beat.max(1) == 0 => {
+                for byte in data.iter_mut().step_by(self.stride.max(1)) {
+                    *byte &= self.mask;
+                }
+                StageOutcome::fired(format!(
+                    "🤥 Usopp: Filtered Harmonic Noise (Step {}, Mask {:02X})",
+                    self.stride, self.mask
+                ))
+            }
+            _ => StageOutcome::held(),
+        }
+    }
+}
+
+/// Sanji (D5): Ground State. ORs a salt drawn from his decay into the first `ground` bytes.
+pub struct SanjiSalt {
+    pub modulus: u16,
+    pub ground: usize,
+}
+
+impl CrewStage for SanjiSalt {
+    fn name(&self) -> &'static str {
+        "sanji"
+    }
+
+    fn apply(&mut self, ctx: &mut StageContext, data: &mut Vec<u8>) -> StageOutcome {
+        match crew(ctx, "Sanji") {
+            Some((decay, _)) => {
+                let salt = (decay % self.modulus.max(1)) as u8;
+                for byte in data.iter_mut().take(self.ground) {
+                    *byte |= salt;
+                }
+                StageOutcome::fired(format!(
+                    "🍳 Sanji: Seasoned the Ground State (Salt: {:05b})",
+                    salt
+                ))
+            }
+            None => StageOutcome::held(),
+        }
+    }
+}
+
+/// Franky (D8): The Iron General. Transmutes the buffer on "Super" alignment with enough energy.
+pub struct FrankyTransmute {
+    pub beat: u16,
+    pub min_decay: u16,
+}
+
+impl FrankyTransmute {
+    /// Analog/Binary Transmutation Algorithm
+    /// "Non-uniform harmonic oscillatory motion"
+    pub fn transmute(data: &mut [u8]) {
+        for (i, byte) in data.iter_mut().enumerate() {
+            let osc = (i as f64 * Talu64::PHI).sin() * Talu64::TAU;
+            let shift = (osc.abs() * 10.0) as u8; // Non-uniform shift
+            *byte = byte.wrapping_add(shift);
+        }
+    }
+}
+
+impl CrewStage for FrankyTransmute {
+    fn name(&self) -> &'static str {
+        "franky"
+    }
+
+    fn apply(&mut self, ctx: &mut StageContext, data: &mut Vec<u8>) -> StageOutcome {
+        match crew(ctx, "Franky") {
+            Some((decay, phase)) if phase % self.beat.max(1) == 0 && decay > self.min_decay => {
+                Self::transmute(data);
+                StageOutcome::fired(
+                    "🤖 Franky: SUPER! Transmuting Analog to Binary via Non-Uniform Oscillator.",
+                )
+            }
+            _ => StageOutcome::held(),
+        }
+    }
+}
+
+/// Yamato (D12): The Guardian. Removes drift artifacts with a Planck XOR.
+pub struct YamatoPlanck {
+    pub beat: u16,
+    pub key: u8,
+}
+
+impl CrewStage for YamatoPlanck {
+    fn name(&self) -> &'static str {
+        "yamato"
+    }
+
+    fn apply(&mut self, ctx: &mut StageContext, data: &mut Vec<u8>) -> StageOutcome {
+        match crew(ctx, "Yamato") {
+            Some((_, phase)) if phase % self.beat.max(1) == 0 => {
+                for byte in data.iter_mut() {
+                    *byte ^= self.key;
+                }
+                StageOutcome::fired("👹 Yamato: Applied Spectral Refinement (Planck XOR).")
+            }
+            _ => StageOutcome::held(),
+        }
+    }
+}
+
+/// Law (D16): The ROOM. Fires when J_T outruns the drift, harmonizing it past `drift_limit`.
+pub struct LawRoom {
+    pub room: u16,
+    pub drift_limit: f64,
+}
+
+impl CrewStage for LawRoom {
+    fn name(&self) -> &'static str {
+        "law"
+    }
+
+    fn apply(&mut self, ctx: &mut StageContext, data: &mut Vec<u8>) -> StageOutcome {
+        let Some((_, phase)) = crew(ctx, "Law") else {
+            return StageOutcome::held();
+        };
+        if phase % self.room.max(1) != 0 {
+            return StageOutcome::held();
+        }
+
+        // r = phase (radius of the current harmonic cycle)
+        let j_t = Talu64::calculate_polar_moment(phase as f64);
+        if j_t <= *ctx.drift_accumulator {
+            return StageOutcome {
+                fired: false,
+                log: vec!["⚕️  Law: J_T Insufficient. Holding Signal.".into()],
+            };
+        }
+
+        let mut outcome = StageOutcome::fired(format!(
+            "⚕️  Law: ROOM Active. J_T ({:.4}) > Drift. Predictive Signal Fired.",
+            j_t
+        ));
+        if *ctx.drift_accumulator >= self.drift_limit {
+            outcome = outcome.note(format!(
+                "⚕️  Law: Harmonizing Drift Accumulator ({:.4}ms). SRAM/DRAM Re-aligned.",
+                ctx.drift_accumulator
+            ));
+            *ctx.drift_accumulator = 0.0;
+        }
+        if data.len() > Talu64::CYBIOSPHERE_UNIT as usize {
+            outcome = outcome.note("⚕️  Law: Scanning Noise Buffer for Harmony...");
+        }
+        outcome
+    }
+}
+
+/// Low coherence: flips a random number of random bits. Runs in place of the crew.
+pub struct Turbulence {
+    pub min_flips: u32,
+    pub max_flips: u32,
+}
+
+impl Default for Turbulence {
+    fn default() -> Self {
+        Self {
+            min_flips: 5,
+            max_flips: 15,
+        }
+    }
+}
+
+impl CrewStage for Turbulence {
+    fn name(&self) -> &'static str {
+        "turbulence"
+    }
+
+    fn apply(&mut self, ctx: &mut StageContext, data: &mut Vec<u8>) -> StageOutcome {
+        if data.is_empty() || self.max_flips <= self.min_flips {
+            return StageOutcome::held();
+        }
+        let turbulence = ctx.rng.gen_range(self.min_flips..self.max_flips);
+        for _ in 0..turbulence {
+            let idx = ctx.rng.gen_range(0..data.len());
+            data[idx] ^= 1 << ctx.rng.gen_range(0..8);
+        }
+        StageOutcome::fired(format!("🌪️  Turbulence: {} raw bit flips", turbulence))
+    }
+}
+
+fn yes() -> bool {
+    true
+}
+
+/// One pipeline slot as written in config: the stage, its parameters, and whether it runs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StageEntry {
+    #[serde(default = "yes")]
+    pub enabled: bool,
+    #[serde(flatten)]
+    pub stage: StageConfig,
+}
+
+/// Stage parameters. Omitted fields take the values the Railgun has always used.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "stage", rename_all = "snake_case")]
+pub enum StageConfig {
+    Zoro {
+        #[serde(default = "StageConfig::zoro_phase")]
+        phase: u16,
+    },
+    Nami {
+        #[serde(default = "StageConfig::nami_threshold")]
+        threshold: u16,
+        #[serde(default = "StageConfig::nami_shift")]
+        shift: usize,
+    },
+    Usopp {
+        #[serde(default = "StageConfig::usopp_beat")]
+        beat: u16,
+        #[serde(default = "StageConfig::usopp_stride")]
+        stride: usize,
+        #[serde(default = "StageConfig::usopp_mask")]
+        mask: u8,
+    },
+    Sanji {
+        #[serde(default = "StageConfig::sanji_modulus")]
+        modulus: u16,
+        #[serde(default = "StageConfig::sanji_ground")]
+        ground: usize,
+    },
+    Franky {
+        #[serde(default = "StageConfig::franky_beat")]
+        beat: u16,
+        #[serde(default = "StageConfig::franky_min_decay")]
+        min_decay: u16,
+    },
+    Yamato {
+        #[serde(default = "StageConfig::yamato_beat")]
+        beat: u16,
+        #[serde(default = "StageConfig::yamato_key")]
+        key: u8,
+    },
+    Law {
+        #[serde(default = "StageConfig::law_room")]
+        room: u16,
+        #[serde(default = "StageConfig::law_drift_limit")]
+        drift_limit: f64,
+    },
+}
+
+impl StageConfig {
+    fn zoro_phase() -> u16 {
+        1
+    }
+    fn nami_threshold() -> u16 {
+        10000
+    }
+    fn nami_shift() -> usize {
+        1
+    }
+    fn usopp_beat() -> u16 {
+        4
+    }
+    fn usopp_stride() -> usize {
+        4
+    }
+    fn usopp_mask() -> u8 {
+        0xF0 // High nibble only, filter low-end noise
+    }
+    fn sanji_modulus() -> u16 {
+        32 // 5 bits of flavor
+    }
+    fn sanji_ground() -> usize {
+        5
+    }
+    fn franky_beat() -> u16 {
+        8
+    }
+    fn franky_min_decay() -> u16 {
+        1000
+    }
+    fn yamato_beat() -> u16 {
+        12
+    }
+    fn yamato_key() -> u8 {
+        Talu64::PLANCK as u8
+    }
+    fn law_room() -> u16 {
+        Talu64::CYBIOSPHERE_UNIT as u16
+    }
+    fn law_drift_limit() -> f64 {
+        100.0
+    }
+
+    /// The canonical crew, in firing order, with default parameters.
+    pub fn crew() -> Vec<StageConfig> {
+        vec![
+            StageConfig::Zoro {
+                phase: Self::zoro_phase(),
+            },
+            StageConfig::Nami {
+                threshold: Self::nami_threshold(),
+                shift: Self::nami_shift(),
+            },
+            StageConfig::Usopp {
+                beat: Self::usopp_beat(),
+                stride: Self::usopp_stride(),
+                mask: Self::usopp_mask(),
+            },
+            StageConfig::Sanji {
+                modulus: Self::sanji_modulus(),
+                ground: Self::sanji_ground(),
+            },
+            StageConfig::Franky {
+                beat: Self::franky_beat(),
+                min_decay: Self::franky_min_decay(),
+            },
+            StageConfig::Yamato {
+                beat: Self::yamato_beat(),
+                key: Self::yamato_key(),
+            },
+            StageConfig::Law {
+                room: Self::law_room(),
+                drift_limit: Self::law_drift_limit(),
+            },
+        ]
+    }
+
+    pub fn build(&self) -> Box<dyn CrewStage> {
+        match *self {
+            StageConfig::Zoro { phase } => Box::new(ZoroCut { phase }),
+            StageConfig::Nami { threshold, shift } => Box::new(NamiTorque { threshold, shift }),
+            StageConfig::Usopp { beat, stride, mask } => Box::new(UsoppMask { beat, stride, mask }),
+            StageConfig::Sanji { modulus, ground } => Box::new(SanjiSalt { modulus, ground }),
+            StageConfig::Franky { beat, min_decay } => {
+                Box::new(FrankyTransmute { beat, min_decay })
+            }
+            StageConfig::Yamato { beat, key } => Box::new(YamatoPlanck { beat, key }),
+            StageConfig::Law { room, drift_limit } => Box::new(LawRoom { room, drift_limit }),
+        }
+    }
+}
+
+/// The pipeline as config: an ordered list of stages. Loads from JSON, e.g.
+/// `{"stages": [{"stage": "sanji", "ground": 8}, {"stage": "zoro", "enabled": false}]}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PipelineConfig {
+    pub stages: Vec<StageEntry>,
+}
+
+impl Default for PipelineConfig {
+    fn default() -> Self {
+        Self {
+            stages: StageConfig::crew()
+                .into_iter()
+                .map(|stage| StageEntry {
+                    enabled: true,
+                    stage,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl PipelineConfig {
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let json = std::fs::read_to_string(path)?;
+        Self::from_json(&json).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+
+    pub fn build(&self) -> CrewPipeline {
+        CrewPipeline {
+            stages: self
+                .stages
+                .iter()
+                .filter(|entry| entry.enabled)
+                .map(|entry| entry.stage.build())
+                .collect(),
+        }
+    }
+}
+
+/// An ordered run of crew stages.
+pub struct CrewPipeline {
+    stages: Vec<Box<dyn CrewStage>>,
+}
+
+impl Default for CrewPipeline {
+    fn default() -> Self {
+        PipelineConfig::default().build()
+    }
+}
+
+impl CrewPipeline {
+    pub fn new(stages: Vec<Box<dyn CrewStage>>) -> Self {
+        Self { stages }
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.stages.iter().map(|s| s.name()).collect()
+    }
+
+    pub fn run(&mut self, ctx: &mut StageContext, data: &mut Vec<u8>) -> Vec<StageReport> {
+        self.stages
+            .iter_mut()
+            .map(|stage| run_stage(stage.as_mut(), ctx, data))
+            .collect()
+    }
+}
+
+/// Applies one stage, prints its log, and measures what it changed.
+pub fn run_stage(
+    stage: &mut dyn CrewStage,
+    ctx: &mut StageContext,
+    data: &mut Vec<u8>,
+) -> StageReport {
+    let before = data.clone();
+    let outcome = stage.apply(ctx, data);
+    for line in &outcome.log {
+        println!("      {}", line);
+    }
+    StageReport::measure(stage.name(), &before, data, outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A Talu64 with a single crew channel set to `(decay, phase)`.
+    fn talu_with(index: usize, decay: u16, phase: u16) -> Talu64 {
+        let mut channels = [0u32; 16];
+        channels[index] = ((decay as u32) << 16) | phase as u32;
+        Talu64 { channels }
+    }
+
+    fn apply(
+        stage: &mut dyn CrewStage,
+        talu64: &Talu64,
+        drift: &mut f64,
+        data: &mut Vec<u8>,
+    ) -> StageReport {
+        let mut rng = StdRng::seed_from_u64(1337);
+        let mut ctx = StageContext {
+            talu64,
+            rng: &mut rng,
+            coherence: 2.0,
+            drift_accumulator: drift,
+        };
+        run_stage(stage, &mut ctx, data)
+    }
+
+    #[test]
+    fn test_stages_report_their_mutations() {
+        let mut drift = 0.0;
+
+        let mut data = vec![0xFF; 8];
+        let report = apply(
+            &mut UsoppMask {
+                beat: 4,
+                stride: 4,
+                mask: 0xF0,
+            },
+            &talu_with(3, 0, 8),
+            &mut drift,
+            &mut data,
+        );
+        assert!(report.fired);
+        assert_eq!(data, [0xF0, 0xFF, 0xFF, 0xFF, 0xF0, 0xFF, 0xFF, 0xFF]);
+        assert_eq!((report.bytes_changed, report.bits_flipped), (2, 8));
+
+        let mut data = vec![1, 2, 3];
+        let report = apply(
+            &mut NamiTorque {
+                threshold: 10000,
+                shift: 1,
+            },
+            &talu_with(2, 20000, 0),
+            &mut drift,
+            &mut data,
+        );
+        assert_eq!(data, [3, 1, 2]);
+        assert_eq!(report.bytes_changed, 3);
+
+        // Off the beat, Yamato holds and the buffer is untouched.
+        let mut data = vec![9; 4];
+        let report = apply(
+            &mut YamatoPlanck { beat: 12, key: 6 },
+            &talu_with(12, 0, 5),
+            &mut drift,
+            &mut data,
+        );
+        assert!(!report.fired);
+        assert_eq!(report.bytes_changed, 0);
+        assert_eq!(data, [9; 4]);
+    }
+
+    #[test]
+    fn test_law_harmonizes_drift_past_the_limit() {
+        let talu64 = talu_with(15, 0, 512);
+        let mut law = LawRoom {
+            room: 512,
+            drift_limit: 100.0,
+        };
+
+        let mut drift = 120.0;
+        let report = apply(&mut law, &talu64, &mut drift, &mut vec![0; 4]);
+        assert!(report.fired);
+        assert_eq!(drift, 0.0);
+        assert_eq!(report.bytes_changed, 0);
+
+        // J_T = TAU * 512 ~ 3217; a larger drift holds the signal.
+        let mut drift = 5000.0;
+        let report = apply(&mut law, &talu64, &mut drift, &mut vec![0; 4]);
+        assert!(!report.fired);
+        assert_eq!(drift, 5000.0);
+    }
+
+    #[test]
+    fn test_pipeline_config_orders_and_disables_stages() {
+        assert_eq!(
+            PipelineConfig::default().build().names(),
+            ["zoro", "nami", "usopp", "sanji", "franky", "yamato", "law"]
+        );
+
+        let config = PipelineConfig::from_json(
+            r#"{"stages": [
+                {"stage": "sanji", "ground": 2},
+                {"stage": "zoro", "enabled": false},
+                {"stage": "usopp"}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.stages[0].stage,
+            StageConfig::Sanji {
+                modulus: 32,
+                ground: 2
+            }
+        );
+        let mut pipeline = config.build();
+        assert_eq!(pipeline.names(), ["sanji", "usopp"]);
+
+        // Sanji (decay 7) salts two bytes, then Usopp (phase 0) masks every 4th.
+        let mut channels = [0u32; 16];
+        channels[4] = 7 << 16;
+        let talu64 = Talu64 { channels };
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut drift = 0.0;
+        let mut ctx = StageContext {
+            talu64: &talu64,
+            rng: &mut rng,
+            coherence: 2.0,
+            drift_accumulator: &mut drift,
+        };
+        let mut data = vec![0x10; 5];
+        let reports = pipeline.run(&mut ctx, &mut data);
+        assert_eq!(data, [0x10, 0x17, 0x10, 0x10, 0x10]);
+        assert_eq!(reports[0].bytes_changed, 2);
+        assert_eq!(reports[1].bytes_changed, 1);
+    }
+}
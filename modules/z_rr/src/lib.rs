@@ -1,4 +1,11 @@
+pub mod crew_stage;
+pub mod mission;
 pub mod railgun;
+pub mod rainbow;
+pub mod shm_reader;
 pub mod shm_writer;
+pub mod survivor_store;
+pub mod validate;
 
+pub use crew_stage::{CrewPipeline, CrewStage, FireReport, PipelineConfig, StageReport};
 pub use railgun::{Talu64, ZRailgun};
@@ -1,57 +1,302 @@
+use coherence_feed::CoherenceFeed;
 use std::env;
-use std::fs::File;
-use std::io::Read;
-use std::thread;
-use std::time::Duration;
-use z_rr::railgun::{dock_survivors, listener_collapse, ZRailgun};
+use std::fs;
+use z_rr::crew_stage::PipelineConfig;
+use z_rr::mission::{run_cycles, CycleLog, MissionConfig, RunReport, SweepReport};
+use z_rr::railgun::{dock_survivors, CoherencePolicy, ZRailgun};
+use z_rr::survivor_store::{sha256_hex, SurvivorStore};
+use z_rr::validate::ValidatorRegistry;
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() < 2 {
-        println!("Usage: zrr_core <target_zip>");
-        return;
-    }
+fn usage() {
+    println!("Usage: zrr_core <target> [pipeline.json] [options]");
+    println!("       zrr_core verify");
+    println!("       zrr_core lineage <hash>");
+    println!();
+    println!("Options:");
+    println!("  --cycles N         shots per run (default 16)");
+    println!("  --seed N           entropy seed (default 1337)");
+    println!("  --sleep MS         delay between cycles (default 50)");
+    println!(
+        "  --out DIR          where run reports go (default {})",
+        z_rr::mission::DEFAULT_OUT_DIR
+    );
+    println!("  --validator NAME   structure to preserve (default: detected)");
+    println!("  --coherence X      fixed wave coherence instead of the Ripple Tank's");
+    println!("  --sweep A..B       run every seed in A..B (or A..=B) and aggregate");
+    println!("  --no-dock          leave survivors out of Amazon Lily");
+    println!("  --refuse-stale     refuse to fire without fresh wave coherence");
+    println!(
+        "  --max-age MS       oldest coherence still fresh (default {})",
+        coherence_feed::DEFAULT_MAX_AGE.as_millis()
+    );
+    println!(
+        "Survivors dock in $AMAZON_LILY_PATH (default {})",
+        z_rr::survivor_store::DEFAULT_ROOT
+    );
+}
 
-    let target_path = &args[1];
-    println!("🎯 Target: {}", target_path);
+fn open_store() -> Option<SurvivorStore> {
+    let root = SurvivorStore::default_root();
+    match SurvivorStore::open(&root) {
+        Ok(store) => Some(store),
+        Err(e) => {
+            println!("❌ Cannot open Amazon Lily at {}: {}", root.display(), e);
+            None
+        }
+    }
+}
 
-    let mut stack: Vec<Vec<u8>> = Vec::new();
-    match File::open(target_path) {
-        Ok(mut f) => {
-            let mut baseline = Vec::new();
-            f.read_to_end(&mut baseline).unwrap();
-            println!("📥 Baseline Loaded: {} bytes", baseline.len());
-            stack.push(baseline);
+/// Re-hashes every docked cube and checks every parent link.
+fn verify() -> bool {
+    let Some(store) = open_store() else {
+        return false;
+    };
+    let report = match store.verify() {
+        Ok(report) => report,
+        Err(e) => {
+            println!("❌ Verify failed: {}", e);
+            return false;
         }
+    };
+    println!(
+        "🔎 Amazon Lily: {} cubes checked in {}",
+        report.checked,
+        store.root().display()
+    );
+    for hash in &report.corrupt {
+        println!("   💥 corrupt   {}", hash);
+    }
+    for hash in &report.missing {
+        println!("   🕳️  missing   {}", hash);
+    }
+    for hash in &report.orphaned {
+        println!("   🧬 orphaned  {}", hash);
+    }
+    for path in &report.strays {
+        println!("   ❓ stray     {}", path.display());
+    }
+    if report.is_clean() {
+        println!("✅ Calm Belt clear: every cube matches its hash.");
+    }
+    report.is_clean()
+}
+
+/// Traces a survivor back to its baseline.
+fn lineage(prefix: &str) -> bool {
+    let Some(store) = open_store() else {
+        return false;
+    };
+    let Some(hash) = store.resolve(prefix) else {
+        println!("❌ No unique survivor matches {}", prefix);
+        return false;
+    };
+    for (depth, record) in store.lineage(hash).iter().enumerate() {
+        let d = &record.docking;
+        let origin = match d.cycle {
+            Some(cycle) => format!("cycle {:>2}, seed {}", cycle, d.seed),
+            None => format!("baseline, seed {}", d.seed),
+        };
+        let mutated = d
+            .mutation
+            .as_ref()
+            .map(|m| format!(", {} bytes mutated", m.bytes_changed()))
+            .unwrap_or_default();
+        println!(
+            "{}{} {} ({} bytes, {}{})",
+            "  ".repeat(depth),
+            if depth == 0 { "🧊" } else { "↳" },
+            &record.hash[..16],
+            record.size,
+            origin,
+            mutated
+        );
+    }
+    true
+}
+
+/// Runs the mission once per seed, writing a report for each (and a sweep summary).
+fn launch(config: &MissionConfig) -> bool {
+    println!("🎯 Target: {}", config.target.display());
+
+    let baseline = match fs::read(&config.target) {
+        Ok(data) => data,
         Err(e) => {
             println!("❌ Error opening file: {}", e);
-            return;
+            return false;
         }
     };
+    println!("📥 Baseline Loaded: {} bytes", baseline.len());
 
-    let mut advertiser = ZRailgun::new(1337);
+    // The structure the run must preserve, chosen from the target's file type
+    let registry = ValidatorRegistry::default();
+    let validator = match &config.validator {
+        Some(name) => match registry.get(name) {
+            Some(validator) => validator,
+            None => {
+                println!(
+                    "❌ Unknown validator {} (known: {})",
+                    name,
+                    registry.names().join(", ")
+                );
+                return false;
+            }
+        },
+        None => registry.detect(Some(&config.target), &baseline),
+    };
+    let baseline_check = validator.validate(&baseline);
+    println!(
+        "🔬 Listener: {} ({:?}, {} intact: {})",
+        validator.name(),
+        baseline_check.grade,
+        baseline_check.unit,
+        baseline_check.intact
+    );
 
-    for i in 0..16 {
-        println!("\n🔥 Railgun Cycle #{}", i);
-        let mut hypercube_state = stack.last().unwrap().clone();
+    let seeds = match config.seeds() {
+        Ok(seeds) => seeds,
+        Err(e) => {
+            println!("❌ {}", e);
+            return false;
+        }
+    };
+    let mut advertiser = ZRailgun::new(seeds.start);
+    if let Some(config_path) = &config.pipeline {
+        match PipelineConfig::load(config_path) {
+            Ok(pipeline) => {
+                let pipeline = pipeline.build();
+                println!("🧭 Crew Pipeline: {}", pipeline.names().join(" -> "));
+                advertiser.set_pipeline(pipeline);
+            }
+            Err(e) => {
+                println!("❌ Error loading pipeline {}: {}", config_path.display(), e);
+                return false;
+            }
+        }
+    }
+    if let Some(coherence) = config.coherence {
+        println!("🎚️  Coherence pinned at {:.4}", coherence);
+    }
+    advertiser.set_coherence_override(config.coherence);
+    if let Some(max_age) = config.max_coherence_age {
+        advertiser.set_coherence_feed(CoherenceFeed::default().with_max_age(max_age));
+    }
+    if config.refuse_stale {
+        advertiser.set_coherence_policy(CoherencePolicy::Strict);
+    }
+    let pipeline: Vec<String> = advertiser
+        .pipeline()
+        .names()
+        .into_iter()
+        .map(String::from)
+        .collect();
+
+    let mut store = if config.dock { open_store() } else { None };
+    let mut runs = Vec::new();
+    for seed in seeds.clone() {
+        if config.sweep.is_some() {
+            println!("\n🌊 Sweep Seed {}", seed);
+        }
+        advertiser.reseed(seed);
+        let CycleLog {
+            cycles,
+            stack,
+            refused,
+        } = run_cycles(
+            &mut advertiser,
+            validator,
+            baseline.clone(),
+            config.cycles,
+            config.sleep,
+        );
 
-        advertiser.fire(&mut hypercube_state);
-        advertiser.entropy_seed += 1;
+        match &refused {
+            Some(e) => println!(
+                "\n🛑 Z-RR Mission Aborted after {} cycles ({}). Survivors: {}",
+                cycles.len(),
+                e,
+                stack.len()
+            ),
+            None => println!("\n🏁 Z-RR Mission Complete. Survivors: {}", stack.len()),
+        }
+        if let Some(store) = store.as_mut() {
+            dock_survivors(store, &stack);
+        }
 
-        // Refresh Talu64 for next cycle (simulating time passing)
-        advertiser.realign();
+        let mut report = RunReport::new(
+            config,
+            seed,
+            validator.name(),
+            pipeline.clone(),
+            sha256_hex(&baseline),
+            cycles,
+        );
+        report.refused = refused.map(|e| e.to_string());
+        let path = match report.write(&config.out_dir) {
+            Ok(path) => {
+                println!("   🧾 Report: {}", path.display());
+                Some(path)
+            }
+            Err(e) => {
+                println!("   ❌ Failed to write report: {}", e);
+                None
+            }
+        };
+        runs.push((report, path));
+    }
 
-        if listener_collapse(&hypercube_state) {
-            println!("✅ Singularity Achieved: Hypercube Collapsed.");
-            stack.push(hypercube_state);
-        } else {
-            println!("💥 Collapse Failed.");
+    if config.sweep.is_some() {
+        let sweep = SweepReport::new(seeds, &runs);
+        println!(
+            "\n📊 Sweep {}..{}: {}/{} cycles survived ({:.1}%), per seed {:.1}%..{:.1}%, {} flawless",
+            sweep.seeds.start,
+            sweep.seeds.end,
+            sweep.total_survivors,
+            sweep.total_cycles,
+            sweep.survival_rate * 100.0,
+            sweep.min_rate * 100.0,
+            sweep.max_rate * 100.0,
+            sweep.flawless_runs
+        );
+        match sweep.write(&config.out_dir) {
+            Ok(path) => println!("   🧾 Sweep Report: {}", path.display()),
+            Err(e) => {
+                println!("   ❌ Failed to write sweep report: {}", e);
+                return false;
+            }
         }
+    }
+    runs.iter()
+        .all(|(report, path)| path.is_some() && report.refused.is_none())
+}
 
-        // NPU Synchronization Delay (Simulating Latency)
-        thread::sleep(Duration::from_millis(50));
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 2 {
+        usage();
+        return;
+    }
+    match args[1].as_str() {
+        "verify" => std::process::exit(if verify() { 0 } else { 1 }),
+        "lineage" => match args.get(2) {
+            Some(hash) => std::process::exit(if lineage(hash) { 0 } else { 1 }),
+            None => {
+                usage();
+                return;
+            }
+        },
+        "-h" | "--help" => {
+            usage();
+            return;
+        }
+        _ => {}
     }
 
-    println!("\n🏁 Z-RR Mission Complete. Survivors: {}", stack.len());
-    dock_survivors(&stack, 1337);
+    match MissionConfig::from_args(&args[1..]) {
+        Ok(config) => std::process::exit(if launch(&config) { 0 } else { 1 }),
+        Err(e) => {
+            println!("❌ {}", e);
+            usage();
+            std::process::exit(2);
+        }
+    }
 }
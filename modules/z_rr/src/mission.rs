@@ -0,0 +1,665 @@
+//! A zrr_core mission: fire the railgun at a target for a number of cycles, grade every
+//! shot and record what happened as JSON. A sweep repeats the mission over a range of
+//! seeds and aggregates how often the structure survived.
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use crate::railgun::{FireError, ZRailgun};
+use crate::survivor_store::{sha256_hex, Docking, Survivor};
+use crate::validate::{Grade, StructureValidator};
+
+/// Where run reports land unless `--out` says otherwise.
+pub const DEFAULT_OUT_DIR: &str = "zrr_runs";
+
+/// Everything a zrr_core invocation can be told on the command line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MissionConfig {
+    pub target: PathBuf,
+    pub pipeline: Option<PathBuf>,
+    pub seed: u64,
+    pub cycles: u32,
+    /// NPU synchronization delay between cycles.
+    pub sleep: Duration,
+    pub out_dir: PathBuf,
+    /// Validator name; detected from the target when unset.
+    pub validator: Option<String>,
+    /// Fixed coherence instead of the Ripple Tank's.
+    pub coherence: Option<f64>,
+    /// Seeds to sweep; replaces `seed`.
+    pub sweep: Option<Range<u64>>,
+    /// Dock survivors in Amazon Lily.
+    pub dock: bool,
+    /// Refuse to fire on stale or missing coherence instead of running dormant.
+    pub refuse_stale: bool,
+    /// Oldest coherence sample still considered fresh.
+    pub max_coherence_age: Option<Duration>,
+}
+
+impl MissionConfig {
+    pub fn new(target: impl Into<PathBuf>) -> Self {
+        Self {
+            target: target.into(),
+            pipeline: None,
+            seed: 1337,
+            cycles: 16,
+            sleep: Duration::from_millis(50),
+            out_dir: PathBuf::from(DEFAULT_OUT_DIR),
+            validator: None,
+            coherence: None,
+            sweep: None,
+            dock: true,
+            refuse_stale: false,
+            max_coherence_age: None,
+        }
+    }
+
+    /// Parses `<target> [pipeline.json] [options]` (arguments after the program name).
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let mut positional = Vec::new();
+        let mut config = Self::new("");
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value =
+                |name: &str| args.next().ok_or_else(|| format!("{} needs a value", name));
+            match arg.as_str() {
+                "--cycles" => config.cycles = parse(arg, value(arg)?)?,
+                "--seed" => config.seed = parse(arg, value(arg)?)?,
+                "--sleep" => config.sleep = Duration::from_millis(parse(arg, value(arg)?)?),
+                "--out" => config.out_dir = PathBuf::from(value(arg)?),
+                "--validator" => config.validator = Some(value(arg)?.clone()),
+                "--coherence" => config.coherence = Some(parse(arg, value(arg)?)?),
+                "--sweep" => config.sweep = Some(parse_seeds(value(arg)?)?),
+                "--no-dock" => config.dock = false,
+                "--refuse-stale" => config.refuse_stale = true,
+                "--max-age" => {
+                    config.max_coherence_age = Some(Duration::from_millis(parse(arg, value(arg)?)?))
+                }
+                flag if flag.starts_with("--") => return Err(format!("unknown option {}", flag)),
+                _ => positional.push(arg),
+            }
+        }
+
+        let mut positional = positional.into_iter();
+        config.target = positional
+            .next()
+            .map(PathBuf::from)
+            .ok_or("missing target")?;
+        config.pipeline = positional.next().map(PathBuf::from);
+        if let Some(extra) = positional.next() {
+            return Err(format!("unexpected argument {}", extra));
+        }
+        config.seeds()?;
+        Ok(config)
+    }
+
+    /// The seeds this mission runs: the sweep, or just `seed`. Each run's shots take the
+    /// `cycles` seeds after its own, so the last run's seed plus `cycles` must fit a `u64`.
+    pub fn seeds(&self) -> Result<Range<u64>, String> {
+        let seeds = match &self.sweep {
+            Some(sweep) => sweep.clone(),
+            None => {
+                let end = self.seed.checked_add(1).ok_or_else(|| {
+                    format!("--seed {} is past the last seed a run can take", self.seed)
+                })?;
+                self.seed..end
+            }
+        };
+        let last = seeds.end - 1;
+        if last.checked_add(u64::from(self.cycles)).is_none() {
+            return Err(format!(
+                "seed {} with {} cycles runs past the last seed",
+                last, self.cycles
+            ));
+        }
+        Ok(seeds)
+    }
+}
+
+fn parse<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("{} cannot take {:?}", name, value))
+}
+
+/// `start..end` (half-open) or `start..=end`.
+pub fn parse_seeds(spec: &str) -> Result<Range<u64>, String> {
+    let bad = || format!("--sweep wants start..end or start..=end, got {:?}", spec);
+    let (start, end) = spec.split_once("..").ok_or_else(bad)?;
+    let start: u64 = start.parse().map_err(|_| bad())?;
+    let end: u64 = match end.strip_prefix('=') {
+        Some(last) => last
+            .parse::<u64>()
+            .map_err(|_| bad())?
+            .checked_add(1)
+            .ok_or_else(|| format!("--sweep {} runs past the last seed", spec))?,
+        None => end.parse().map_err(|_| bad())?,
+    };
+    if start >= end {
+        return Err(format!("--sweep {} is empty", spec));
+    }
+    Ok(start..end)
+}
+
+/// One railgun shot and its verdict.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CycleReport {
+    pub cycle: u32,
+    pub seed: u64,
+    pub coherence: f64,
+    pub energized: bool,
+    pub stages_fired: Vec<String>,
+    pub bytes_changed: usize,
+    pub grade: Grade,
+    pub collapsed: bool,
+    pub reason: Option<String>,
+    /// Hash of the cube that was fired at.
+    pub parent: String,
+    /// Hash of the mutated cube, if it survived.
+    pub survivor: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunReport {
+    pub target: String,
+    pub validator: String,
+    pub seed: u64,
+    pub coherence_override: Option<f64>,
+    pub pipeline: Vec<String>,
+    pub started_at: String,
+    pub baseline: String,
+    pub cycles: Vec<CycleReport>,
+    pub survivors: usize,
+    pub survival_rate: f64,
+    /// Why the rails stopped early, if they did.
+    pub refused: Option<String>,
+}
+
+impl RunReport {
+    pub fn new(
+        config: &MissionConfig,
+        seed: u64,
+        validator: &str,
+        pipeline: Vec<String>,
+        baseline: String,
+        cycles: Vec<CycleReport>,
+    ) -> Self {
+        let survivors = cycles.iter().filter(|c| !c.collapsed).count();
+        Self {
+            target: config.target.display().to_string(),
+            validator: validator.to_string(),
+            seed,
+            coherence_override: config.coherence,
+            pipeline,
+            started_at: Utc::now().to_rfc3339(),
+            baseline,
+            survival_rate: rate(survivors, cycles.len()),
+            survivors,
+            cycles,
+            refused: None,
+        }
+    }
+
+    /// Writes `run_<seed>_<time>.json` into `dir`.
+    pub fn write(&self, dir: &Path) -> io::Result<PathBuf> {
+        let stamp = Utc::now().format("%Y%m%d-%H%M%S%3f");
+        write_json(&dir.join(format!("run_{}_{}.json", self.seed, stamp)), self)
+    }
+}
+
+/// One seed of a sweep.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SweepRun {
+    pub seed: u64,
+    pub cycles: usize,
+    pub survivors: usize,
+    pub survival_rate: f64,
+    pub report: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SweepReport {
+    pub target: String,
+    pub validator: String,
+    pub seeds: Range<u64>,
+    pub runs: Vec<SweepRun>,
+    pub total_cycles: usize,
+    pub total_survivors: usize,
+    /// Survivors over cycles, across every run.
+    pub survival_rate: f64,
+    pub min_rate: f64,
+    pub max_rate: f64,
+    /// Runs in which every cycle survived.
+    pub flawless_runs: usize,
+}
+
+impl SweepReport {
+    pub fn new(seeds: Range<u64>, reports: &[(RunReport, Option<PathBuf>)]) -> Self {
+        let (target, validator) = reports
+            .first()
+            .map(|(r, _)| (r.target.clone(), r.validator.clone()))
+            .unwrap_or_default();
+        let runs: Vec<SweepRun> = reports
+            .iter()
+            .map(|(report, path)| SweepRun {
+                seed: report.seed,
+                cycles: report.cycles.len(),
+                survivors: report.survivors,
+                survival_rate: report.survival_rate,
+                report: path.clone(),
+            })
+            .collect();
+        let total_cycles = runs.iter().map(|r| r.cycles).sum();
+        let total_survivors = runs.iter().map(|r| r.survivors).sum();
+        let rates = runs.iter().map(|r| r.survival_rate);
+        Self {
+            target,
+            validator,
+            seeds,
+            total_cycles,
+            total_survivors,
+            survival_rate: rate(total_survivors, total_cycles),
+            min_rate: rates.clone().reduce(f64::min).unwrap_or(0.0),
+            max_rate: rates.reduce(f64::max).unwrap_or(0.0),
+            flawless_runs: runs.iter().filter(|r| r.survivors == r.cycles).count(),
+            runs,
+        }
+    }
+
+    /// Writes `sweep_<start>-<end>_<time>.json` into `dir`.
+    pub fn write(&self, dir: &Path) -> io::Result<PathBuf> {
+        let stamp = Utc::now().format("%Y%m%d-%H%M%S%3f");
+        let name = format!(
+            "sweep_{}-{}_{}.json",
+            self.seeds.start, self.seeds.end, stamp
+        );
+        write_json(&dir.join(name), self)
+    }
+}
+
+fn rate(hits: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        hits as f64 / total as f64
+    }
+}
+
+fn write_json<T: Serialize>(path: &Path, value: &T) -> io::Result<PathBuf> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let json = serde_json::to_vec_pretty(value)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    fs::write(path, json)?;
+    Ok(path.to_path_buf())
+}
+
+/// What [`run_cycles`] fired and kept.
+pub struct CycleLog {
+    pub cycles: Vec<CycleReport>,
+    /// Survivors, baseline first.
+    pub stack: Vec<Survivor>,
+    /// Set when the rails refused a shot; the run stops there.
+    pub refused: Option<FireError>,
+}
+
+/// Fires up to `cycles` shots starting from `baseline`, each at the latest survivor.
+pub fn run_cycles(
+    railgun: &mut ZRailgun,
+    validator: &dyn StructureValidator,
+    baseline: Vec<u8>,
+    cycles: u32,
+    sleep: Duration,
+) -> CycleLog {
+    let mut stack = vec![Survivor {
+        data: baseline,
+        docking: Docking::baseline(railgun.entropy_seed),
+    }];
+    let mut reports = Vec::new();
+
+    for i in 0..cycles {
+        println!("\n🔥 Railgun Cycle #{}", i);
+        let parent = stack.last().unwrap();
+        let parent_hash = parent.hash();
+        let mut hypercube_state = parent.data.clone();
+
+        let shot_seed = railgun.entropy_seed;
+        let report = match railgun.fire(&mut hypercube_state) {
+            Ok(report) => report,
+            Err(e) => {
+                return CycleLog {
+                    cycles: reports,
+                    stack,
+                    refused: Some(e),
+                }
+            }
+        };
+        let fired: Vec<String> = report
+            .fired()
+            .map(|s| format!("{}({}B)", s.stage, s.bytes_changed))
+            .collect();
+        println!(
+            "   📋 Stages fired: [{}] | {} bytes mutated",
+            fired.join(", "),
+            report.bytes_changed()
+        );
+        railgun.entropy_seed = railgun.entropy_seed.wrapping_add(1);
+
+        // Refresh Talu64 for next cycle (simulating time passing)
+        railgun.realign();
+
+        let assessment = validator.validate(&hypercube_state);
+        let reason = assessment.reason.as_deref().unwrap_or("");
+        match assessment.grade {
+            Grade::Valid => println!("✅ Singularity Achieved: Hypercube Collapsed."),
+            Grade::Partial => println!(
+                "🩹 Partial Collapse: {} salvageable {}/{} ({})",
+                assessment.unit,
+                assessment.intact,
+                assessment.intact + assessment.damaged,
+                reason
+            ),
+            Grade::Collapsed => println!("💥 Collapse Failed: {}", reason),
+        }
+
+        let survivor = assessment.survived().then(|| sha256_hex(&hypercube_state));
+        reports.push(CycleReport {
+            cycle: i,
+            seed: shot_seed,
+            coherence: report.coherence,
+            energized: report.energized,
+            stages_fired: report.fired().map(|s| s.stage.clone()).collect(),
+            bytes_changed: report.bytes_changed(),
+            grade: assessment.grade,
+            collapsed: !assessment.survived(),
+            reason: assessment.reason.clone(),
+            parent: parent_hash.clone(),
+            survivor: survivor.clone(),
+        });
+        if survivor.is_some() {
+            stack.push(Survivor {
+                data: hypercube_state,
+                docking: Docking {
+                    seed: shot_seed,
+                    cycle: Some(i),
+                    parent: Some(parent_hash),
+                    grade: Some(assessment.grade),
+                    mutation: Some(report),
+                },
+            });
+        }
+
+        // NPU Synchronization Delay (Simulating Latency)
+        if !sleep.is_zero() {
+            thread::sleep(sleep);
+        }
+    }
+    CycleLog {
+        cycles: reports,
+        stack,
+        refused: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::railgun::CoherencePolicy;
+    use crate::shm_reader::D16ShmReader;
+    use crate::shm_writer::{D16ShmWriter, ShmConfig};
+    use crate::validate::JsonValidator;
+    use coherence_feed::{CoherenceFeed, CoherencePublisher, CoherenceSample};
+    use rp1_rio::RecordingBank;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_parses_options_around_positionals() {
+        let config = MissionConfig::from_args(&args(
+            "cube.zip --cycles 4 crew.json --seed 7 --sleep 0 --out runs --validator zip \
+             --coherence 1.5 --no-dock --refuse-stale --max-age 250",
+        ))
+        .unwrap();
+        assert_eq!(config.target, PathBuf::from("cube.zip"));
+        assert_eq!(config.pipeline, Some(PathBuf::from("crew.json")));
+        assert_eq!((config.cycles, config.seed), (4, 7));
+        assert_eq!(config.sleep, Duration::ZERO);
+        assert_eq!(config.out_dir, PathBuf::from("runs"));
+        assert_eq!(config.validator.as_deref(), Some("zip"));
+        assert_eq!(config.coherence, Some(1.5));
+        assert!(!config.dock);
+        assert!(config.refuse_stale);
+        assert_eq!(config.max_coherence_age, Some(Duration::from_millis(250)));
+        assert_eq!(config.seeds(), Ok(7..8));
+
+        let defaults = MissionConfig::from_args(&args("cube.zip")).unwrap();
+        assert_eq!(defaults, MissionConfig::new("cube.zip"));
+
+        assert!(MissionConfig::from_args(&args("--seed 7")).is_err());
+        assert!(MissionConfig::from_args(&args("cube.zip --cycles")).is_err());
+        assert!(MissionConfig::from_args(&args("cube.zip --cycles many")).is_err());
+        assert!(MissionConfig::from_args(&args("cube.zip --warp 9")).is_err());
+        assert!(MissionConfig::from_args(&args("a b c")).is_err());
+
+        // The default 16 cycles take the 16 seeds after the run's own.
+        let last = MissionConfig::from_args(&args("cube.zip --seed 18446744073709551599"));
+        assert_eq!(last.unwrap().seeds(), Ok(u64::MAX - 16..u64::MAX - 15));
+        for seed in [
+            "18446744073709551600",
+            "18446744073709551614",
+            "18446744073709551615",
+        ] {
+            let spec = format!("cube.zip --seed {}", seed);
+            assert!(MissionConfig::from_args(&args(&spec)).is_err(), "{}", seed);
+        }
+        assert!(
+            MissionConfig::from_args(&args("cube.zip --seed 18446744073709551614 --cycles 1"))
+                .is_ok()
+        );
+        let past_the_end = MissionConfig {
+            seed: u64::MAX,
+            cycles: 0,
+            ..MissionConfig::new("cube.zip")
+        };
+        assert!(past_the_end.seeds().is_err());
+    }
+
+    #[test]
+    fn test_sweep_ranges_are_half_open_or_inclusive() {
+        assert_eq!(parse_seeds("10..13"), Ok(10..13));
+        assert_eq!(parse_seeds("10..=13"), Ok(10..14));
+        assert!(parse_seeds("13..10").is_err());
+        assert!(parse_seeds("10").is_err());
+        assert!(parse_seeds("a..b").is_err());
+
+        assert_eq!(parse_seeds("5..18446744073709551615"), Ok(5..u64::MAX));
+        assert!(parse_seeds("5..=18446744073709551615").is_err());
+        assert!(
+            MissionConfig::from_args(&args("cube.zip --sweep 5..=18446744073709551615")).is_err()
+        );
+        assert!(
+            MissionConfig::from_args(&args("cube.zip --sweep 5..18446744073709551615")).is_err()
+        );
+        assert!(
+            MissionConfig::from_args(&args("cube.zip --sweep 5..18446744073709551600")).is_ok()
+        );
+        assert!(
+            MissionConfig::from_args(&args("cube.zip --sweep 5..18446744073709551601")).is_err()
+        );
+    }
+
+    #[test]
+    fn test_the_last_allowed_seed_runs_every_cycle() {
+        let config = MissionConfig::new("cube.json");
+        let seed = u64::MAX - u64::from(config.cycles);
+        assert!(MissionConfig {
+            seed,
+            ..config.clone()
+        }
+        .seeds()
+        .is_ok());
+
+        let mut railgun = ZRailgun::with_gpio(seed, Box::new(RecordingBank::new()));
+        railgun.set_coherence_override(Some(0.5));
+        let log = run_cycles(
+            &mut railgun,
+            &JsonValidator,
+            b"[1]".to_vec(),
+            config.cycles,
+            Duration::ZERO,
+        );
+        assert_eq!(log.cycles.len(), config.cycles as usize);
+        assert_eq!(log.cycles.last().unwrap().seed, u64::MAX - 1);
+        assert_eq!(railgun.entropy_seed, u64::MAX);
+    }
+
+    #[test]
+    fn test_reseeded_runs_repeat_and_sweeps_aggregate() {
+        let mut railgun = ZRailgun::with_gpio(1, Box::new(RecordingBank::new()));
+        // Dormant rails: every shot is raw turbulence.
+        railgun.set_coherence_override(Some(0.5));
+        let config = MissionConfig::new("cube.json");
+        let baseline = br#"{"crew": ["Luffy", "Zoro", "Nami"]}"#.to_vec();
+
+        let mut runs = Vec::new();
+        for seed in [42, 43, 42] {
+            railgun.reseed(seed);
+            let CycleLog {
+                cycles,
+                stack,
+                refused,
+            } = run_cycles(
+                &mut railgun,
+                &JsonValidator,
+                baseline.clone(),
+                3,
+                Duration::ZERO,
+            );
+            assert!(refused.is_none());
+            assert_eq!(cycles.len(), 3);
+            assert_eq!(
+                stack.len(),
+                1 + cycles.iter().filter(|c| !c.collapsed).count()
+            );
+            assert!(cycles.iter().all(|c| c.coherence == 0.5 && !c.energized));
+            assert_eq!(cycles[0].parent, sha256_hex(&baseline));
+            assert_eq!(cycles[0].seed, seed);
+            let report = RunReport::new(
+                &config,
+                seed,
+                "json",
+                Vec::new(),
+                sha256_hex(&baseline),
+                cycles,
+            );
+            runs.push((report, None));
+        }
+        assert_eq!(runs[0].0.cycles, runs[2].0.cycles);
+
+        let sweep = SweepReport::new(42..44, &runs[..2]);
+        assert_eq!(sweep.total_cycles, 6);
+        assert_eq!(
+            sweep.total_survivors,
+            runs[0].0.survivors + runs[1].0.survivors
+        );
+        assert_eq!(sweep.survival_rate, sweep.total_survivors as f64 / 6.0);
+        assert!(sweep.min_rate <= sweep.survival_rate && sweep.survival_rate <= sweep.max_rate);
+        assert_eq!(sweep.validator, "json");
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = sweep.write(dir.path()).unwrap();
+        let back: SweepReport = serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+        assert_eq!(back, sweep);
+    }
+
+    #[test]
+    fn test_rails_publish_only_to_the_shm_they_are_given() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = ShmConfig {
+            path: dir.path().join("d16_state"),
+            ..ShmConfig::default()
+        };
+        let mut railgun = ZRailgun::with_gpio(7, Box::new(RecordingBank::new()));
+        railgun.set_coherence_override(Some(0.5));
+        railgun.set_shm(Some(D16ShmWriter::with_config(&config).unwrap()));
+        let reader = D16ShmReader::with_config(&config).unwrap();
+        assert_eq!(reader.seq(), 0);
+
+        run_cycles(
+            &mut railgun,
+            &JsonValidator,
+            b"[1]".to_vec(),
+            2,
+            Duration::ZERO,
+        );
+        // Two fires, two seqlock bumps each.
+        assert_eq!(reader.try_read().unwrap().seq, 4);
+    }
+
+    #[test]
+    fn test_strict_rails_refuse_stale_or_missing_coherence() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        let feed = CoherenceFeed::at(dir.join("feed")).with_legacy(None);
+        let mut railgun = ZRailgun::with_gpio(1, Box::new(RecordingBank::new()));
+        railgun.set_coherence_feed(feed.clone());
+        let baseline = b"[1, 2, 3]".to_vec();
+
+        // Lenient: no producer means dormant rails, not a refusal.
+        let log = run_cycles(
+            &mut railgun,
+            &JsonValidator,
+            baseline.clone(),
+            1,
+            Duration::ZERO,
+        );
+        assert!(log.refused.is_none());
+        assert_eq!(log.cycles[0].coherence, 0.0);
+
+        railgun.set_coherence_policy(CoherencePolicy::Strict);
+        let mut untouched = baseline.clone();
+        assert!(matches!(
+            railgun.fire(&mut untouched),
+            Err(FireError::NoProducer)
+        ));
+        assert_eq!(untouched, baseline);
+
+        let mut sample = CoherenceSample::new(1.2, 9, "ripple_tank_core");
+        sample.timestamp_ns -= 10_000_000_000;
+        fs::write(dir.join("feed"), serde_json::to_vec(&sample).unwrap()).unwrap();
+        let log = run_cycles(
+            &mut railgun,
+            &JsonValidator,
+            baseline.clone(),
+            4,
+            Duration::ZERO,
+        );
+        assert!(log.cycles.is_empty());
+        assert_eq!(log.stack.len(), 1);
+        match log.refused {
+            Some(FireError::StaleCoherence {
+                producer, frame, ..
+            }) => assert_eq!((producer.as_str(), frame), ("ripple_tank_core", 9)),
+            other => panic!("expected a stale refusal, got {:?}", other),
+        }
+
+        // A fresh sample from the same producer arms the rails again.
+        CoherencePublisher::new("ripple_tank_core")
+            .with_path(dir.join("feed"))
+            .with_legacy(None)
+            .publish(1.2, 10)
+            .unwrap();
+        let log = run_cycles(&mut railgun, &JsonValidator, baseline, 1, Duration::ZERO);
+        assert!(log.refused.is_none());
+        assert!(log.cycles[0].energized);
+    }
+}
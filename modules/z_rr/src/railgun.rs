@@ -1,13 +1,15 @@
-use chrono::Utc;
-
 use rand::prelude::*;
-use std::fs::File;
+use std::fmt;
 use std::io::Cursor;
-use std::io::Write;
+use std::time::Duration;
 use zip::ZipArchive;
 
+use crate::crew_stage::{run_stage, CrewPipeline, FireReport, StageContext, Turbulence};
+use crate::rainbow::{RainbowOutput, VuMeterBank};
 use crate::shm_writer::D16ShmWriter;
-use rp1_rio::Rp1Rio;
+use crate::survivor_store::{Survivor, SurvivorStore};
+use coherence_feed::{CoherenceFeed, FeedError};
+use rp1_rio::{ChipBank, GpioBank, Rp1Rio};
 
 /// The Talu64 (Tau-Aligned Logic Unity - 64 Byte)
 #[derive(Debug, Clone, Copy)]
@@ -71,6 +73,76 @@ impl Talu64 {
     }
 }
 
+/// What the rails do when the coherence feed has nothing current to say.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CoherencePolicy {
+    /// No producer (or an unreadable feed) reads as 0.0 and the rails stay dormant;
+    /// stale values are used with a warning.
+    #[default]
+    Lenient,
+    /// Refuse to fire without a fresh sample.
+    Strict,
+}
+
+/// A shot the rails refused. The buffer is left untouched.
+#[derive(Debug)]
+pub enum FireError {
+    /// Nothing is publishing coherence.
+    NoProducer,
+    /// The last sample is too old to trust.
+    StaleCoherence {
+        producer: String,
+        frame: u64,
+        age: Duration,
+        max_age: Duration,
+    },
+    /// The feed exists but could not be read.
+    BadFeed(String),
+}
+
+impl fmt::Display for FireError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FireError::NoProducer => write!(f, "no wave coherence producer"),
+            FireError::StaleCoherence {
+                producer,
+                frame,
+                age,
+                max_age,
+            } => write!(
+                f,
+                "wave coherence from {} frame {} is {} ms old (limit {} ms)",
+                producer,
+                frame,
+                age.as_millis(),
+                max_age.as_millis()
+            ),
+            FireError::BadFeed(reason) => write!(f, "wave coherence unreadable: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for FireError {}
+
+impl From<FeedError> for FireError {
+    fn from(e: FeedError) -> Self {
+        match e {
+            FeedError::NoProducer => FireError::NoProducer,
+            FeedError::Stale {
+                sample,
+                age,
+                max_age,
+            } => FireError::StaleCoherence {
+                producer: sample.source,
+                frame: sample.frame,
+                age,
+                max_age,
+            },
+            other => FireError::BadFeed(other.to_string()),
+        }
+    }
+}
+
 /// Z-RR: Zip Railgun Core (Refactored)
 /// "Evolutionary Annealing via Authentic Talu64 Harmonics"
 pub struct ZRailgun {
@@ -79,7 +151,12 @@ pub struct ZRailgun {
     pub entropy_seed: u64,
     drift_accumulator: f64,
     shm: Option<D16ShmWriter>,
-    rio: Option<Rp1Rio>,
+    rainbow: RainbowOutput,
+    pipeline: CrewPipeline,
+    turbulence: Turbulence,
+    coherence_override: Option<f64>,
+    coherence_feed: CoherenceFeed,
+    coherence_policy: CoherencePolicy,
 }
 
 extern "C" {
@@ -103,23 +180,41 @@ impl ZRailgun {
             seed
         );
 
-        // Calibrated Ignition: Pulse is geometrically aligned to TAU * 10^4
-        // (Fits within u16 max of 65535 for kernel packing)
-        let pulse_tau = (Talu64::TAU * 10000.0) as u64; // ~62831
-        let mut raw_channels = [0u32; 16];
+        // Initialize RP1 RIO (Sovereign Access Check)
+        let bank: Box<dyn GpioBank> = match Rp1Rio::new() {
+            Ok(driver) => {
+                println!("   ⚡ RP1 RIO: Sovereign Access Granted. RAILS ARMED (GPIO 17-27).");
+                Box::new(driver)
+            }
+            Err(e) => match ChipBank::rp1("zrr-rainbow") {
+                // Unprivileged route: the same pins through the gpiochip character device
+                Ok(chip) => {
+                    println!(
+                        "   ⚡ RP1 RIO: /dev/mem Denied ({}). RAILS ARMED via {} (GPIO 17-27).",
+                        e,
+                        chip.chip().path().display()
+                    );
+                    Box::new(chip)
+                }
+                Err(_) => {
+                    println!(
+                        "   ⚠️  RP1 RIO: Access Denied ({}). Running in Simulation Mode (VU Meter).",
+                        e
+                    );
+                    Box::new(VuMeterBank::new())
+                }
+            },
+        };
 
-        // IGNITION: Call the Assembly Kernel
-        println!(
-            "   >> Igniting Talu64 via D16 Kernel (Global Pulse: {})",
-            pulse_tau
-        );
-        unsafe {
-            d16_soft_fpga(pulse_tau, raw_channels.as_mut_ptr());
-        }
+        let mut railgun = Self::with_gpio(seed, bank);
+        railgun.set_shm(D16ShmWriter::new());
+        railgun
+    }
 
-        let talu64 = Talu64 {
-            channels: raw_channels,
-        };
+    /// Builds the Railgun with its rainbow driven through `bank` (hardware, mock or meter).
+    /// It stays off shared memory until given a writer with `set_shm`.
+    pub fn with_gpio(seed: u64, bank: Box<dyn GpioBank>) -> Self {
+        let talu64 = Self::ignition();
 
         // Audit the crew state for validation
         if let Some((decay, phase)) = talu64.get_crew_state("Zoro") {
@@ -129,195 +224,143 @@ impl ZRailgun {
             );
         }
 
-        // Initialize RP1 RIO (Sovereign Access Check)
-        let mut rio = None;
-        match Rp1Rio::new() {
-            Ok(driver) => {
-                println!("   ⚡ RP1 RIO: Sovereign Access Granted. RAILS ARMED (GPIO 17-27).");
-                rio = Some(driver);
-            }
-            Err(e) => {
-                println!(
-                    "   ⚠️  RP1 RIO: Access Denied ({}). Running in Simulation Mode.",
-                    e
-                );
-            }
-        }
-
-        // Initialize output pins if armed
-        if let Some(r) = &mut rio {
-            // Enable output for pins 17..=27
-            for pin in 17..=27 {
-                r.enable_output(pin);
-            }
-        }
-
         Self {
             talu64,
             entropy_seed: seed,
             drift_accumulator: 0.0,
-            shm: D16ShmWriter::new(),
-            rio,
+            shm: None,
+            rainbow: RainbowOutput::new(bank),
+            pipeline: CrewPipeline::default(),
+            turbulence: Turbulence::default(),
+            coherence_override: None,
+            coherence_feed: CoherenceFeed::default(),
+            coherence_policy: CoherencePolicy::default(),
         }
     }
 
-    /// Reads the current Wave Coherence from the Ripple Tank.
-    fn get_coherence(&self) -> f64 {
-        let shm_path = "/dev/shm/current_wave_coherence";
-        if std::path::Path::new(shm_path).exists() {
-            if let Ok(contents) = std::fs::read_to_string(shm_path) {
-                if let Ok(val) = contents.trim().parse::<f64>() {
-                    return val;
-                }
-            }
-        }
-        0.0
+    /// The Talu64 every run starts from.
+    fn ignition() -> Talu64 {
+        // Calibrated Ignition: Pulse is geometrically aligned to TAU * 10^4
+        // (Fits within u16 max of 65535 for kernel packing)
+        let pulse_tau = (Talu64::TAU * 10000.0) as u64; // ~62831
+
+        // IGNITION: Call the Assembly Kernel
+        println!(
+            "   >> Igniting Talu64 via D16 Kernel (Global Pulse: {})",
+            pulse_tau
+        );
+        Talu64::ignite(pulse_tau)
     }
 
-    /// "Railguns" a byte buffer: Applies controlled entropy guided by Talu64 structure.
-    pub fn fire(&mut self, data: &mut Vec<u8>) {
-        let mut rng = StdRng::seed_from_u64(self.entropy_seed);
-        let coherence = self.get_coherence();
+    /// Starts a fresh run at `seed` on the same rails: re-ignites Talu64 and clears
+    /// drift, so a run is reproducible regardless of what was fired before.
+    pub fn reseed(&mut self, seed: u64) {
+        self.entropy_seed = seed;
+        self.drift_accumulator = 0.0;
+        self.talu64 = Self::ignition();
+    }
 
-        println!("   >> Wave Coherence: {:.4}", coherence);
+    /// Pins the coherence every shot sees instead of reading the Ripple Tank.
+    pub fn set_coherence_override(&mut self, coherence: Option<f64>) {
+        self.coherence_override = coherence;
+    }
 
-        if coherence > 1.0 {
-            println!("   ⚡ RAILS ENERGIZED. Applying Crew Logic Pipeline.");
+    /// Where each fire publishes the crew state (`None` publishes nowhere).
+    pub fn set_shm(&mut self, shm: Option<D16ShmWriter>) {
+        self.shm = shm;
+    }
 
-            // Update Physical Rainbow (UV Layer)
-            self.update_rainbow_output(coherence);
+    /// Reads coherence from `feed` instead of the default Ripple Tank feed.
+    pub fn set_coherence_feed(&mut self, feed: CoherenceFeed) {
+        self.coherence_feed = feed;
+    }
 
-            // 2. Zoro (D2): Polarization (Bit Flip at PHI boundaries)
-            if let Some((decay, phase)) = self.talu64.get_crew_state("Zoro") {
-                if phase == 1 {
-                    let cut_point = (rng.gen_range(0..data.len()) as f64 / Talu64::PHI) as usize;
-                    let safe_cut = cut_point.min(data.len() - 1);
-                    data[safe_cut] ^= (decay % 255) as u8;
-                    println!("      ⚔️  Zoro: Polarized cut at idx {}", safe_cut);
-                }
-            }
+    pub fn set_coherence_policy(&mut self, policy: CoherencePolicy) {
+        self.coherence_policy = policy;
+    }
 
-            // 3. Nami (D3): Torque Shift Logic
-            // "Maps the gradient of the wave to physical rotation"
-            if let Some((decay, _phase)) = self.talu64.get_crew_state("Nami") {
-                // Decay represents 'Potential Energy'
-                let torque_threshold = 10000;
-                if decay > torque_threshold {
-                    // Shift buffer right by 1 (Physical Displacement)
-                    data.rotate_right(1);
-                    println!(
-                        "      🍊 Nami: Applied Torque Shift (Right 1) | Energy: {}",
-                        decay
-                    );
-                }
+    /// Reads the current Wave Coherence from the Ripple Tank, applying the policy.
+    fn get_coherence(&self) -> Result<f64, FireError> {
+        if let Some(coherence) = self.coherence_override {
+            return Ok(coherence);
+        }
+        match self.coherence_feed.read() {
+            Ok(sample) => Ok(sample.value),
+            Err(e) if self.coherence_policy == CoherencePolicy::Strict => Err(e.into()),
+            Err(FeedError::Stale { sample, .. }) => {
+                println!(
+                    "   ⚠️  Wave Coherence stale ({} ms since {} frame {}). Using it anyway.",
+                    sample.age().as_millis(),
+                    sample.source,
+                    sample.frame
+                );
+                Ok(sample.value)
             }
-
-            // --- LITTLE CORES (Precision / Noise Collection) ---
-            // Agents: Usopp (4), Franky (8), Yamato (12), Law (16)
-
-            // 4. Usopp (D4): Harmonic Filter Logic
-            // "Filters signal noise using a 4-beat harmonic series"
-            if let Some((_decay, phase)) = self.talu64.get_crew_state("Usopp") {
-                // Predictive Register: Only fire if phase aligns with the Beat
-                if phase % 4 == 0 {
-                    let mask = 0xF0; // High nibble only, filter low-end noise
-                    for i in (0..data.len()).step_by(4) {
-                        if i < data.len() {
-                            data[i] &= mask;
-                        }
-                    }
-                    println!("      🤥 Usopp: Filtered Harmonic Noise (Step 4, Mask F0)");
-                }
+            Err(FeedError::NoProducer) => {
+                println!("   ⚠️  No Wave Coherence producer. Rails stay dormant.");
+                Ok(0.0)
             }
-
-            // 5. Sanji (D5): Ground State Buffer Logic (Big Core)
-            // "Prepares the base layer (food/energy) for the next operation"
-            if let Some((decay, _phase)) = self.talu64.get_crew_state("Sanji") {
-                let salt = (decay % 32) as u8; // 5 bits of flavor
-                                               // Salt the "Ground" (first few bytes)
-                for byte in data.iter_mut().take(5) {
-                    *byte |= salt;
-                }
+            Err(e) => {
                 println!(
-                    "      🍳 Sanji: Seasoned the Ground State (Salt: {:05b})",
-                    salt
+                    "   ⚠️  Wave Coherence unreadable ({}). Rails stay dormant.",
+                    e
                 );
+                Ok(0.0)
             }
+        }
+    }
 
-            // 8. Franky (D8): The Iron General (Buffer Prediction / Noise Collection)
-            if let Some((decay, phase)) = self.talu64.get_crew_state("Franky") {
-                // Fire only on "Super" Alignment (Phase % 8 == 0)
-                if phase % 8 == 0 {
-                    // Prediction: If Decay (Energy) is adequate, we 'build' (transmute)
-                    if decay > 1000 {
-                        println!("      🤖 Franky: SUPER! Transmuting Analog to Binary via Non-Uniform Oscillator.");
-                        self.transmute_signal(data);
-                    }
-                }
-            }
+    /// Replaces the Crew Logic Pipeline run while the rails are energized.
+    pub fn set_pipeline(&mut self, pipeline: CrewPipeline) {
+        self.pipeline = pipeline;
+    }
 
-            // 12. Yamato (D12): The Guardian (Spectral Refinement)
-            if let Some((_decay, phase)) = self.talu64.get_crew_state("Yamato") {
-                if phase % 12 == 0 {
-                    // Refines the signal by removing "Drift" artifacts using Planck XOR
-                    let h_int = Talu64::PLANCK as u8;
-                    for byte in data.iter_mut() {
-                        *byte ^= h_int;
-                    }
-                    println!("      👹 Yamato: Applied Spectral Refinement (Planck XOR).");
-                }
-            }
+    pub fn pipeline(&self) -> &CrewPipeline {
+        &self.pipeline
+    }
 
-            // 16. Law (D16): The Room & Drift Harmonization
-            // "Checks the 16th Harmonic for Alignment"
-            if let Some((_decay, phase)) = self.talu64.get_crew_state("Law") {
-                // Law's Domain is 4096.
-                // If Phase aligns with the Cybiosphere Unit (512)
-                if phase % Talu64::CYBIOSPHERE_UNIT as u16 == 0 {
-                    // Polar Moment of Inertia (J_T) Calculation
-                    // r = phase (radius of the current harmonic cycle)
-                    let r = phase as f64;
-                    let j_t = Talu64::calculate_polar_moment(r);
-
-                    // The "Final u32" Differential Check:
-                    // Only fire if J_T exceeds the Drift Residue (Predictive Firing)
-                    if j_t > self.drift_accumulator {
-                        println!(
-                            "      ⚕️  Law: ROOM Active. J_T ({:.4}) > Drift. Predictive Signal Fired.",
-                            j_t
-                        );
-                        self.harmonize_drift();
-
-                        // Check "Noise Buffer" (Simulated by top of stack/data)
-                        if data.len() > Talu64::CYBIOSPHERE_UNIT as usize {
-                            println!("      ⚕️  Law: Scanning Noise Buffer for Harmony...");
-                            // (Placeholder for Noise Buffer operations)
-                        }
-                    } else {
-                        println!("      ⚕️  Law: J_T Insufficient. Holding Signal.");
-                    }
-                }
+    /// "Railguns" a byte buffer: Applies controlled entropy guided by Talu64 structure.
+    /// Returns what each stage of the pipeline did to the buffer, or why the rails
+    /// refused to fire under [`CoherencePolicy::Strict`].
+    pub fn fire(&mut self, data: &mut Vec<u8>) -> Result<FireReport, FireError> {
+        let coherence = match self.get_coherence() {
+            Ok(coherence) => coherence,
+            Err(e) => {
+                println!("   🛑 RAILS REFUSED: {}", e);
+                self.rainbow.clear();
+                return Err(e);
             }
+        };
+        let mut rng = StdRng::seed_from_u64(self.entropy_seed);
+
+        println!("   >> Wave Coherence: {:.4}", coherence);
+
+        let energized = coherence > 1.0;
+        if energized {
+            println!("   ⚡ RAILS ENERGIZED. Applying Crew Logic Pipeline.");
+
+            // Update Physical Rainbow (UV Layer)
+            self.update_rainbow_output(coherence);
         } else {
             // LOW COHERENCE: RAILS DORMANT
             println!("   ❄️  RAILS DORMANT. Low Coherence. Applying Raw Turbulence.");
 
             // Clear Rainbow
-            if let Some(rio) = &mut self.rio {
-                for pin in 17..=27 {
-                    rio.clr_pin(pin);
-                }
-            }
-
-            if !data.is_empty() {
-                let turbulence = rng.gen_range(5..15);
-                for _ in 0..turbulence {
-                    let idx = rng.gen_range(0..data.len());
-                    data[idx] ^= 1 << rng.gen_range(0..8);
-                }
-            }
+            self.rainbow.clear();
         }
 
+        let mut ctx = StageContext {
+            talu64: &self.talu64,
+            rng: &mut rng,
+            coherence,
+            drift_accumulator: &mut self.drift_accumulator,
+        };
+        let stages = if energized {
+            self.pipeline.run(&mut ctx, data)
+        } else {
+            vec![run_stage(&mut self.turbulence, &mut ctx, data)]
+        };
+
         // Accumulate Drift per cycle (Simulating 1ms measurement per tick)
         self.drift_accumulator += Talu64::DRIFT_RESIDUE;
 
@@ -325,74 +368,19 @@ impl ZRailgun {
         if let Some(shm) = self.shm.as_mut() {
             shm.write(self.talu64.channels, self.entropy_seed as u32);
         }
+
+        Ok(FireReport {
+            coherence,
+            energized,
+            stages,
+        })
     }
 
     /// Maps Coherence & Spectral Density to Physical Pins (Rainbow Railgun Output)
-    /// GPIO 17 (UV) -> GPIO 27 (Red)
+    /// GPIO 17 (UV) -> GPIO 27 (Red), shimmering with Zoro's phase.
     fn update_rainbow_output(&mut self, coherence: f64) {
-        if let Some(rio) = &mut self.rio {
-            // Map 11 pins (17..27) representing the spectrum.
-            // Pin 17 = Highest Frequency (UV)
-            // Pin 27 = Lowest Frequency (Red)
-
-            // We use 'coherence' as magnitude and 'zoro phase' as modulation
-            let modulation = if let Some((_, phase)) = self.talu64.get_crew_state("Zoro") {
-                phase as f64
-            } else {
-                1.0
-            };
-
-            // Calculate active 'bar' height based on Coherence (1.0 to 10.0 scale?)
-            let _limit = (coherence * 2.0).clamp(0.0, 11.0) as usize;
-
-            for i in 0..11 {
-                let pin = 17 + i as u32; // 17..=27
-                                         // Reverse Mapping: i=0 is UV (Pin 17), i=10 is Red (Pin 27)
-                                         // If coherence is high, we light up from Red towards UV?
-                                         // OR: Diffraction pattern?
-
-                // Active if index within limit?
-                // Let's model it as a VU Meter for now, filling from Red (27) to UV (17) would be typical energy build up.
-                // BUT User asked for: "expanded diffusion ... reverse order (GPIO 17 greatest, GPIO 27 least)"
-
-                // Let's interpret: 17 is the PEAK (Greatest), 27 is the BASE (Least).
-                // So clear all, set bit logic based on spectral density.
-
-                // Simple Logic:
-                // If Coherence > Threshold(i), fire Pin(i).
-                // Threshold decreases from 17 to 27? Or increases?
-                // Higher coherence = Higher Frequency access.
-
-                // Energy Level i (0..11):
-                // 0 (Pin 17 - UV) requires MOST coherence.
-                // 10 (Pin 27 - Red) requires LEAST coherence.
-
-                let threshold = (11.0 - i as f64) * 0.5; // Pin 17 needs 5.5, Pin 27 needs 0.5
-
-                if coherence > threshold {
-                    // Modulate with phase for "Shimmer"
-                    if (modulation as usize + i) % 2 == 0 {
-                        rio.set_pin(pin);
-                    } else {
-                        rio.clr_pin(pin);
-                    }
-                } else {
-                    rio.clr_pin(pin);
-                }
-            }
-        }
-    }
-
-    /// Analog/Binary Transmutation Algorithm
-    /// "Non-uniform harmonic oscillatory motion"
-    fn transmute_signal(&self, data: &mut Vec<u8>) {
-        // Rebuilds the photon (signal) at the CI/CD boundary.
-        // We apply a non-uniform oscillation using PHI and TAU.
-        for (i, byte) in data.iter_mut().enumerate() {
-            let osc = (i as f64 * Talu64::PHI).sin() * Talu64::TAU;
-            let shift = (osc.abs() * 10.0) as u8; // Non-uniform shift
-            *byte = byte.wrapping_add(shift);
-        }
+        let zoro_phase = self.talu64.get_crew_state("Zoro").map(|(_, phase)| phase);
+        self.rainbow.show(coherence, zoro_phase);
     }
 
     /// Re-aligns internal Talu64 state using the Hardware Kernel
@@ -407,62 +395,42 @@ impl ZRailgun {
             channels: raw_channels,
         };
     }
-
-    /// Harmonizes the accumulated drift (0.64ms Cycle)
-    fn harmonize_drift(&mut self) {
-        if self.drift_accumulator >= 100.0 {
-            // Reset/Harmonize
-            println!(
-                "      ⚕️  Law: Harmonizing Drift Accumulator ({:.4}ms). SRAM/DRAM Re-aligned.",
-                self.drift_accumulator
-            );
-            self.drift_accumulator = 0.0;
-        }
-    }
 }
 
+/// The legacy ZIP check: the hypercube survives while its archive still opens. Only the
+/// central directory is read; [`crate::validate`] has graded checks that also extract.
 pub fn listener_collapse(data: &[u8]) -> bool {
     // We treat the ZIP structure as the "Singularity"
-    let cursor = Cursor::new(data);
-    match ZipArchive::new(cursor) {
-        Ok(_) => true,
-        Err(_) => false,
-    }
+    ZipArchive::new(Cursor::new(data)).is_ok()
 }
 
-/// Offloads surviving Hypercubes to Amazon Lily (Franky/Storage)
-pub fn dock_survivors(stack: &Vec<Vec<u8>>, seed: u64) {
-    let storage_path = "../../workspaces/d8_franky/amazon_lily";
-    println!("   📦 Docking Survivors to Amazon Lily: {}", storage_path);
-
-    // Ensure directory exists (redundant if using mkdir previously, but good for robustness)
-    let _ = std::fs::create_dir_all(storage_path);
-
-    for (i, survivor) in stack.iter().enumerate() {
-        // Calm Belt Check: Verify Integrity via Checksum (CRC32 from zip crate dependency or simple hash)
-        // For now, valid size check > 0
-        if survivor.is_empty() {
-            continue;
-        }
-
-        let timestamp = Utc::now().format("%Y%m%d_%H%M%S").to_string();
-        let filename = format!(
-            "{}/survivor_{}_seed{}_id{}.cube",
-            storage_path, timestamp, seed, i
-        );
-
-        let mut file = match File::create(&filename) {
-            Ok(f) => f,
-            Err(e) => {
-                println!("      ❌ Failed to dock Cube #{}: {}", i, e);
-                continue;
+/// Offloads surviving Hypercubes to Amazon Lily (Franky/Storage).
+/// Identical cubes are stored once; returns the hash of each survivor that docked, in
+/// order. Survivors the store refuses are reported and left out.
+pub fn dock_survivors(store: &mut SurvivorStore, survivors: &[Survivor]) -> Vec<String> {
+    println!(
+        "   📦 Docking Survivors to Amazon Lily: {}",
+        store.root().display()
+    );
+
+    let mut hashes = Vec::new();
+    for (i, survivor) in survivors.iter().enumerate() {
+        // Calm Belt Check: the store hashes every cube and refuses empty ones
+        match store.dock(&survivor.data, survivor.docking.clone()) {
+            Ok(docked) if docked.new => {
+                println!("      ⚓ Docked Cube #{} -> {}", i, &docked.hash[..16]);
+                hashes.push(docked.hash);
             }
-        };
-
-        if let Err(e) = file.write_all(survivor) {
-            println!("      ❌ Failed to write Cube #{}: {}", i, e);
-        } else {
-            println!("      ⚓ Docked Cube #{} -> {}", i, filename);
+            Ok(docked) => {
+                println!(
+                    "      ♻️  Cube #{} already docked as {}",
+                    i,
+                    &docked.hash[..16]
+                );
+                hashes.push(docked.hash);
+            }
+            Err(e) => println!("      ❌ Failed to dock Cube #{}: {}", i, e),
         }
     }
+    hashes
 }
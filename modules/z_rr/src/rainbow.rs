@@ -0,0 +1,144 @@
+//! Rainbow Railgun Output: coherence mapped onto GPIO 17 (UV) .. GPIO 27 (Red).
+
+use rp1_rio::{pin_mask, GpioBank};
+use std::ops::RangeInclusive;
+
+/// The spectrum pins. Pin 17 is the PEAK (Greatest), pin 27 the BASE (Least).
+pub const RAINBOW_PINS: RangeInclusive<u32> = 17..=27;
+
+pub const RAINBOW_BANDS: usize = 11;
+
+/// Which bands light for a given coherence, UV first.
+///
+/// Band `i` needs coherence above `(11 - i) * 0.5`: UV (pin 17) needs 5.5, Red (pin 27)
+/// needs 0.5. Lit bands shimmer with Zoro's phase, alternating on `(phase + i) % 2`.
+pub fn rainbow_pattern(coherence: f64, zoro_phase: Option<u16>) -> [bool; RAINBOW_BANDS] {
+    let modulation = zoro_phase.map_or(1, |phase| phase as usize);
+    let mut pattern = [false; RAINBOW_BANDS];
+    for (i, lit) in pattern.iter_mut().enumerate() {
+        let threshold = (11.0 - i as f64) * 0.5;
+        *lit = coherence > threshold && (modulation + i).is_multiple_of(2);
+    }
+    pattern
+}
+
+/// The eleven rainbow pins on some [`GpioBank`].
+pub struct RainbowOutput {
+    bank: Box<dyn GpioBank>,
+}
+
+impl RainbowOutput {
+    /// Takes the bank and enables output on every rainbow pin.
+    pub fn new(mut bank: Box<dyn GpioBank>) -> Self {
+        for pin in RAINBOW_PINS {
+            bank.enable_output(pin);
+        }
+        Self { bank }
+    }
+
+    /// Drives the pattern for `coherence` and Zoro's phase.
+    pub fn show(&mut self, coherence: f64, zoro_phase: Option<u16>) {
+        let pattern = rainbow_pattern(coherence, zoro_phase);
+        for (pin, lit) in RAINBOW_PINS.zip(pattern) {
+            self.bank.write_pin(pin, lit);
+        }
+        self.bank.flush();
+    }
+
+    /// Clears every rainbow pin (rails dormant).
+    pub fn clear(&mut self) {
+        for pin in RAINBOW_PINS {
+            self.bank.clr_pin(pin);
+        }
+        self.bank.flush();
+    }
+}
+
+/// Simulation Mode: renders the rainbow pins as a VU meter on the terminal.
+#[derive(Debug, Default)]
+pub struct VuMeterBank {
+    levels: u32,
+}
+
+impl VuMeterBank {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The meter as text, UV on the left and Red on the right.
+    pub fn render(&self) -> String {
+        let bars: String = RAINBOW_PINS
+            .map(|pin| if self.read_pin(pin) { '█' } else { '·' })
+            .collect();
+        format!("🌈 [UV {} Red]", bars)
+    }
+}
+
+impl GpioBank for VuMeterBank {
+    fn enable_output(&mut self, _pin: u32) {}
+
+    fn set_pin(&mut self, pin: u32) {
+        self.levels |= pin_mask(pin);
+    }
+
+    fn clr_pin(&mut self, pin: u32) {
+        self.levels &= !pin_mask(pin);
+    }
+
+    fn read_pin(&self, pin: u32) -> bool {
+        self.levels & pin_mask(pin) != 0
+    }
+
+    fn flush(&mut self) {
+        println!("   {}", self.render());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rp1_rio::RecordingBank;
+
+    #[test]
+    fn test_rainbow_pattern_follows_coherence_and_zoro_phase() {
+        let probe = RecordingBank::new();
+        let mut rainbow = RainbowOutput::new(Box::new(probe.clone()));
+        assert_eq!(probe.outputs(), 0x0FFE_0000);
+
+        // 3.2 lights bands 5..=10 (Green..Red); an even phase keeps the even ones.
+        rainbow.show(3.2, Some(4));
+        assert_eq!(
+            probe.pattern(RAINBOW_PINS),
+            [false, false, false, false, false, false, true, false, true, false, true]
+        );
+
+        // An odd phase (or no Zoro at all) shimmers onto the odd bands.
+        rainbow.show(3.2, Some(7));
+        assert_eq!(probe.levels(), (1 << 22) | (1 << 24) | (1 << 26));
+        assert_eq!(rainbow_pattern(3.2, None), rainbow_pattern(3.2, Some(7)));
+
+        rainbow.clear();
+        assert_eq!(probe.levels(), 0);
+        assert_eq!(probe.frames().len(), 3);
+    }
+
+    #[test]
+    fn test_full_coherence_reaches_uv() {
+        assert_eq!(rainbow_pattern(0.5, Some(0)), [false; RAINBOW_BANDS]);
+        assert_eq!(
+            rainbow_pattern(6.0, Some(0)),
+            [true, false, true, false, true, false, true, false, true, false, true]
+        );
+    }
+
+    #[test]
+    fn test_vu_meter_renders_uv_to_red() {
+        let mut vu = VuMeterBank::new();
+        vu.set_pin(17);
+        vu.set_pin(27);
+        assert_eq!(vu.render(), "🌈 [UV █·········█ Red]");
+        vu.set_pin(32);
+        assert!(!vu.read_pin(32));
+        assert_eq!(vu.render(), "🌈 [UV █·········█ Red]");
+    }
+}
@@ -0,0 +1,350 @@
+//! Reader side of `/dev/shm/d16_state`.
+//!
+//! Frames are published under a seqlock (see [`D16ShmHeader`]), so a read either sees one
+//! complete frame or is retried; it never returns channels from two different writes.
+
+use memmap2::Mmap;
+use std::fs::File;
+use std::io;
+use std::mem::size_of;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{fence, Ordering};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::shm_writer::{
+    dword, word, D16ShmFrame, D16ShmHeader, D16ShmLayout, ShmConfig, D16_SHM_MAGIC,
+    D16_SHM_VERSION, PAYLOAD_OFFSET, PAYLOAD_WORDS, SEQ_OFFSET, WRITTEN_OFFSET,
+};
+
+/// Spins this many times on a busy seqlock before yielding the CPU.
+const SPINS_BEFORE_YIELD: u32 = 64;
+/// Sleep between polls while waiting for the writer to publish something new.
+const POLL_INTERVAL: Duration = Duration::from_micros(500);
+
+/// One consistent frame.
+#[derive(Debug, Clone, Copy)]
+pub struct D16Snapshot {
+    /// Even seqlock value the frame was published under; grows with every write.
+    pub seq: u32,
+    pub written_at: SystemTime,
+    pub layout: D16ShmLayout,
+}
+
+impl D16Snapshot {
+    /// Time since the writer published this frame (zero if its clock is ahead of ours).
+    pub fn age(&self) -> Duration {
+        SystemTime::now()
+            .duration_since(self.written_at)
+            .unwrap_or_default()
+    }
+
+    pub fn is_stale(&self, max_age: Duration) -> bool {
+        self.age() > max_age
+    }
+
+    /// Nothing has been published since the header was stamped.
+    pub fn is_empty(&self) -> bool {
+        self.seq == 0
+    }
+}
+
+pub struct D16ShmReader {
+    mmap: Mmap,
+    path: PathBuf,
+    last_seq: Option<u32>,
+}
+
+impl D16ShmReader {
+    /// Maps the frame at `path`, refusing files that are too short or carry another
+    /// magic or version.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = File::open(&path)?;
+        if file.metadata()?.len() < size_of::<D16ShmFrame>() as u64 {
+            return Err(invalid(format!("{} is too short", path.display())));
+        }
+        let mmap = unsafe { Mmap::map(&file)? };
+
+        let header = unsafe { std::ptr::read_volatile(mmap.as_ptr() as *const D16ShmHeader) };
+        if header.magic != D16_SHM_MAGIC {
+            return Err(invalid(format!(
+                "{} is not a D16 state file (magic {:#010x})",
+                path.display(),
+                header.magic
+            )));
+        }
+        if header.version != D16_SHM_VERSION {
+            return Err(invalid(format!(
+                "{} is version {}, expected {}",
+                path.display(),
+                header.version,
+                D16_SHM_VERSION
+            )));
+        }
+        Ok(Self {
+            mmap,
+            path,
+            last_seq: None,
+        })
+    }
+
+    pub fn with_config(config: &ShmConfig) -> io::Result<Self> {
+        Self::open(&config.path)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Current sequence value; odd while a write is in progress.
+    pub fn seq(&self) -> u32 {
+        unsafe { word(self.mmap.as_ptr(), SEQ_OFFSET).load(Ordering::Acquire) }
+    }
+
+    /// Single attempt: `None` if the writer is mid-frame or overtook the read.
+    pub fn try_read(&self) -> Option<D16Snapshot> {
+        let base = self.mmap.as_ptr();
+        let mut words = [0u32; PAYLOAD_WORDS];
+        unsafe {
+            let seq = word(base, SEQ_OFFSET);
+            let begin = seq.load(Ordering::Acquire);
+            if begin & 1 == 1 {
+                return None;
+            }
+            for (i, w) in words.iter_mut().enumerate() {
+                *w = word(base, PAYLOAD_OFFSET + i * 4).load(Ordering::Relaxed);
+            }
+            let written_ns = dword(base, WRITTEN_OFFSET).load(Ordering::Relaxed);
+            fence(Ordering::Acquire);
+            if seq.load(Ordering::Relaxed) != begin {
+                return None;
+            }
+
+            let mut channels = [0u32; 16];
+            channels.copy_from_slice(&words[..16]);
+            Some(D16Snapshot {
+                seq: begin,
+                written_at: UNIX_EPOCH + Duration::from_nanos(written_ns),
+                layout: D16ShmLayout {
+                    channels,
+                    timestamp: words[16],
+                },
+            })
+        }
+    }
+
+    /// Retries until a consistent frame is read, or `timeout` passes (a writer that died
+    /// mid-frame leaves the lock odd forever).
+    pub fn read(&self, timeout: Duration) -> Option<D16Snapshot> {
+        let deadline = Instant::now() + timeout;
+        let mut spins = 0;
+        loop {
+            if let Some(snapshot) = self.try_read() {
+                return Some(snapshot);
+            }
+            if Instant::now() >= deadline {
+                return None;
+            }
+            spins += 1;
+            if spins < SPINS_BEFORE_YIELD {
+                std::hint::spin_loop();
+            } else {
+                thread::yield_now();
+            }
+        }
+    }
+
+    /// True if a frame newer than the last one handed out by [`Self::wait_for_change`]
+    /// has been published (or is being written).
+    pub fn has_changed(&self) -> bool {
+        let seq = self.seq();
+        seq != 0 && self.last_seq.is_none_or(|last| seq != last)
+    }
+
+    /// Blocks until the writer publishes a frame this reader has not seen yet.
+    pub fn wait_for_change(&mut self, timeout: Duration) -> Option<D16Snapshot> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if self.has_changed() {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                if let Some(snapshot) = self.read(remaining) {
+                    if !snapshot.is_empty() && Some(snapshot.seq) != self.last_seq {
+                        self.last_seq = Some(snapshot.seq);
+                        return Some(snapshot);
+                    }
+                }
+            }
+            if Instant::now() >= deadline {
+                return None;
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    /// True when nothing was ever published, or the last frame is older than `max_age`.
+    pub fn is_stale(&self, max_age: Duration) -> bool {
+        match self.read(max_age) {
+            Some(snapshot) => snapshot.is_empty() || snapshot.is_stale(max_age),
+            None => true,
+        }
+    }
+}
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shm_writer::D16ShmWriter;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    const HAMMER_ENV: &str = "D16_SHM_HAMMER_PATH";
+    const HAMMER_FRAMES: u32 = 200_000;
+
+    /// A config whose frame lives in a fresh directory, removed when the `TempDir` drops.
+    fn scratch() -> (TempDir, ShmConfig) {
+        let dir = tempfile::tempdir().unwrap();
+        let config = ShmConfig {
+            path: dir.path().join("d16_state"),
+            ..ShmConfig::default()
+        };
+        (dir, config)
+    }
+
+    #[test]
+    fn test_reads_back_frames_and_tracks_changes() {
+        let (_dir, config) = scratch();
+        let mut writer = D16ShmWriter::with_config(&config).unwrap();
+        let mut reader = D16ShmReader::with_config(&config).unwrap();
+
+        assert!(!reader.has_changed());
+        assert!(reader.is_stale(Duration::from_secs(60)));
+        assert!(reader.wait_for_change(Duration::from_millis(5)).is_none());
+
+        let mut channels = [0u32; 16];
+        channels[15] = 0xD16;
+        writer.write(channels, 42);
+        let first = reader.wait_for_change(Duration::from_secs(1)).unwrap();
+        assert_eq!(first.layout.channels[15], 0xD16);
+        assert_eq!(first.layout.timestamp, 42);
+        assert_eq!(first.seq, 2);
+        assert!(!reader.has_changed());
+        assert!(!reader.is_stale(Duration::from_secs(60)));
+
+        writer.write(channels, 43);
+        assert!(reader.has_changed());
+        assert_eq!(
+            reader.wait_for_change(Duration::from_secs(1)).unwrap().seq,
+            4
+        );
+
+        // A restarted writer keeps counting.
+        drop(writer);
+        let mut writer = D16ShmWriter::with_config(&config).unwrap();
+        writer.write(channels, 44);
+        assert_eq!(reader.try_read().unwrap().seq, 6);
+    }
+
+    #[test]
+    fn test_rejects_foreign_files_and_enforces_mode() {
+        let (_dir, config) = scratch();
+        let config = ShmConfig {
+            mode: 0o600,
+            ..config
+        };
+        std::fs::write(&config.path, vec![0u8; size_of::<D16ShmFrame>()]).unwrap();
+        let err = D16ShmReader::open(&config.path).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        std::fs::write(&config.path, b"D16S").unwrap();
+        assert!(D16ShmReader::open(&config.path).is_err());
+
+        // The writer re-stamps it and tightens the permissions.
+        let _writer = D16ShmWriter::with_config(&config).unwrap();
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&config.path)
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert!(D16ShmReader::open(&config.path).is_ok());
+    }
+
+    /// Child half of `test_hammered_reads_never_tear`: writes frames whose every word equals
+    /// the frame number. Does nothing unless launched by the parent test.
+    #[test]
+    #[ignore]
+    fn test_hammer_writer_child() {
+        let Some(path) = std::env::var_os(HAMMER_ENV) else {
+            return;
+        };
+        let config = ShmConfig {
+            path: PathBuf::from(path),
+            ..ShmConfig::default()
+        };
+        let mut writer = D16ShmWriter::with_config(&config).unwrap();
+        for n in 1..=HAMMER_FRAMES {
+            writer.write([n; 16], n);
+        }
+    }
+
+    #[test]
+    fn test_hammered_reads_never_tear() {
+        let (_dir, config) = scratch();
+        // Stamp the header before the child starts so the reader can map it.
+        D16ShmWriter::with_config(&config).unwrap();
+        let reader = D16ShmReader::with_config(&config).unwrap();
+
+        let mut child = Command::new(std::env::current_exe().unwrap())
+            .args([
+                "--ignored",
+                "--exact",
+                "shm_reader::tests::test_hammer_writer_child",
+                "--test-threads=1",
+            ])
+            .env(HAMMER_ENV, &config.path)
+            .stdout(std::process::Stdio::null())
+            .spawn()
+            .unwrap();
+
+        let mut reads = 0u64;
+        let mut distinct = 0u64;
+        let mut last = 0;
+        loop {
+            let finished = child.try_wait().unwrap();
+            if let Some(snapshot) = reader.try_read() {
+                let n = snapshot.layout.timestamp;
+                assert!(
+                    snapshot.layout.channels.iter().all(|&c| c == n),
+                    "torn frame at seq {}: {:?} / {}",
+                    snapshot.seq,
+                    snapshot.layout.channels,
+                    n
+                );
+                assert!(n >= last, "frame went backwards: {} after {}", n, last);
+                reads += 1;
+                if n != last {
+                    distinct += 1;
+                    last = n;
+                }
+            }
+            if let Some(status) = finished {
+                assert!(status.success(), "writer process failed: {}", status);
+                break;
+            }
+        }
+
+        assert_eq!(reader.try_read().unwrap().layout.timestamp, HAMMER_FRAMES);
+        assert_eq!(reader.seq(), HAMMER_FRAMES * 2);
+        assert!(
+            reads > 0 && distinct > 1,
+            "{} reads, {} distinct",
+            reads,
+            distinct
+        );
+    }
+}
@@ -1,7 +1,16 @@
 use memmap2::MmapMut;
-use std::fs::OpenOptions;
+use std::fs::{OpenOptions, Permissions};
+use std::io;
+use std::mem::{offset_of, size_of};
+use std::path::PathBuf;
+use std::sync::atomic::{fence, AtomicU32, AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
 
-use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+/// "D16S", little-endian.
+pub const D16_SHM_MAGIC: u32 = u32::from_le_bytes(*b"D16S");
+pub const D16_SHM_VERSION: u16 = 1;
 
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
@@ -10,59 +19,175 @@ pub struct D16ShmLayout {
     pub timestamp: u32,
 }
 
+/// Leads every frame. `seq` is a seqlock: odd while the writer is mid-frame, bumped
+/// to the next even value once the payload and `written_ns` are complete.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct D16ShmHeader {
+    pub magic: u32,
+    pub version: u16,
+    pub header_len: u16,
+    pub payload_len: u32,
+    pub seq: u32,
+    /// Wall-clock time of the last complete write (ns since the Unix epoch).
+    pub written_ns: u64,
+}
+
+/// The whole shared-memory file.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct D16ShmFrame {
+    pub header: D16ShmHeader,
+    pub payload: D16ShmLayout,
+}
+
+pub(crate) const SEQ_OFFSET: usize = offset_of!(D16ShmHeader, seq);
+pub(crate) const WRITTEN_OFFSET: usize = offset_of!(D16ShmHeader, written_ns);
+pub(crate) const PAYLOAD_OFFSET: usize = offset_of!(D16ShmFrame, payload);
+pub(crate) const PAYLOAD_WORDS: usize = size_of::<D16ShmLayout>() / 4;
+
+/// Atomic view of the u32 at byte `offset` of a mapping.
+///
+/// # Safety
+/// `base` must point to a live mapping of at least `offset + 4` bytes, 4-byte aligned.
+pub(crate) unsafe fn word<'a>(base: *const u8, offset: usize) -> &'a AtomicU32 {
+    unsafe { &*(base.add(offset) as *const AtomicU32) }
+}
+
+/// # Safety
+/// As [`word`], for 8 bytes at an 8-byte aligned `offset`.
+pub(crate) unsafe fn dword<'a>(base: *const u8, offset: usize) -> &'a AtomicU64 {
+    unsafe { &*(base.add(offset) as *const AtomicU64) }
+}
+
+/// Where the D16 state lives and who may open it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShmConfig {
+    pub path: PathBuf,
+    /// Applied on every open, not just on create. Readers only need read access.
+    pub mode: u32,
+}
+
+impl Default for ShmConfig {
+    fn default() -> Self {
+        Self {
+            path: PathBuf::from("/dev/shm/d16_state"),
+            mode: 0o644,
+        }
+    }
+}
+
 pub struct D16ShmWriter {
     mmap: MmapMut,
+    path: PathBuf,
 }
 
 impl D16ShmWriter {
+    /// Opens the default `/dev/shm/d16_state`, logging (and swallowing) any failure.
     pub fn new() -> Option<Self> {
-        let path = "/dev/shm/d16_state";
-        println!("   [SHM] Creating/Opening {}", path);
+        let config = ShmConfig::default();
+        println!("   [SHM] Creating/Opening {}", config.path.display());
 
-        let file = match OpenOptions::new()
+        match Self::with_config(&config) {
+            Ok(writer) => {
+                println!("   [SHM] Shared Memory Initialized Successfully.");
+                Some(writer)
+            }
+            Err(e) => {
+                println!("   [SHM] Failed to initialize: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Creates or reopens the frame at `config.path`, re-stamping the header if the file
+    /// is missing, short, or from another version. An existing sequence count is kept so
+    /// readers tracking changes carry on across writer restarts.
+    pub fn with_config(config: &ShmConfig) -> io::Result<Self> {
+        let file = OpenOptions::new()
             .read(true)
             .write(true)
             .create(true)
-            .mode(0o666) // RW for everyone
-            .open(path)
-        {
-            Ok(f) => f,
-            Err(e) => {
-                println!("   [SHM] Failed to open file: {}", e);
-                return None;
-            }
-        };
+            .truncate(false)
+            .mode(config.mode)
+            .open(&config.path)?;
+        file.set_permissions(Permissions::from_mode(config.mode))?;
 
-        let size = std::mem::size_of::<D16ShmLayout>() as u64;
-        if let Err(e) = file.set_len(size) {
-            println!("   [SHM] Failed to set length: {}", e);
-            return None;
+        let size = size_of::<D16ShmFrame>() as u64;
+        let fresh = file.metadata()?.len() != size;
+        if fresh {
+            file.set_len(size)?;
         }
+        let mmap = unsafe { MmapMut::map_mut(&file)? };
 
-        let mmap = unsafe {
-            match MmapMut::map_mut(&file) {
-                Ok(m) => m,
-                Err(e) => {
-                    println!("   [SHM] Failed to mmap: {}", e);
-                    return None;
-                }
-            }
+        let mut writer = Self {
+            mmap,
+            path: config.path.clone(),
         };
+        let header = writer.header();
+        if fresh || header.magic != D16_SHM_MAGIC || header.version != D16_SHM_VERSION {
+            writer.stamp_header();
+        }
+        Ok(writer)
+    }
 
-        println!("   [SHM] Shared Memory Initialized Successfully.");
-        Some(Self { mmap })
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
     }
 
-    pub fn write(&mut self, channels: [u32; 16], timestamp: u32) {
-        let layout = D16ShmLayout {
-            channels,
-            timestamp,
+    /// The header as currently in memory.
+    pub fn header(&self) -> D16ShmHeader {
+        unsafe { std::ptr::read_volatile(self.mmap.as_ptr() as *const D16ShmHeader) }
+    }
+
+    fn stamp_header(&mut self) {
+        let header = D16ShmHeader {
+            magic: D16_SHM_MAGIC,
+            version: D16_SHM_VERSION,
+            header_len: size_of::<D16ShmHeader>() as u16,
+            payload_len: size_of::<D16ShmLayout>() as u32,
+            seq: 0,
+            written_ns: 0,
         };
+        unsafe {
+            std::ptr::write_volatile(self.mmap.as_mut_ptr() as *mut D16ShmHeader, header);
+        }
+        let _ = self.mmap.flush();
+    }
+
+    pub fn write(&mut self, channels: [u32; 16], timestamp: u32) {
+        let base = self.mmap.as_ptr();
+        let written_ns = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos() as u64);
 
         unsafe {
-            let src_ptr = &layout as *const D16ShmLayout as *const u8;
-            let dst_ptr = self.mmap.as_mut_ptr();
-            std::ptr::copy_nonoverlapping(src_ptr, dst_ptr, std::mem::size_of::<D16ShmLayout>());
+            let seq = word(base, SEQ_OFFSET);
+            let begin = seq.load(Ordering::Relaxed).wrapping_add(1) | 1;
+            seq.store(begin, Ordering::Relaxed);
+            fence(Ordering::Release);
+
+            for (i, value) in channels.iter().chain([timestamp].iter()).enumerate() {
+                word(base, PAYLOAD_OFFSET + i * 4).store(*value, Ordering::Relaxed);
+            }
+            dword(base, WRITTEN_OFFSET).store(written_ns, Ordering::Relaxed);
+
+            seq.store(begin.wrapping_add(1), Ordering::Release);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_layout_is_stable() {
+        // Mirrored by hailo_d16_shim/d16_hailo_feeder.cpp
+        assert_eq!(size_of::<D16ShmHeader>(), 24);
+        assert_eq!(size_of::<D16ShmFrame>(), 96);
+        assert_eq!((SEQ_OFFSET, WRITTEN_OFFSET, PAYLOAD_OFFSET), (12, 16, 24));
+        assert_eq!(PAYLOAD_WORDS, 17);
+        assert_eq!(&D16_SHM_MAGIC.to_le_bytes(), b"D16S");
+    }
+}
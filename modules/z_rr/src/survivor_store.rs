@@ -0,0 +1,431 @@
+//! Amazon Lily: a content-addressed store for surviving hypercubes.
+//!
+//! Each survivor is stored once under its SHA-256 (`objects/ab/cdef….cube`) and described
+//! in `manifest.json`: the seed and cycle that produced it, its parent's hash and the
+//! mutation report of the shot. Following parents leads back to the baseline.
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::crew_stage::FireReport;
+use crate::validate::Grade;
+
+/// Where survivors dock unless `AMAZON_LILY_PATH` says otherwise.
+pub const DEFAULT_ROOT: &str = "../../workspaces/d8_franky/amazon_lily";
+
+const MANIFEST: &str = "manifest.json";
+const MANIFEST_VERSION: u32 = 1;
+
+pub fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+/// Lowercase hex, as `sha256_hex` writes it. Anything else (separators, `..`, non-ASCII)
+/// never names an object.
+fn is_lower_hex(s: &str) -> bool {
+    s.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+/// How a survivor came to be. The baseline has no parent and no cycle.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Docking {
+    pub seed: u64,
+    pub cycle: Option<u32>,
+    pub parent: Option<String>,
+    pub grade: Option<Grade>,
+    pub mutation: Option<FireReport>,
+}
+
+impl Docking {
+    pub fn baseline(seed: u64) -> Self {
+        Self {
+            seed,
+            grade: Some(Grade::Valid),
+            ..Self::default()
+        }
+    }
+}
+
+/// A hypercube that survived its shot, waiting to dock.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Survivor {
+    pub data: Vec<u8>,
+    pub docking: Docking,
+}
+
+impl Survivor {
+    pub fn hash(&self) -> String {
+        sha256_hex(&self.data)
+    }
+}
+
+/// One manifest entry.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SurvivorRecord {
+    pub hash: String,
+    pub size: u64,
+    pub docked_at: String,
+    /// Times this exact content was docked; only the first docking's lineage is kept.
+    pub sightings: u32,
+    #[serde(flatten)]
+    pub docking: Docking,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Manifest {
+    version: u32,
+    survivors: BTreeMap<String, SurvivorRecord>,
+}
+
+impl Default for Manifest {
+    fn default() -> Self {
+        Self {
+            version: MANIFEST_VERSION,
+            survivors: BTreeMap::new(),
+        }
+    }
+}
+
+/// Result of [`SurvivorStore::dock`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Docked {
+    pub hash: String,
+    /// False when the content was already in the store.
+    pub new: bool,
+}
+
+/// What [`SurvivorStore::verify`] found. Clean when every list is empty.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VerifyReport {
+    pub checked: usize,
+    /// Objects whose bytes no longer hash to their name, and manifest hashes that are
+    /// not SHA-256 hashes at all.
+    pub corrupt: Vec<String>,
+    /// Manifest entries without an object.
+    pub missing: Vec<String>,
+    /// Entries whose parent is not in the manifest.
+    pub orphaned: Vec<String>,
+    /// Objects on disk the manifest does not know.
+    pub strays: Vec<PathBuf>,
+}
+
+impl VerifyReport {
+    pub fn is_clean(&self) -> bool {
+        self.corrupt.is_empty()
+            && self.missing.is_empty()
+            && self.orphaned.is_empty()
+            && self.strays.is_empty()
+    }
+}
+
+pub struct SurvivorStore {
+    root: PathBuf,
+    manifest: Manifest,
+}
+
+impl SurvivorStore {
+    /// `AMAZON_LILY_PATH`, or [`DEFAULT_ROOT`].
+    pub fn default_root() -> PathBuf {
+        std::env::var_os("AMAZON_LILY_PATH")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_ROOT))
+    }
+
+    /// Opens (creating if needed) the store at `root`.
+    pub fn open(root: impl AsRef<Path>) -> io::Result<Self> {
+        let root = root.as_ref().to_path_buf();
+        fs::create_dir_all(root.join("objects"))?;
+        let manifest = match fs::read_to_string(root.join(MANIFEST)) {
+            Ok(json) => serde_json::from_str(&json)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Manifest::default(),
+            Err(e) => return Err(e),
+        };
+        Ok(Self { root, manifest })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn len(&self) -> usize {
+        self.manifest.survivors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.manifest.survivors.is_empty()
+    }
+
+    pub fn records(&self) -> impl Iterator<Item = &SurvivorRecord> {
+        self.manifest.survivors.values()
+    }
+
+    pub fn get(&self, hash: &str) -> Option<&SurvivorRecord> {
+        self.manifest.survivors.get(hash)
+    }
+
+    /// Resolves a unique hash prefix (as printed in logs) to a full hash.
+    pub fn resolve(&self, prefix: &str) -> Option<&str> {
+        if !is_lower_hex(prefix) {
+            return None;
+        }
+        let mut matches = self
+            .manifest
+            .survivors
+            .range(prefix.to_string()..)
+            .take_while(|(hash, _)| hash.starts_with(prefix));
+        match (matches.next(), matches.next()) {
+            (Some((hash, _)), None) => Some(hash),
+            _ => None,
+        }
+    }
+
+    /// Where the object for `hash` lives. Refuses anything but a full lowercase SHA-256.
+    pub fn object_path(&self, hash: &str) -> io::Result<PathBuf> {
+        if hash.len() != 64 || !is_lower_hex(hash) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{:?} is not a SHA-256 hash", hash),
+            ));
+        }
+        let (fan, rest) = hash.split_at(2);
+        Ok(self
+            .root
+            .join("objects")
+            .join(fan)
+            .join(format!("{}.cube", rest)))
+    }
+
+    /// Stores `data` (once) and records how it was made. Empty buffers are refused.
+    pub fn dock(&mut self, data: &[u8], docking: Docking) -> io::Result<Docked> {
+        if data.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "refusing to dock an empty hypercube",
+            ));
+        }
+        let hash = sha256_hex(data);
+        if let Some(record) = self.manifest.survivors.get_mut(&hash) {
+            record.sightings += 1;
+            self.save_manifest()?;
+            return Ok(Docked { hash, new: false });
+        }
+
+        let path = self.object_path(&hash)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        write_atomic(&path, data)?;
+
+        self.manifest.survivors.insert(
+            hash.clone(),
+            SurvivorRecord {
+                hash: hash.clone(),
+                size: data.len() as u64,
+                docked_at: Utc::now().to_rfc3339(),
+                sightings: 1,
+                docking,
+            },
+        );
+        self.save_manifest()?;
+        Ok(Docked { hash, new: true })
+    }
+
+    /// Reads a survivor back, refusing bytes that no longer match their hash.
+    pub fn load(&self, hash: &str) -> io::Result<Vec<u8>> {
+        let data = fs::read(self.object_path(hash)?)?;
+        if sha256_hex(&data) != hash {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("object {} is corrupt", hash),
+            ));
+        }
+        Ok(data)
+    }
+
+    /// `hash` and its ancestors, newest first, ending at the baseline (or where the
+    /// chain breaks).
+    pub fn lineage(&self, hash: &str) -> Vec<&SurvivorRecord> {
+        let mut chain = Vec::new();
+        let mut seen = HashSet::new();
+        let mut next = Some(hash);
+        while let Some(hash) = next {
+            let Some(record) = self.get(hash) else { break };
+            if !seen.insert(hash) {
+                break;
+            }
+            chain.push(record);
+            next = record.docking.parent.as_deref();
+        }
+        chain
+    }
+
+    /// Re-hashes every object and checks every parent link.
+    pub fn verify(&self) -> io::Result<VerifyReport> {
+        let mut report = VerifyReport::default();
+        for record in self.records() {
+            report.checked += 1;
+            // A manifest hash that cannot name an object is corrupt in itself.
+            match self.object_path(&record.hash).and_then(fs::read) {
+                Ok(data) if sha256_hex(&data) == record.hash => {}
+                Err(e) if e.kind() == io::ErrorKind::InvalidInput => {
+                    report.corrupt.push(record.hash.clone())
+                }
+                Ok(_) => report.corrupt.push(record.hash.clone()),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                    report.missing.push(record.hash.clone())
+                }
+                Err(e) => return Err(e),
+            }
+            if let Some(parent) = &record.docking.parent {
+                if self.get(parent).is_none() {
+                    report.orphaned.push(record.hash.clone());
+                }
+            }
+        }
+
+        for fan in fs::read_dir(self.root.join("objects"))? {
+            let fan = fan?;
+            if !fan.file_type()?.is_dir() {
+                continue;
+            }
+            for object in fs::read_dir(fan.path())? {
+                let path = object?.path();
+                let name = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+                let hash = format!("{}{}", fan.file_name().to_string_lossy(), name);
+                if self.get(&hash).is_none() {
+                    report.strays.push(path);
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    fn save_manifest(&self) -> io::Result<()> {
+        let json = serde_json::to_vec_pretty(&self.manifest)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        write_atomic(&self.root.join(MANIFEST), &json)
+    }
+}
+
+/// Writes via a temporary sibling and a rename, so a crash never leaves half a file.
+fn write_atomic(path: &Path, data: &[u8]) -> io::Result<()> {
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, data)?;
+    fs::rename(&tmp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_docking_dedups_and_traces_lineage() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let mut store = SurvivorStore::open(root).unwrap();
+
+        let base = store.dock(b"baseline", Docking::baseline(1337)).unwrap();
+        assert!(base.new);
+        let child = store
+            .dock(
+                b"mutant",
+                Docking {
+                    seed: 1338,
+                    cycle: Some(0),
+                    parent: Some(base.hash.clone()),
+                    grade: Some(Grade::Partial),
+                    mutation: None,
+                },
+            )
+            .unwrap();
+        let again = store.dock(b"mutant", Docking::default()).unwrap();
+        assert_eq!(
+            again,
+            Docked {
+                hash: child.hash.clone(),
+                new: false
+            }
+        );
+        assert_eq!(store.len(), 2);
+        assert_eq!(store.get(&child.hash).unwrap().sightings, 2);
+        assert!(store.dock(b"", Docking::default()).is_err());
+
+        // Reopening reads the manifest back.
+        let store = SurvivorStore::open(root).unwrap();
+        let chain: Vec<&str> = store
+            .lineage(&child.hash)
+            .iter()
+            .map(|r| r.hash.as_str())
+            .collect();
+        assert_eq!(chain, [child.hash.as_str(), base.hash.as_str()]);
+        assert_eq!(store.load(&base.hash).unwrap(), b"baseline");
+        assert_eq!(store.resolve(&child.hash[..12]), Some(child.hash.as_str()));
+        assert_eq!(base.hash, sha256_hex(b"baseline"));
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_verify_finds_corruption_and_strays() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let mut store = SurvivorStore::open(root).unwrap();
+        let base = store.dock(b"sunny", Docking::baseline(7)).unwrap();
+        assert!(store.verify().unwrap().is_clean());
+
+        fs::write(store.object_path(&base.hash).unwrap(), b"merry").unwrap();
+        let stray = store.object_path(&sha256_hex(b"stray")).unwrap();
+        fs::create_dir_all(stray.parent().unwrap()).unwrap();
+        fs::write(&stray, b"stray").unwrap();
+
+        let report = store.verify().unwrap();
+        assert_eq!(report.checked, 1);
+        assert_eq!(report.corrupt, [base.hash.as_str()]);
+        assert_eq!(report.strays, [stray]);
+        assert!(store.load(&base.hash).is_err());
+
+        // A hand-edited manifest hash is reported with the rest, not fatal.
+        let manifest = fs::read_to_string(root.join(MANIFEST)).unwrap();
+        fs::write(root.join(MANIFEST), manifest.replace(&base.hash, "../é")).unwrap();
+        let store = SurvivorStore::open(root).unwrap();
+        let report = store.verify().unwrap();
+        assert_eq!(report.checked, 1);
+        assert_eq!(report.corrupt, ["../é"]);
+        assert_eq!(report.strays.len(), 2);
+    }
+
+    #[test]
+    fn test_hashes_from_the_command_line_must_be_lowercase_hex() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let mut store = SurvivorStore::open(root).unwrap();
+        let base = store.dock(b"sunny", Docking::baseline(7)).unwrap();
+
+        // "é" is two bytes, so a two-byte fan would split it.
+        for bad in [
+            "é",
+            "éa",
+            "../",
+            "..",
+            "a/b",
+            "AB",
+            &base.hash.to_uppercase(),
+        ] {
+            assert_eq!(store.resolve(bad), None, "{}", bad);
+            assert!(store.lineage(bad).is_empty());
+            let err = store.object_path(bad).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+            assert!(store.load(bad).is_err());
+        }
+        assert!(store.object_path(&base.hash[..12]).is_err());
+        assert_eq!(store.resolve(&base.hash[..2]), Some(base.hash.as_str()));
+    }
+}
@@ -0,0 +1,596 @@
+//! Listener Collapse: does the hypercube still hold its structure after a shot?
+//!
+//! Each validator grades a buffer as fully valid, partially salvageable (some of its
+//! entries / chunks / members / lines still stand) or collapsed.
+
+use serde::{Deserialize, Serialize};
+use std::io::{self, Cursor, Read};
+use std::path::Path;
+use zip::ZipArchive;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Grade {
+    Valid,
+    Partial,
+    Collapsed,
+}
+
+/// A graded verdict, counted in the validator's own unit (entry, chunk, member, ...).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Assessment {
+    pub validator: &'static str,
+    pub grade: Grade,
+    pub unit: &'static str,
+    pub intact: usize,
+    pub damaged: usize,
+    /// Names of the units that survived, where units have names.
+    pub salvaged: Vec<String>,
+    /// The first damage found.
+    pub reason: Option<String>,
+}
+
+impl Assessment {
+    /// Valid with no damage; Partial if anything survived the damage; Collapsed otherwise.
+    pub fn graded(
+        validator: &'static str,
+        unit: &'static str,
+        salvaged: Vec<String>,
+        intact: usize,
+        damaged: usize,
+        reason: Option<String>,
+    ) -> Self {
+        let grade = if damaged == 0 {
+            Grade::Valid
+        } else if intact > 0 {
+            Grade::Partial
+        } else {
+            Grade::Collapsed
+        };
+        Self {
+            validator,
+            grade,
+            unit,
+            intact,
+            damaged,
+            salvaged,
+            reason,
+        }
+    }
+
+    pub fn collapsed(
+        validator: &'static str,
+        unit: &'static str,
+        reason: impl Into<String>,
+    ) -> Self {
+        Self::graded(validator, unit, Vec::new(), 0, 1, Some(reason.into()))
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.grade == Grade::Valid
+    }
+
+    pub fn survived(&self) -> bool {
+        self.grade != Grade::Collapsed
+    }
+}
+
+/// A structure a railgun run can target.
+pub trait StructureValidator {
+    fn name(&self) -> &'static str;
+
+    /// Whether this validator recognises the file, by magic bytes or extension.
+    fn detect(&self, path: Option<&Path>, data: &[u8]) -> bool;
+
+    fn validate(&self, data: &[u8]) -> Assessment;
+}
+
+fn has_extension(path: Option<&Path>, extensions: &[&str]) -> bool {
+    path.and_then(|p| p.extension())
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| extensions.iter().any(|x| e.eq_ignore_ascii_case(x)))
+}
+
+/// ZIP: the central directory must parse; every entry must then extract with a good CRC.
+pub struct ZipValidator;
+
+impl StructureValidator for ZipValidator {
+    fn name(&self) -> &'static str {
+        "zip"
+    }
+
+    fn detect(&self, path: Option<&Path>, data: &[u8]) -> bool {
+        data.starts_with(b"PK\x03\x04")
+            || data.starts_with(b"PK\x05\x06")
+            || has_extension(path, &["zip", "jar", "apk", "docx", "xlsx"])
+    }
+
+    fn validate(&self, data: &[u8]) -> Assessment {
+        let mut archive = match ZipArchive::new(Cursor::new(data)) {
+            Ok(archive) => archive,
+            Err(e) => return Assessment::collapsed(self.name(), "entry", e.to_string()),
+        };
+
+        let (mut salvaged, mut damaged, mut reason) = (Vec::new(), 0, None);
+        for i in 0..archive.len() {
+            let extracted = archive
+                .by_index(i)
+                .map_err(io::Error::from)
+                .and_then(|mut entry| {
+                    let name = entry.name().to_string();
+                    io::copy(&mut entry, &mut io::sink()).map(|_| name)
+                });
+            match extracted {
+                Ok(name) => salvaged.push(name),
+                Err(e) => {
+                    damaged += 1;
+                    reason.get_or_insert_with(|| format!("entry {}: {}", i, e));
+                }
+            }
+        }
+        let intact = salvaged.len();
+        Assessment::graded(self.name(), "entry", salvaged, intact, damaged, reason)
+    }
+}
+
+/// gzip: every member must inflate and match its CRC32 / ISIZE trailer.
+pub struct GzipValidator;
+
+impl StructureValidator for GzipValidator {
+    fn name(&self) -> &'static str {
+        "gzip"
+    }
+
+    fn detect(&self, path: Option<&Path>, data: &[u8]) -> bool {
+        data.starts_with(&[0x1F, 0x8B]) || has_extension(path, &["gz", "tgz"])
+    }
+
+    fn validate(&self, data: &[u8]) -> Assessment {
+        let (mut salvaged, mut damaged, mut reason) = (Vec::new(), 0, None);
+        let mut rest = data;
+        while !rest.is_empty() {
+            let member = salvaged.len();
+            let mut decoder = flate2::bufread::GzDecoder::new(rest);
+            let mut inflated = 0u64;
+            let mut buf = [0u8; 8192];
+            let result = loop {
+                match decoder.read(&mut buf) {
+                    Ok(0) => break Ok(()),
+                    Ok(n) => inflated += n as u64,
+                    Err(e) => break Err(e),
+                }
+            };
+            match result {
+                Ok(()) => {
+                    salvaged.push(format!("member {} ({} bytes)", member, inflated));
+                    rest = decoder.into_inner();
+                }
+                Err(e) => {
+                    damaged += 1;
+                    reason = Some(format!("member {}: {} after {} bytes", member, e, inflated));
+                    break;
+                }
+            }
+        }
+        if salvaged.is_empty() && damaged == 0 {
+            return Assessment::collapsed(self.name(), "member", "empty stream");
+        }
+        let intact = salvaged.len();
+        Assessment::graded(self.name(), "member", salvaged, intact, damaged, reason)
+    }
+}
+
+/// tar: walks 512-byte headers, checking each header checksum and that its data fits.
+/// Entry contents carry no checksum in tar, so damage there cannot be seen.
+pub struct TarValidator;
+
+impl TarValidator {
+    const BLOCK: usize = 512;
+
+    fn octal(field: &[u8]) -> Option<u64> {
+        let text = std::str::from_utf8(field).ok()?;
+        let text = text.trim_matches(|c: char| c == '\0' || c == ' ');
+        if text.is_empty() {
+            return Some(0);
+        }
+        u64::from_str_radix(text, 8).ok()
+    }
+
+    fn checksum_ok(header: &[u8]) -> bool {
+        let sum: u64 = header
+            .iter()
+            .enumerate()
+            .map(|(i, &b)| {
+                if (148..156).contains(&i) {
+                    b' ' as u64
+                } else {
+                    b as u64
+                }
+            })
+            .sum();
+        Self::octal(&header[148..156]) == Some(sum)
+    }
+
+    fn entry_name(header: &[u8]) -> String {
+        let field = |bytes: &[u8]| {
+            let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+            String::from_utf8_lossy(&bytes[..end]).into_owned()
+        };
+        let name = field(&header[..100]);
+        let prefix = if &header[257..262] == b"ustar" {
+            field(&header[345..500])
+        } else {
+            String::new()
+        };
+        if prefix.is_empty() {
+            name
+        } else {
+            format!("{}/{}", prefix, name)
+        }
+    }
+}
+
+impl StructureValidator for TarValidator {
+    fn name(&self) -> &'static str {
+        "tar"
+    }
+
+    fn detect(&self, path: Option<&Path>, data: &[u8]) -> bool {
+        data.get(257..262) == Some(b"ustar") || has_extension(path, &["tar"])
+    }
+
+    fn validate(&self, data: &[u8]) -> Assessment {
+        let (mut salvaged, mut damaged, mut reason) = (Vec::new(), 0, None);
+        let mut offset = 0;
+        while offset + Self::BLOCK <= data.len() {
+            let header = &data[offset..offset + Self::BLOCK];
+            if header.iter().all(|&b| b == 0) {
+                break; // End-of-archive marker
+            }
+            if !Self::checksum_ok(header) {
+                damaged += 1;
+                reason = Some(format!("header checksum mismatch at byte {}", offset));
+                break;
+            }
+            let name = Self::entry_name(header);
+            let Some(size) = Self::octal(&header[124..136]) else {
+                damaged += 1;
+                reason = Some(format!("{}: unreadable size", name));
+                break;
+            };
+            let blocks = (size as usize).div_ceil(Self::BLOCK);
+            let next = offset + Self::BLOCK + blocks * Self::BLOCK;
+            if next > data.len() {
+                damaged += 1;
+                reason = Some(format!("{}: truncated", name));
+                break;
+            }
+            salvaged.push(name);
+            offset = next;
+        }
+        if salvaged.is_empty() && damaged == 0 {
+            return Assessment::collapsed(self.name(), "entry", "no tar headers");
+        }
+        let intact = salvaged.len();
+        Assessment::graded(self.name(), "entry", salvaged, intact, damaged, reason)
+    }
+}
+
+/// PNG: signature, then every chunk's CRC; IHDR must come first and IEND must close it.
+pub struct PngValidator;
+
+impl PngValidator {
+    const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+}
+
+impl StructureValidator for PngValidator {
+    fn name(&self) -> &'static str {
+        "png"
+    }
+
+    fn detect(&self, path: Option<&Path>, data: &[u8]) -> bool {
+        data.starts_with(&Self::SIGNATURE) || has_extension(path, &["png"])
+    }
+
+    fn validate(&self, data: &[u8]) -> Assessment {
+        if !data.starts_with(&Self::SIGNATURE) {
+            return Assessment::collapsed(self.name(), "chunk", "bad PNG signature");
+        }
+
+        let (mut salvaged, mut damaged, mut reason) = (Vec::new(), 0, None);
+        let mut offset = Self::SIGNATURE.len();
+        let mut closed = false;
+        while offset + 12 <= data.len() {
+            let len = u32::from_be_bytes(data[offset..offset + 4].try_into().unwrap()) as usize;
+            let end = offset + 12 + len;
+            if end > data.len() {
+                damaged += 1;
+                reason = Some(format!("chunk at byte {} overruns the file", offset));
+                break;
+            }
+            let kind = String::from_utf8_lossy(&data[offset + 4..offset + 8]).into_owned();
+            let stored = u32::from_be_bytes(data[end - 4..end].try_into().unwrap());
+            if crc32fast::hash(&data[offset + 4..end - 4]) == stored {
+                salvaged.push(format!("{}@{}", kind, offset));
+            } else {
+                damaged += 1;
+                reason.get_or_insert_with(|| format!("{} CRC mismatch at byte {}", kind, offset));
+            }
+            if salvaged.is_empty() {
+                break; // Without a sound first chunk (IHDR) nothing else can be read
+            }
+            offset = end;
+            if kind == "IEND" {
+                closed = true;
+                break;
+            }
+        }
+        if !closed && damaged == 0 {
+            damaged += 1;
+            reason = Some("missing IEND".to_string());
+        }
+        if !salvaged.first().is_some_and(|c| c.starts_with("IHDR@")) {
+            return Assessment::collapsed(
+                self.name(),
+                "chunk",
+                reason.unwrap_or_else(|| "IHDR is not the first chunk".to_string()),
+            );
+        }
+        let intact = salvaged.len();
+        Assessment::graded(self.name(), "chunk", salvaged, intact, damaged, reason)
+    }
+}
+
+/// JSON: one document, or a stream of them (JSON Lines); counts values parsed before damage.
+pub struct JsonValidator;
+
+impl StructureValidator for JsonValidator {
+    fn name(&self) -> &'static str {
+        "json"
+    }
+
+    fn detect(&self, path: Option<&Path>, data: &[u8]) -> bool {
+        has_extension(path, &["json", "jsonl", "ndjson", "geojson"])
+            || matches!(
+                data.iter().find(|b| !b.is_ascii_whitespace()),
+                Some(b'{') | Some(b'[')
+            )
+    }
+
+    fn validate(&self, data: &[u8]) -> Assessment {
+        let (mut intact, mut damaged, mut reason) = (0, 0, None);
+        let stream = serde_json::Deserializer::from_slice(data).into_iter::<serde_json::Value>();
+        for value in stream {
+            match value {
+                Ok(_) => intact += 1,
+                Err(e) => {
+                    damaged += 1;
+                    reason = Some(e.to_string());
+                    break;
+                }
+            }
+        }
+        if intact == 0 && damaged == 0 {
+            return Assessment::collapsed(self.name(), "value", "no JSON value");
+        }
+        Assessment::graded(self.name(), "value", Vec::new(), intact, damaged, reason)
+    }
+}
+
+/// UTF-8 text: counts the lines that still decode.
+pub struct Utf8Validator;
+
+impl StructureValidator for Utf8Validator {
+    fn name(&self) -> &'static str {
+        "utf8"
+    }
+
+    fn detect(&self, path: Option<&Path>, data: &[u8]) -> bool {
+        has_extension(path, &["txt", "md", "csv", "log", "toml", "yaml", "yml"])
+            || std::str::from_utf8(data).is_ok()
+    }
+
+    fn validate(&self, data: &[u8]) -> Assessment {
+        if let Err(e) = std::str::from_utf8(data) {
+            let (intact, damaged) =
+                data.split(|&b| b == b'\n').fold(
+                    (0, 0),
+                    |(ok, bad), line| match std::str::from_utf8(line) {
+                        Ok(_) => (ok + 1, bad),
+                        Err(_) => (ok, bad + 1),
+                    },
+                );
+            let reason = format!("invalid UTF-8 at byte {}", e.valid_up_to());
+            return Assessment::graded(
+                self.name(),
+                "line",
+                Vec::new(),
+                intact,
+                damaged,
+                Some(reason),
+            );
+        }
+        let lines = data.split(|&b| b == b'\n').count();
+        Assessment::graded(self.name(), "line", Vec::new(), lines, 0, None)
+    }
+}
+
+/// The validators a run can pick from, in detection order.
+pub struct ValidatorRegistry {
+    validators: Vec<Box<dyn StructureValidator>>,
+}
+
+impl Default for ValidatorRegistry {
+    fn default() -> Self {
+        Self {
+            validators: vec![
+                Box::new(PngValidator),
+                Box::new(GzipValidator),
+                Box::new(ZipValidator),
+                Box::new(TarValidator),
+                Box::new(JsonValidator),
+                Box::new(Utf8Validator),
+            ],
+        }
+    }
+}
+
+impl ValidatorRegistry {
+    pub fn register(&mut self, validator: Box<dyn StructureValidator>) {
+        self.validators.push(validator);
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.validators.iter().map(|v| v.name()).collect()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn StructureValidator> {
+        self.validators
+            .iter()
+            .find(|v| v.name() == name)
+            .map(|v| v.as_ref())
+    }
+
+    /// The first validator that recognises the file; ZIP when none does.
+    pub fn detect(&self, path: Option<&Path>, data: &[u8]) -> &dyn StructureValidator {
+        self.validators
+            .iter()
+            .find(|v| v.detect(path, data))
+            .or_else(|| self.validators.iter().find(|v| v.name() == "zip"))
+            .map(|v| v.as_ref())
+            .unwrap_or(&ZipValidator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn zip_of(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options =
+            zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+        for (name, body) in entries {
+            writer.start_file(*name, options).unwrap();
+            writer.write_all(body).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    fn png_chunk(kind: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut chunk = (body.len() as u32).to_be_bytes().to_vec();
+        chunk.extend_from_slice(kind);
+        chunk.extend_from_slice(body);
+        chunk.extend_from_slice(&crc32fast::hash(&chunk[4..]).to_be_bytes());
+        chunk
+    }
+
+    #[test]
+    fn test_zip_grades_entries_that_still_extract() {
+        let mut data = zip_of(&[
+            ("keel.txt", b"going merry"),
+            ("sail.txt", b"thousand sunny"),
+        ]);
+        let registry = ValidatorRegistry::default();
+        let zip = registry.detect(None, &data);
+        assert_eq!(zip.name(), "zip");
+        assert!(zip.validate(&data).is_valid());
+
+        // Corrupt the stored body of the second entry: its CRC fails, the first survives.
+        let at = data.windows(8).position(|w| w == b"thousand").unwrap();
+        data[at] ^= 0x20;
+        let assessment = zip.validate(&data);
+        assert_eq!(assessment.grade, Grade::Partial);
+        assert_eq!(assessment.salvaged, ["keel.txt"]);
+        assert_eq!((assessment.intact, assessment.damaged), (1, 1));
+        // The legacy check only opens the archive, so it still counts this one as survived.
+        assert!(crate::railgun::listener_collapse(&data));
+
+        assert_eq!(zip.validate(b"not a zip").grade, Grade::Collapsed);
+        assert!(!crate::railgun::listener_collapse(b"not a zip"));
+    }
+
+    #[test]
+    fn test_png_checks_every_chunk_crc() {
+        let mut data = PngValidator::SIGNATURE.to_vec();
+        data.extend(png_chunk(b"IHDR", &[0, 0, 0, 1, 0, 0, 0, 1, 8, 0, 0, 0, 0]));
+        data.extend(png_chunk(b"IDAT", b"pixels"));
+        data.extend(png_chunk(b"IEND", b""));
+
+        let png = PngValidator;
+        assert!(png.detect(None, &data));
+        let valid = png.validate(&data);
+        assert!(valid.is_valid());
+        assert_eq!(valid.intact, 3);
+
+        let mut hit = data.clone();
+        let idat = hit.windows(6).position(|w| w == b"pixels").unwrap();
+        hit[idat] ^= 1;
+        let assessment = png.validate(&hit);
+        assert_eq!(assessment.grade, Grade::Partial);
+        assert_eq!((assessment.intact, assessment.damaged), (2, 1));
+
+        data[12] ^= 1; // IHDR's type byte
+        assert_eq!(png.validate(&data).grade, Grade::Collapsed);
+    }
+
+    #[test]
+    fn test_gzip_and_tar_grade_members_and_headers() {
+        let member = |text: &[u8]| {
+            let mut enc = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+            enc.write_all(text).unwrap();
+            enc.finish().unwrap()
+        };
+        let mut data = member(b"first");
+        let second = data.len();
+        data.extend(member(b"second"));
+        let registry = ValidatorRegistry::default();
+        let gzip = registry.detect(Some(Path::new("log.gz")), &data);
+        assert_eq!(gzip.name(), "gzip");
+        assert_eq!(gzip.validate(&data).intact, 2);
+        let last = data.len() - 5; // Inside the second member's CRC32 trailer
+        data[last] ^= 0xFF;
+        let assessment = gzip.validate(&data);
+        assert_eq!(assessment.grade, Grade::Partial);
+        assert_eq!(assessment.salvaged, ["member 0 (5 bytes)"]);
+        data.truncate(second);
+        assert!(gzip.validate(&data).is_valid());
+
+        let mut header = [0u8; 512];
+        header[..8].copy_from_slice(b"log.txt\0");
+        header[124..136].copy_from_slice(b"00000000004\0");
+        header[257..263].copy_from_slice(b"ustar\0");
+        header[148..156].fill(b' ');
+        let sum: u32 = header.iter().map(|&b| b as u32).sum();
+        header[148..155].copy_from_slice(format!("{:06o}\0", sum).as_bytes());
+        let mut tar = header.to_vec();
+        tar.extend_from_slice(&[b'x'; 512]);
+        tar.extend(header);
+        tar.extend_from_slice(&[0; 1536]);
+        let validator = registry.detect(None, &tar);
+        assert_eq!(validator.name(), "tar");
+        assert_eq!(validator.validate(&tar).intact, 2);
+        tar[1024 + 3] ^= 1; // Second header's name
+        let assessment = validator.validate(&tar);
+        assert_eq!(assessment.grade, Grade::Partial);
+        assert_eq!(assessment.salvaged, ["log.txt"]);
+    }
+
+    #[test]
+    fn test_text_validators_count_values_and_lines() {
+        let registry = ValidatorRegistry::default();
+        let json = registry.get("json").unwrap();
+        assert!(json.validate(br#"{"crew": 16}"#).is_valid());
+        let lines = b"{\"a\":1}\n{\"b\":2}\n{\"c\":";
+        let assessment = json.validate(lines);
+        assert_eq!((assessment.grade, assessment.intact), (Grade::Partial, 2));
+        assert_eq!(json.validate(b"}{").grade, Grade::Collapsed);
+
+        let utf8 = registry.detect(Some(Path::new("notes.txt")), b"ok");
+        assert_eq!(utf8.name(), "utf8");
+        let assessment = utf8.validate(b"line one\nline \xFF two\nline three");
+        assert_eq!(assessment.grade, Grade::Partial);
+        assert_eq!((assessment.intact, assessment.damaged), (2, 1));
+    }
+}
@@ -0,0 +1,28 @@
+#!/bin/bash
+# Description: Check that spectral_sensor's gate core still builds and matches without std
+#
+# Without the `std` feature the library is `#![no_std]` and alloc-free, so these builds
+# fail on any std or alloc use in the core. The golden digest test in gate_core.rs holds
+# the value the std build produces; the no_std test run must reproduce it.
+set -e
+
+cd "$(dirname "$0")/../modules/spectral_sensor"
+
+echo "🔧 no_std library build (host)..."
+cargo build --no-default-features --lib
+
+# A bare-metal target has no std at all, so it also catches dependencies that pull it in.
+TARGET=thumbv7em-none-eabihf
+if rustup target list --installed 2>/dev/null | grep -qx "$TARGET"; then
+    echo "🔧 no_std library build ($TARGET)..."
+    cargo build --no-default-features --lib --target "$TARGET"
+else
+    echo "⚠️  $TARGET not installed; skipping the bare-metal build (rustup target add $TARGET)."
+fi
+
+echo "🧪 Tests with std..."
+cargo test
+echo "🧪 Tests without std (golden digest against the no_std core)..."
+cargo test --no-default-features
+
+echo "✅ spectral_sensor core is no_std and matches the std build."